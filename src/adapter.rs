@@ -0,0 +1,104 @@
+//! Adapters for tools that are run through the usual commands of a
+//! language ecosystem (e.g., `cargo run --release --`), declared with a
+//! `kind` and a `target` rather than a `program`. Anything that needs
+//! building is built once, before the tool's first run, so that no run
+//! pays for (or even checks) the build.
+
+use crate::Program;
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// A binary target of the Cargo package in the current directory
+    Cargo,
+    /// A script of the npm package in the current directory
+    Npm,
+    /// A Python module, run with `python3 -m`
+    Python,
+}
+
+impl Kind {
+    /// Executable of the ecosystem's toolchain, which accepts `--version`
+    pub fn toolchain(self) -> &'static str {
+        match self {
+            Kind::Cargo => "cargo",
+            Kind::Npm => "npm",
+            Kind::Python => "python3",
+        }
+    }
+
+    /// The program that runs `target` through the toolchain
+    pub fn program(self, target: &str) -> Program {
+        let argv: &[&str] = match self {
+            Kind::Cargo => &[
+                "cargo",
+                "run",
+                "--release",
+                "--quiet",
+                "--bin",
+                target,
+                "--",
+            ],
+            Kind::Npm => &["npm", "run", "--silent", target, "--"],
+            Kind::Python => &["python3", "-m", target],
+        };
+        Program::Argv(argv.iter().map(|a| a.to_string()).collect())
+    }
+
+    /// Builds `target` (with the environment variables `env`), returning
+    /// the program that runs the build directly (rather than through the
+    /// toolchain), if there is one
+    pub fn build(
+        self,
+        target: &str,
+        env: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Option<Program>> {
+        match self {
+            Kind::Cargo => {
+                let output = std::process::Command::new("cargo")
+                    .args(["build", "--release", "--bin", target])
+                    .arg("--message-format=json-render-diagnostics")
+                    .envs(env)
+                    .stdin(std::process::Stdio::null())
+                    .output()
+                    .map_err(|e| eyre!("Could not run cargo: {}", e))?;
+                if !output.status.success() {
+                    return Err(eyre!(
+                        "Could not build {} with cargo: {}",
+                        target,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+                let executable = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+                    .filter(|message| message["reason"] == "compiler-artifact")
+                    .filter(|message| message["target"]["name"] == target)
+                    .find_map(|message| message["executable"].as_str().map(str::to_string))
+                    .ok_or_else(|| eyre!("Cargo built no executable for {}", target))?;
+                Ok(Some(Program::Executable(executable)))
+            }
+            Kind::Npm => Ok(None),
+            Kind::Python => {
+                // Byte-compiled up front, so that the first runs do not
+                // write the bytecode caches that later ones read
+                let output = std::process::Command::new("python3")
+                    .args(["-m", "compileall", "-q", "."])
+                    .envs(env)
+                    .stdin(std::process::Stdio::null())
+                    .output()
+                    .map_err(|e| eyre!("Could not run python3: {}", e))?;
+                if !output.status.success() {
+                    return Err(eyre!(
+                        "Could not byte-compile {} with python3: {}",
+                        target,
+                        String::from_utf8_lossy(&output.stdout).trim()
+                    ));
+                }
+                Ok(None)
+            }
+        }
+    }
+}
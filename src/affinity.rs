@@ -0,0 +1,84 @@
+/// The CPUs that this process is allowed to run on
+#[cfg(target_os = "linux")]
+pub fn usable_cpus() -> Vec<usize> {
+    // SAFETY: `set` is a valid, zero-initialized CPU set of the size
+    // passed in.
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let res = unsafe { libc::sched_getaffinity(0, std::mem::size_of_val(&set), &mut set) };
+    if res != 0 {
+        return (0..num_cpus::get()).collect();
+    }
+    (0..libc::CPU_SETSIZE as usize)
+        .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn usable_cpus() -> Vec<usize> {
+    (0..num_cpus::get()).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn cpu_set(cpus: &[usize]) -> libc::cpu_set_t {
+    // SAFETY: `set` is a valid, zero-initialized CPU set, and
+    // `CPU_SET` is only called with in-range CPUs.
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for cpu in cpus.iter().filter(|c| **c < libc::CPU_SETSIZE as usize) {
+        unsafe { libc::CPU_SET(*cpu, &mut set) };
+    }
+    set
+}
+
+/// Makes `command` run pinned to `cpus`. Only supported on Linux;
+/// elsewhere, this does nothing (macOS, in particular, has no way to pin
+/// a process, only affinity hints for threads of the same process).
+#[cfg(target_os = "linux")]
+pub fn pin(command: &mut std::process::Command, cpus: &[usize]) {
+    use std::os::unix::process::CommandExt;
+
+    let set = cpu_set(cpus);
+    // SAFETY: `sched_setaffinity` is async-signal-safe, and the closure
+    // does not allocate.
+    unsafe {
+        command.pre_exec(move || {
+            if libc::sched_setaffinity(0, std::mem::size_of_val(&set), &set) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin(_command: &mut std::process::Command, _cpus: &[usize]) {}
+
+/// Pins the calling thread to `cpu`, as far as possible. Only supported
+/// on Linux; elsewhere, this does nothing.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpu: usize) {
+    let set = cpu_set(&[cpu]);
+    // SAFETY: `set` is a valid CPU set of the size passed in. A failure
+    // only leaves the thread unpinned.
+    unsafe { libc::sched_setaffinity(0, std::mem::size_of_val(&set), &set) };
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_cpu: usize) {}
+
+/// What is wrong with pinning runs to `cpus`, if anything
+pub fn problem(cpus: &[usize]) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return Some("pinning to CPUs is only supported on Linux".to_string());
+    }
+    if cpus.is_empty() {
+        return Some("no CPUs to pin to".to_string());
+    }
+    let usable = usable_cpus();
+    let unusable: Vec<&usize> = cpus.iter().filter(|c| !usable.contains(c)).collect();
+    (!unusable.is_empty()).then(|| {
+        format!(
+            "CPUs {:?} are not available to benchify, which may only use {:?}",
+            unusable, usable
+        )
+    })
+}
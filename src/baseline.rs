@@ -0,0 +1,127 @@
+//! Named snapshots of results (`--save-baseline`), that later runs can
+//! be compared against (`--load-baseline`)
+
+use crate::{metadata::Metadata, BenchifyResults, Statistics};
+use color_eyre::eyre::{eyre, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Timings of the measured runs of each cell, keyed by cell ID and
+/// machine class
+type Timings = BTreeMap<(String, Option<String>), Vec<std::time::Duration>>;
+
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    pub name: String,
+    pub metadata: Option<Metadata>,
+    timings: Timings,
+}
+
+/// Directory that the baseline `name` is stored in
+fn dir(results_dir: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(std::path::is_separator) || name.starts_with('.') {
+        return Err(eyre!("Invalid baseline name {:?}", name));
+    }
+    Ok(results_dir.join("baselines").join(name))
+}
+
+/// Stores `results` as the baseline `name`, replacing any previous
+/// baseline of that name
+pub fn save(results: &BenchifyResults, results_dir: &Path, name: &str) -> Result<()> {
+    let dir = dir(results_dir, name)?;
+    std::fs::create_dir_all(&dir)?;
+    results.write_data_csv(std::fs::File::create(dir.join("data.csv"))?)?;
+    std::fs::write(
+        dir.join("metadata.json"),
+        serde_json::to_string_pretty(&results.metadata)? + "\n",
+    )?;
+    Ok(())
+}
+
+impl Baseline {
+    pub fn load(results_dir: &Path, name: &str) -> Result<Self> {
+        let dir = dir(results_dir, name)?;
+        if !dir.is_dir() {
+            return Err(eyre!(
+                "No baseline named {:?} (looked in {:?}). Save one with --save-baseline.",
+                name,
+                dir
+            ));
+        }
+        let mut timings = Timings::new();
+        for r in crate::read_data_csv(&dir.join("data.csv"))? {
+            if !r.sample.warmup {
+                timings
+                    .entry((r.cell_id(), r.machine_class.clone()))
+                    .or_default()
+                    .push(r.sample.timing);
+            }
+        }
+        let metadata = std::fs::read_to_string(dir.join("metadata.json"))
+            .ok()
+            .and_then(|m| serde_json::from_str(&m).ok());
+        Ok(Baseline {
+            name: name.to_string(),
+            metadata,
+            timings,
+        })
+    }
+
+    /// The note recorded with the baseline (with `--note`), formatted to
+    /// follow its name, or nothing if there is none
+    pub fn formatted_note(&self) -> String {
+        match self.metadata.as_ref().and_then(|m| m.note.as_ref()) {
+            Some(note) => format!(" ({})", note),
+            None => String::new(),
+        }
+    }
+
+    /// Timings of the measured runs of the cell `id`, on `machine_class`
+    pub fn timings(&self, id: &str, machine_class: Option<&str>) -> Option<&[std::time::Duration]> {
+        self.timings
+            .get(&(id.to_string(), machine_class.map(str::to_string)))
+            .map(Vec::as_slice)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Regression,
+    Improvement,
+    NoChange,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Regression => write!(f, "REGRESSION"),
+            Verdict::Improvement => write!(f, "improvement"),
+            Verdict::NoChange => write!(f, "~"),
+        }
+    }
+}
+
+/// Whether going from `base` to `new` is a regression or an
+/// improvement: the central values (`base_central`, `new_central`) must
+/// differ by more than a fraction `threshold`, and the means by more
+/// than twice the standard error of their difference, so that noise is
+/// not reported as a change.
+pub fn verdict(
+    base: &Statistics,
+    base_central: std::time::Duration,
+    new: &Statistics,
+    new_central: std::time::Duration,
+    threshold: f64,
+) -> Verdict {
+    let variance = |s: &Statistics| s.sample_stddev.as_secs_f64().powi(2) / s.count as f64;
+    let standard_error = (variance(base) + variance(new)).sqrt();
+    let mean_difference = new.mean.as_secs_f64() - base.mean.as_secs_f64();
+    let relative_change = new_central.as_secs_f64() / base_central.as_secs_f64() - 1.;
+    if mean_difference.abs() <= 2. * standard_error || relative_change.abs() <= threshold {
+        Verdict::NoChange
+    } else if relative_change > 0. {
+        Verdict::Regression
+    } else {
+        Verdict::Improvement
+    }
+}
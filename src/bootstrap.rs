@@ -0,0 +1,50 @@
+//! Bootstrap confidence intervals of the central value of a (test, tool)
+//! pair. Resamples are drawn from a generator seeded with a recorded
+//! seed, so that an interval is exactly reproducible from the same raw
+//! data.
+
+use std::time::Duration;
+
+/// Number of resamples an interval is estimated from
+const RESAMPLES: usize = 1000;
+
+/// Confidence level of the intervals, in percent
+pub const CONFIDENCE: f64 = 95.;
+
+/// SplitMix64, which is more than random enough to draw resamples with
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// The percentile bootstrap interval of `estimate` over `data`, from
+/// resamples drawn with `seed`
+pub fn interval(
+    data: &[Duration],
+    seed: u64,
+    estimate: impl Fn(&[Duration]) -> Duration,
+) -> (Duration, Duration) {
+    let mut rng = Rng(seed);
+    let mut resample = vec![Duration::ZERO; data.len()];
+    let mut estimates: Vec<Duration> = (0..RESAMPLES)
+        .map(|_| {
+            for x in resample.iter_mut() {
+                *x = data[(rng.next() % data.len() as u64) as usize];
+            }
+            estimate(&resample)
+        })
+        .collect();
+    estimates.sort();
+    let tail = (100. - CONFIDENCE) / 2.;
+    (
+        crate::percentile_of_sorted(&estimates, tail),
+        crate::percentile_of_sorted(&estimates, 100. - tail),
+    )
+}
@@ -0,0 +1,117 @@
+//! Build matrices: one project benchmarked under several build
+//! configurations (e.g., feature flags or optimization levels). Each
+//! build of a tool is registered as a tool of its own, and built once
+//! before any preparation, into a directory of its own that is kept
+//! across invocations, so that incremental builds pick up where the last
+//! invocation left off.
+
+use color_eyre::eyre::{eyre, Result};
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Directory that builds are kept in, one subdirectory per build
+const BUILDS_DIR: &str = "./benchify-builds";
+
+/// A build configuration of a tool
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Build {
+    /// Name of the build, e.g. "lto"
+    pub name: String,
+    /// Shell command that builds the project into `{BUILD_DIR}`
+    pub cmd: String,
+    /// For revisions of `benchify git`, the revision checked out into
+    /// `{BUILD_DIR}`, which the command is then run in
+    #[serde(skip)]
+    pub checkout: Option<String>,
+}
+
+/// A build of a tool, as registered as a tool of its own
+#[derive(Debug)]
+pub struct RegisteredBuild {
+    build: Build,
+    dir: PathBuf,
+}
+
+/// The builds of a tool, and the tools they were registered as, for the
+/// report of their impact
+#[derive(Debug, Clone)]
+pub struct BuildSet {
+    pub tool: String,
+    /// (build name, registered tool name), in the order declared
+    pub builds: Vec<(String, String)>,
+}
+
+impl RegisteredBuild {
+    /// `build` of the tool `tool_id`, in its own directory
+    pub fn new(tool_id: &str, build: &Build) -> Self {
+        let dir = Path::new(BUILDS_DIR)
+            .join(tool_id)
+            .join(crate::slugify(&build.name));
+        RegisteredBuild {
+            build: build.clone(),
+            dir: std::path::absolute(&dir).unwrap_or(dir),
+        }
+    }
+
+    /// `s`, with `{BUILD_DIR}` replaced by the directory of the build
+    pub fn interpolated_into(&self, s: &str) -> String {
+        s.replace("{BUILD_DIR}", &self.dir.to_string_lossy())
+    }
+
+    /// Runs the build command for the tool `tool`, as a shell command
+    /// made by `shell_command`
+    pub fn build(
+        &self,
+        ui: &crate::ui::UiConfig,
+        tool: &str,
+        shell_command: impl Fn(&str) -> std::process::Command,
+    ) -> Result<()> {
+        if let Some(revision) = &self.build.checkout {
+            crate::git::checkout(revision, &self.dir)?;
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let what = format!("[{}] Build", tool);
+        let pb = ui.spinner();
+        pb.set_style(ui.spinner_style());
+        ui.start_ticking(&pb);
+        pb.set_message(&what);
+        let phase = ui.phase(what);
+
+        let cmd = self.interpolated_into(&self.build.cmd);
+        trace!("Building {} with `{}`", tool, cmd);
+        let mut command = shell_command(&cmd);
+        if self.build.checkout.is_some() {
+            command.current_dir(&self.dir);
+        }
+        let mut child = command
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+        let stderr = child.stderr.take().unwrap();
+        let stderr = std::thread::spawn(move || crate::read_capped(stderr, 64 * 1024));
+        let status = loop {
+            match child.try_wait()? {
+                Some(status) => break status,
+                None => {
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                    pb.tick();
+                }
+            }
+        };
+        pb.finish_and_clear();
+        if !status.success() {
+            let stderr = stderr.join().unwrap().unwrap_or_default();
+            error!("COMMAND:\n{}\n\n", cmd);
+            error!("STDERR:\n{}\n\n", String::from_utf8_lossy(&stderr));
+            return Err(eyre!(
+                "Build of {} failed with status code {}",
+                tool,
+                status
+            ));
+        }
+        phase.finish();
+        Ok(())
+    }
+}
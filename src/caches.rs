@@ -0,0 +1,40 @@
+//! Dropping the kernel's caches before each run, with `drop_caches =
+//! true`, so that I/O-heavy tools are benchmarked reading cold from disk
+//! rather than from the page cache. Only supported on Linux, where it
+//! takes the privileges to write to `/proc/sys/vm/drop_caches` (i.e.,
+//! root).
+
+use color_eyre::eyre::{eyre, Result};
+
+#[cfg(target_os = "linux")]
+const DROP_CACHES: &str = "/proc/sys/vm/drop_caches";
+
+/// Why caches could not be dropped, if they could not
+#[cfg(target_os = "linux")]
+pub fn problem() -> Option<String> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(DROP_CACHES)
+        .err()
+        .map(|e| format!("{} cannot be written to ({})", DROP_CACHES, e))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn problem() -> Option<String> {
+    Some("only supported on Linux".to_string())
+}
+
+/// Writes back dirty pages, and then drops the page cache, dentries and
+/// inodes
+#[cfg(target_os = "linux")]
+pub fn drop() -> Result<()> {
+    // Only clean pages are dropped, so everything is written back first
+    // SAFETY: sync takes no arguments, and cannot fail.
+    unsafe { libc::sync() };
+    std::fs::write(DROP_CACHES, "3").map_err(|e| eyre!("Could not drop caches: {}", e))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn drop() -> Result<()> {
+    Err(eyre!("Could not drop caches: only supported on Linux"))
+}
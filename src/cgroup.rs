@@ -0,0 +1,190 @@
+//! Isolation of measured runs in cgroups (v2, Linux only), as configured
+//! in `[cgroup]`: each run is placed in a transient cgroup of its own,
+//! with any configured limits, and what the cgroup accounted for
+//! (including any processes the run left behind) is recorded as metrics
+//! of the run.
+
+use color_eyre::eyre::{eyre, Result};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Cgroup that the transient cgroups are created in, by default
+const DEFAULT_PARENT: &str = "/sys/fs/cgroup/benchify";
+
+/// Period that `cpu_max` is enforced over, in microseconds
+const CPU_PERIOD: u64 = 100_000;
+
+/// Number of cgroups created so far, to name them uniquely
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CgroupConfig {
+    /// Cgroup that the transient cgroups are created in, created if
+    /// missing
+    parent: Option<PathBuf>,
+    /// CPUs' worth of time that a run may use, e.g. 1.5
+    cpu_max: Option<f64>,
+    /// Memory that a run may use, as for `memory.max`, e.g. "512M"
+    memory_max: Option<String>,
+}
+
+impl CgroupConfig {
+    fn parent(&self) -> &Path {
+        self.parent.as_deref().unwrap_or(Path::new(DEFAULT_PARENT))
+    }
+
+    /// Why runs cannot be placed in cgroups, if they cannot
+    pub fn problem(&self) -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return Some("cgroups are only supported on Linux".to_string());
+        }
+        RunCgroup::create(self).err().map(|e| e.to_string())
+    }
+}
+
+/// The transient cgroup of a single run, removed once dropped
+pub struct RunCgroup {
+    dir: PathBuf,
+}
+
+impl RunCgroup {
+    /// Creates a cgroup for a run, with the limits of `config`
+    pub fn create(config: &CgroupConfig) -> Result<Self> {
+        let parent = config.parent();
+        let root = parent
+            .ancestors()
+            .find(|dir| dir.join("cgroup.controllers").is_file())
+            .ok_or(eyre!(
+                "{:?} is not in a cgroup v2 hierarchy (is cgroup2 mounted?)",
+                parent
+            ))?;
+        trace!(
+            "Creating a cgroup in {:?}, of the hierarchy at {:?}",
+            parent,
+            root
+        );
+        std::fs::create_dir_all(parent)
+            .map_err(|e| eyre!("Could not create the cgroup {:?}: {}", parent, e))?;
+        // Limits can only be set on a cgroup if its parent delegates the
+        // controller to it
+        let mut controllers = vec![];
+        if config.cpu_max.is_some() {
+            controllers.push("+cpu");
+        }
+        if config.memory_max.is_some() {
+            controllers.push("+memory");
+        }
+        if !controllers.is_empty() {
+            write(
+                &parent.join("cgroup.subtree_control"),
+                &controllers.join(" "),
+            )
+            .map_err(|e| {
+                eyre!(
+                    "{} (the controllers must also be enabled in the cgroup.subtree_control of \
+                     every cgroup above it)",
+                    e
+                )
+            })?;
+        }
+
+        let dir = parent.join(format!(
+            "run-{}-{}",
+            std::process::id(),
+            RUNS.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir(&dir)
+            .map_err(|e| eyre!("Could not create the cgroup {:?}: {}", dir, e))?;
+        let cgroup = RunCgroup { dir };
+        if let Some(cpus) = config.cpu_max {
+            let quota = (cpus * CPU_PERIOD as f64).round() as u64;
+            write(
+                &cgroup.dir.join("cpu.max"),
+                &format!("{} {}", quota, CPU_PERIOD),
+            )?;
+        }
+        if let Some(memory) = &config.memory_max {
+            write(&cgroup.dir.join("memory.max"), memory)?;
+            // Swapping would let the run exceed the limit, only slower
+            let _ = write(&cgroup.dir.join("memory.swap.max"), "0");
+        }
+        Ok(cgroup)
+    }
+
+    /// Makes `command` start in the cgroup
+    #[cfg(target_os = "linux")]
+    pub fn enter(&self, command: &mut std::process::Command) -> Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::process::CommandExt;
+
+        let procs = std::ffi::CString::new(self.dir.join("cgroup.procs").as_os_str().as_bytes())?;
+        // SAFETY: `open`, `write` and `close` are async-signal-safe, and
+        // the closure does not allocate. Writing 0 to `cgroup.procs`
+        // moves the writing process.
+        unsafe {
+            command.pre_exec(move || {
+                let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                if fd < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let written = libc::write(fd, b"0".as_ptr() as *const libc::c_void, 1);
+                let err = std::io::Error::last_os_error();
+                libc::close(fd);
+                if written != 1 {
+                    return Err(err);
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn enter(&self, _command: &mut std::process::Command) -> Result<()> {
+        Err(eyre!("cgroups are only supported on Linux"))
+    }
+
+    /// What the cgroup accounted for, as metrics. Only called once the
+    /// run has exited.
+    pub fn finish(self) -> Vec<(String, f64)> {
+        let mut metrics = vec![];
+        let read = |file: &str| std::fs::read_to_string(self.dir.join(file)).ok();
+        if let Some(stat) = read("cpu.stat") {
+            let field = |name: &str| {
+                stat.lines()
+                    .find_map(|l| l.strip_prefix(name)?.strip_prefix(' '))
+                    .and_then(|v| v.trim().parse::<f64>().ok())
+            };
+            for (name, field_name) in [
+                ("Cgroup CPU Time (s)", "usage_usec"),
+                ("Cgroup Throttled Time (s)", "throttled_usec"),
+            ] {
+                if let Some(usec) = field(field_name) {
+                    metrics.push((name.to_string(), usec / 1e6));
+                }
+            }
+        }
+        if let Some(peak) = read("memory.peak").and_then(|p| p.trim().parse::<f64>().ok()) {
+            metrics.push(("Cgroup Peak Memory (KiB)".to_string(), peak / 1024.));
+        }
+        metrics
+    }
+}
+
+impl Drop for RunCgroup {
+    fn drop(&mut self) {
+        // Fails if the run left processes behind, which then keep the
+        // cgroup (and its accounting) around for inspection
+        if let Err(e) = std::fs::remove_dir(&self.dir) {
+            trace!("Could not remove the cgroup {:?}: {}", self.dir, e);
+        }
+    }
+}
+
+fn write(path: &Path, value: &str) -> Result<()> {
+    std::fs::write(path, value)
+        .map_err(|e| eyre!("Could not write {:?} to {:?}: {}", value, path, e))
+}
@@ -0,0 +1,145 @@
+//! Exit codes that tell apart the ways an invocation can go wrong, so
+//! that wrapper scripts (e.g., in CI) can branch on them without parsing
+//! logs, as configured in `[ci]`
+
+use serde::{Deserialize, Serialize};
+
+/// A way an invocation can go wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Anything not covered below
+    Error,
+    /// The config could not be loaded, has problems, or does not fit the
+    /// command line
+    ConfigError,
+    /// Some tool could not be executed
+    MissingTool,
+    /// Some (test, tool) pair failed
+    BenchmarkFailure,
+    /// Some (test, tool) pair regressed since the loaded baseline
+    Regression,
+    /// Stopped by Ctrl-C
+    Interrupted,
+    /// Something cast doubt on the results, with `--strict`
+    Warnings,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ExitCodes {
+    config_error: Option<i32>,
+    missing_tool: Option<i32>,
+    benchmark_failure: Option<i32>,
+    regression: Option<i32>,
+    interrupted: Option<i32>,
+    warnings: Option<i32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CiConfig {
+    /// Whether a failed (test, tool) pair fails the invocation
+    fail_on_benchmark_failure: Option<bool>,
+    /// Whether a regression since the loaded baseline fails the
+    /// invocation
+    fail_on_regression: Option<bool>,
+    /// Coefficient of variation of a cell's timings over which
+    /// `--strict` fails the invocation
+    max_cv: Option<f64>,
+    /// Limits on how much slower tools may get since the loaded baseline
+    assertions: Option<Vec<Assertion>>,
+    exit_codes: Option<ExitCodes>,
+}
+
+impl CiConfig {
+    /// Whether `outcome` fails the invocation. Errors always do.
+    pub fn fails_on(&self, outcome: Outcome) -> bool {
+        match outcome {
+            Outcome::BenchmarkFailure => self.fail_on_benchmark_failure.unwrap_or_default(),
+            Outcome::Regression => self.fail_on_regression.unwrap_or_default(),
+            _ => true,
+        }
+    }
+
+    pub fn assertions(&self) -> &[Assertion] {
+        self.assertions.as_deref().unwrap_or(&[])
+    }
+
+    pub fn max_cv(&self) -> f64 {
+        self.max_cv.unwrap_or(0.05)
+    }
+
+    pub fn exit_code(&self, outcome: Outcome) -> i32 {
+        let codes = self.exit_codes.clone().unwrap_or_default();
+        match outcome {
+            Outcome::Error => 1,
+            Outcome::ConfigError => codes.config_error.unwrap_or(2),
+            Outcome::MissingTool => codes.missing_tool.unwrap_or(3),
+            Outcome::BenchmarkFailure => codes.benchmark_failure.unwrap_or(4),
+            Outcome::Regression => codes.regression.unwrap_or(5),
+            Outcome::Interrupted => codes.interrupted.unwrap_or(130),
+            Outcome::Warnings => codes.warnings.unwrap_or(6),
+        }
+    }
+
+    /// Reports `error`, and exits with the code for `outcome`
+    pub fn exit(&self, outcome: Outcome, error: impl std::fmt::Debug) -> ! {
+        eprintln!("Error: {:?}", error);
+        crate::remove_scratch_dir();
+        std::process::exit(self.exit_code(outcome))
+    }
+}
+
+/// A limit on how much slower a tool may get, on each test, since the
+/// loaded baseline, e.g. `main_tool <= 1.05x baseline`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct Assertion {
+    /// Name of the tool, or `main_tool` for the main tool
+    pub tool: String,
+    /// Largest allowed ratio of its central value to the baseline's
+    pub max_ratio: f64,
+}
+
+impl Assertion {
+    pub fn parse(assertion: &str) -> Result<Self, String> {
+        let parsed = assertion.split_once("<=").and_then(|(tool, limit)| {
+            let max_ratio = limit
+                .trim()
+                .strip_suffix("baseline")?
+                .trim_end()
+                .strip_suffix('x')?
+                .trim()
+                .parse()
+                .ok()?;
+            Some(Assertion {
+                tool: tool.trim().to_string(),
+                max_ratio,
+            })
+        });
+        parsed.filter(|a| !a.tool.is_empty()).ok_or_else(|| {
+            format!(
+                "expected `<tool> <= <factor>x baseline`, got {:?}",
+                assertion
+            )
+        })
+    }
+}
+
+impl TryFrom<String> for Assertion {
+    type Error = String;
+
+    fn try_from(assertion: String) -> Result<Self, String> {
+        Assertion::parse(&assertion)
+    }
+}
+
+impl From<Assertion> for String {
+    fn from(assertion: Assertion) -> String {
+        assertion.to_string()
+    }
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <= {}x baseline", self.tool, self.max_ratio)
+    }
+}
@@ -0,0 +1,177 @@
+//! `benchify clean`: pruning of the artifacts that pile up over many
+//! invocations (baselines, crash artifacts, run logs, preparation times,
+//! and the scratch directories of invocations that did not get to remove
+//! them), as per the `[retention]` policy
+
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Age beyond which artifacts are pruned, if not configured
+const DEFAULT_OLDER_THAN: &str = "30d";
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct RetentionConfig {
+    /// Age beyond which artifacts are pruned, e.g. "30d" or "12h"
+    older_than: Option<String>,
+    /// Baselines that are never pruned, however old
+    keep_baselines: Option<Vec<String>>,
+}
+
+/// Time since `path` (or for directories, `marker` in them, if given)
+/// was last modified
+fn age_of(path: &Path, marker: Option<&str>) -> Option<std::time::Duration> {
+    let path = match marker {
+        Some(marker) if path.join(marker).exists() => path.join(marker),
+        _ => path.to_path_buf(),
+    };
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+}
+
+/// Entries of the directory `dir`, or none if it does not exist
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|e| e.path()).collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Whether the process `pid` is still running
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    // SAFETY: plain syscall; signal 0 only checks for existence
+    let res = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    res == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    // `tasklist` lists no processes (but still succeeds) if there is none
+    // with the PID
+    std::process::Command::new("tasklist")
+        .args(["/NH", "/FO", "CSV", "/FI"])
+        .arg(format!("PID eq {}", pid))
+        .output()
+        .map_or(true, |output| {
+            String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid))
+        })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_running(_pid: u32) -> bool {
+    false
+}
+
+/// Everything that has outlived the retention policy, each with a
+/// description of what it is
+fn stale_artifacts(
+    results_dir: &Path,
+    retention: &RetentionConfig,
+    max_age: std::time::Duration,
+) -> Vec<(PathBuf, &'static str)> {
+    let is_stale =
+        |path: &Path, marker: Option<&str>| age_of(path, marker).is_some_and(|age| age > max_age);
+    let mut stale = vec![];
+
+    let kept = retention.keep_baselines.as_deref().unwrap_or_default();
+    for baseline in entries(&results_dir.join("baselines")) {
+        let name = baseline.file_name().unwrap().to_string_lossy().to_string();
+        if baseline.is_dir() && !kept.contains(&name) && is_stale(&baseline, Some("data.csv")) {
+            stale.push((baseline, "baseline"));
+        }
+    }
+
+    // Crash artifacts are in `<tag>/<test id>/<tool id>/crashes/<pid>`
+    for tag in entries(results_dir).into_iter().filter(|p| p.is_dir()) {
+        for test in entries(&tag) {
+            for tool in entries(&test) {
+                for crash in entries(&tool.join("crashes")) {
+                    if is_stale(&crash, None) {
+                        stale.push((crash, "crash artifacts"));
+                    }
+                }
+            }
+        }
+    }
+
+    // Run logs are in `logs/<test id>/<tool id>`
+    for test in entries(&results_dir.join("logs")) {
+        for tool in entries(&test) {
+            if tool.is_dir() && is_stale(&tool, None) {
+                stale.push((tool, "run logs"));
+            }
+        }
+    }
+
+    for prep_time in entries(Path::new("./preparation")) {
+        if prep_time.extension().is_some_and(|e| e == "prep-time") && is_stale(&prep_time, None) {
+            stale.push((prep_time, "preparation time"));
+        }
+    }
+
+    // Scratch directories are normally removed on exit, but are left
+    // behind if benchify is killed
+    for scratch in entries(&std::env::temp_dir()) {
+        let name = scratch.file_name().unwrap().to_string_lossy().to_string();
+        let Some(pid) = name
+            .strip_prefix("benchify-")
+            .and_then(|pid| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if scratch.is_dir() && !is_running(pid) && is_stale(&scratch, None) {
+            stale.push((scratch, "scratch directory"));
+        }
+    }
+
+    stale
+}
+
+/// Removes everything under `results_dir` (and elsewhere) that is older
+/// than `older_than` (or the configured age), or with `dry_run`, only
+/// lists it
+pub fn clean(
+    results_dir: &Path,
+    retention: &RetentionConfig,
+    older_than: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    let older_than = older_than
+        .or(retention.older_than.as_deref())
+        .unwrap_or(DEFAULT_OLDER_THAN);
+    let max_age = crate::parse_duration(older_than)?;
+    let stale = stale_artifacts(results_dir, retention, max_age);
+    let mut failures = 0;
+    for (path, what) in stale.iter() {
+        if dry_run {
+            println!("Would remove {} {:?}", what, path);
+            continue;
+        }
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        match removed {
+            Ok(()) => println!("Removed {} {:?}", what, path),
+            Err(e) => {
+                eprintln!("Could not remove {} {:?}: {}", what, path, e);
+                failures += 1;
+            }
+        }
+    }
+    if stale.is_empty() {
+        println!("Nothing older than {} to remove.", older_than);
+    }
+    if failures > 0 {
+        return Err(eyre!(
+            "Could not remove {} of {} artifacts",
+            failures,
+            stale.len()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Running the processes of a tool (its runs, and its `prepare` and
+//! `cleanup` commands) inside a fresh container of an image, for tools
+//! that are only distributed as images. Runs are still timed from the
+//! outside, so their timings include starting (and removing) the
+//! container.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Container engine that is used unless the tool sets `container_engine`
+pub const DEFAULT_ENGINE: &str = "docker";
+
+/// `command`, run instead inside a fresh container of `image` with
+/// `engine` (e.g., "docker" or "podman"). The current directory, the
+/// scratch directory and `mounts` are mounted at the same paths, so that
+/// paths in the command mean the same inside the container, and its
+/// environment variables and working directory are carried over.
+pub fn wrap(engine: &str, image: &str, command: &Command, mounts: &[PathBuf]) -> Command {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let dir = command
+        .get_current_dir()
+        .map(|dir| cwd.join(dir))
+        .unwrap_or_else(|| cwd.clone());
+    let mut wrapped = Command::new(engine);
+    // Interactive, so that stdin reaches the command
+    wrapped.args(["run", "--rm", "-i"]);
+    // Mounted even if unused, and a mount needs an existing directory
+    let _ = std::fs::create_dir_all(&*crate::SCRATCH_DIR);
+    let mut mounted: Vec<&Path> = vec![];
+    for mount in std::iter::once(&cwd)
+        .chain(std::iter::once(&*crate::SCRATCH_DIR))
+        .chain(mounts)
+    {
+        if mounted.iter().any(|m| mount.starts_with(m)) {
+            continue;
+        }
+        mounted.push(mount);
+        wrapped
+            .arg("-v")
+            .arg(format!("{}:{}", mount.display(), mount.display()));
+    }
+    wrapped.arg("-w").arg(&dir);
+    for (key, value) in command.get_envs() {
+        if let Some(value) = value {
+            let mut env = key.to_os_string();
+            env.push("=");
+            env.push(value);
+            wrapped.arg("-e").arg(env);
+        }
+    }
+    wrapped
+        .arg(image)
+        .arg(command.get_program())
+        .args(command.get_args());
+    wrapped
+}
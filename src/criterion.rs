@@ -0,0 +1,180 @@
+//! Results in the on-disk format of Criterion.rs
+//! (`<group>/<function>/<baseline>/{benchmark,estimates,sample,tukey}.json`),
+//! so that tooling built around it (e.g., critcmp) can consume them. Each
+//! test is a group, and each tool a function of it. All times are in
+//! nanoseconds, as Criterion records them.
+
+use crate::CellResult;
+use color_eyre::eyre::Result;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Serialize)]
+struct Benchmark<'a> {
+    group_id: &'a str,
+    function_id: Option<&'a str>,
+    value_str: Option<&'a str>,
+    throughput: Option<CriterionThroughput>,
+    full_id: String,
+    directory_name: String,
+    title: String,
+}
+
+#[derive(Serialize)]
+enum CriterionThroughput {
+    Bytes(u64),
+    Elements(u64),
+}
+
+#[derive(Serialize)]
+struct ConfidenceInterval {
+    confidence_level: f64,
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+#[derive(Serialize)]
+struct Estimate {
+    confidence_interval: ConfidenceInterval,
+    point_estimate: f64,
+    standard_error: f64,
+}
+
+#[derive(Serialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    median_abs_dev: Estimate,
+    slope: Option<Estimate>,
+    std_dev: Estimate,
+}
+
+/// A "flat" sample: each run is a single iteration
+#[derive(Serialize)]
+struct Sample {
+    sampling_mode: &'static str,
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+fn nanos(d: Duration) -> f64 {
+    d.as_secs_f64() * 1e9
+}
+
+fn mean(data: &[Duration]) -> Duration {
+    data.iter().sum::<Duration>() / data.len() as u32
+}
+
+fn median(data: &[Duration]) -> Duration {
+    let mut sorted = data.to_vec();
+    sorted.sort();
+    crate::percentile_of_sorted(&sorted, 50.)
+}
+
+fn std_dev(data: &[Duration]) -> Duration {
+    if data.len() < 2 {
+        return Duration::ZERO;
+    }
+    let mean = nanos(mean(data));
+    let variance =
+        data.iter().map(|d| (nanos(*d) - mean).powi(2)).sum::<f64>() / (data.len() - 1) as f64;
+    Duration::from_secs_f64(variance.sqrt() / 1e9)
+}
+
+/// Median absolute deviation, scaled (as by Criterion) to estimate the
+/// standard deviation of normally distributed data
+fn median_abs_dev(data: &[Duration]) -> Duration {
+    let median = nanos(median(data));
+    let deviations: Vec<Duration> = data
+        .iter()
+        .map(|d| Duration::from_secs_f64((nanos(*d) - median).abs() / 1e9))
+        .collect();
+    self::median(&deviations).mul_f64(1.4826)
+}
+
+/// `statistic` of `data`, with its bootstrap confidence interval and
+/// standard error from resamples drawn with `seed`
+fn estimate(data: &[Duration], seed: u64, statistic: fn(&[Duration]) -> Duration) -> Estimate {
+    let (lower, upper) = crate::bootstrap::interval(data, seed, statistic);
+    Estimate {
+        confidence_interval: ConfidenceInterval {
+            confidence_level: crate::bootstrap::CONFIDENCE / 100.,
+            lower_bound: nanos(lower),
+            upper_bound: nanos(upper),
+        },
+        point_estimate: nanos(statistic(data)),
+        standard_error: nanos(std_dev(data)) / (data.len() as f64).sqrt(),
+    }
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+    std::fs::write(path, serde_json::to_string(value)?)?;
+    Ok(())
+}
+
+/// Writes the measured runs of `cell` into `dir`, as the benchmark
+/// `<test>/<tool>` of the Criterion baseline `baseline` (Criterion's own
+/// being "new"), with confidence intervals from resamples drawn with
+/// `seed`. Failed cells are left out.
+pub fn write(dir: &Path, baseline: &str, cell: &CellResult, seed: u64) -> Result<()> {
+    let timings = match cell.timings() {
+        Ok(timings) if !timings.is_empty() => timings,
+        _ => return Ok(()),
+    };
+    let timings = &timings[..];
+    let (test, tool) = (cell.test, cell.executor);
+    let (test_id, tool_id) = cell.id.split_once('/').unwrap_or((&cell.id, ""));
+    let (test_id, tool_id) = (crate::slugify(test_id), crate::slugify(tool_id));
+    let directory_name = format!("{}/{}", test_id, tool_id);
+    let benchmark_dir = dir.join(&test_id).join(&tool_id).join(baseline);
+    std::fs::create_dir_all(&benchmark_dir)?;
+
+    write_json(
+        &benchmark_dir.join("benchmark.json"),
+        &Benchmark {
+            group_id: test,
+            function_id: Some(tool),
+            value_str: None,
+            throughput: cell.throughput.map(|t| match t.unit.as_str() {
+                "B" | "byte" | "bytes" => CriterionThroughput::Bytes(t.amount as u64),
+                _ => CriterionThroughput::Elements(t.amount as u64),
+            }),
+            full_id: format!("{}/{}", test, tool),
+            directory_name,
+            title: format!("{}/{}", test, tool),
+        },
+    )?;
+    write_json(
+        &benchmark_dir.join("estimates.json"),
+        &Estimates {
+            mean: estimate(timings, seed, mean),
+            median: estimate(timings, seed, median),
+            median_abs_dev: estimate(timings, seed, median_abs_dev),
+            slope: None,
+            std_dev: estimate(timings, seed, std_dev),
+        },
+    )?;
+    write_json(
+        &benchmark_dir.join("sample.json"),
+        &Sample {
+            sampling_mode: "Flat",
+            iters: vec![1.; timings.len()],
+            times: timings.iter().copied().map(nanos).collect(),
+        },
+    )?;
+    // Tukey's fences: the low severe, low mild, high mild and high
+    // severe bounds of outliers
+    let mut sorted = timings.to_vec();
+    sorted.sort();
+    let (q1, q3) = (
+        nanos(crate::percentile_of_sorted(&sorted, 25.)),
+        nanos(crate::percentile_of_sorted(&sorted, 75.)),
+    );
+    let iqr = q3 - q1;
+    write_json(
+        &benchmark_dir.join("tukey.json"),
+        &[q1 - 3. * iqr, q1 - 1.5 * iqr, q3 + 1.5 * iqr, q3 + 3. * iqr],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,141 @@
+//! Long-lived processes (e.g., a JVM running a nailgun server) that a
+//! tool's runs send requests to, so that the runs measure steady-state
+//! performance rather than the startup of a managed runtime.
+
+use color_eyre::eyre::{eyre, Result};
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+
+/// How often readiness is checked while waiting for a daemon to start
+const READY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DaemonConfig {
+    /// Shell command that runs the daemon in the foreground
+    start: crate::ShellCommand,
+    /// Shell command that succeeds once the daemon is ready for requests
+    ready: Option<crate::ShellCommand>,
+    /// Seconds to wait for `ready` to succeed
+    ready_timeout: Option<f64>,
+    /// Shell command that stops the daemon. If not given, the daemon is
+    /// killed.
+    stop: Option<crate::ShellCommand>,
+}
+
+/// A running daemon, stopped when dropped
+pub struct Daemon {
+    tool: String,
+    child: std::process::Child,
+    stop: Option<std::process::Command>,
+}
+
+fn interpolated(cmd: &str) -> String {
+    cmd.replace("{TMPDIR}", &crate::SCRATCH_DIR.to_string_lossy())
+}
+
+impl Daemon {
+    /// Starts the daemon for `tool`, waiting until it is ready. Its
+    /// output goes to a log file in the scratch directory. Its shell
+    /// commands are run with `shell_command`.
+    pub fn start(
+        tool: &str,
+        config: &DaemonConfig,
+        shell_command: impl Fn(&str) -> std::process::Command,
+    ) -> Result<Self> {
+        std::fs::create_dir_all(&*crate::SCRATCH_DIR)?;
+        let log_path = crate::SCRATCH_DIR.join(format!("daemon-{}.log", crate::slugify(tool)));
+        let log = std::fs::File::create(&log_path)?;
+        let cmd = interpolated(&config.start);
+        trace!("Starting daemon for {} with `{}`", tool, cmd);
+        let mut command = shell_command(&cmd);
+        command
+            .stdin(std::process::Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log);
+        // In a process group of its own, so that killing it also kills
+        // anything the shell started
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let child = command.spawn()?;
+        let mut daemon = Daemon {
+            tool: tool.to_string(),
+            child,
+            stop: config
+                .stop
+                .as_deref()
+                .map(|stop| shell_command(&interpolated(stop))),
+        };
+
+        let Some(ready) = &config.ready else {
+            return Ok(daemon);
+        };
+        let ready = interpolated(ready);
+        let timeout = std::time::Duration::from_secs_f64(config.ready_timeout.unwrap_or(60.));
+        let timer = std::time::Instant::now();
+        loop {
+            if let Some(status) = daemon.child.try_wait()? {
+                return Err(eyre!(
+                    "Daemon for {} exited with {} before becoming ready. See {:?}",
+                    tool,
+                    status,
+                    log_path
+                ));
+            }
+            let ready_status = shell_command(&ready)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status()?;
+            if ready_status.success() {
+                trace!("Daemon for {} is ready", tool);
+                return Ok(daemon);
+            }
+            if timer.elapsed() > timeout || crate::interrupt::interrupted() {
+                return Err(eyre!(
+                    "Daemon for {} did not become ready within {:?}. See {:?}",
+                    tool,
+                    timeout,
+                    log_path
+                ));
+            }
+            std::thread::sleep(READY_POLL_INTERVAL);
+        }
+    }
+}
+
+impl Daemon {
+    #[cfg(unix)]
+    fn kill(&mut self) -> std::io::Result<()> {
+        // SAFETY: plain syscall; the process group is the daemon's own,
+        // and the daemon has not been reaped yet
+        if unsafe { libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn kill(&mut self) -> std::io::Result<()> {
+        self.child.kill()
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        trace!("Stopping daemon for {}", self.tool);
+        let stopped = self.stop.as_mut().is_some_and(|stop| {
+            // Its output would otherwise mix into whatever benchify
+            // prints on stdout (e.g., `--json` results)
+            stop.stdin(std::process::Stdio::null())
+                .stdout(std::io::stderr())
+                .status()
+                .is_ok_and(|s| s.success())
+        });
+        if !stopped {
+            if let Err(e) = self.kill() {
+                error!("Could not kill daemon for {}: {}", self.tool, e);
+            }
+        }
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,104 @@
+use crate::plan::Plan;
+use color_eyre::eyre::{eyre, Result};
+use std::io::Write;
+
+/// A remote machine that runs part of a plan, reached over SSH
+#[derive(Debug, Clone)]
+pub struct Worker {
+    /// SSH destination, e.g. `user@host`
+    pub host: String,
+    /// Directory on the worker that holds its benchify.toml, and
+    /// relative to which its tests are run
+    pub dir: String,
+    /// Class of machine the worker's results are labeled with
+    pub machine_class: Option<String>,
+}
+
+impl std::str::FromStr for Worker {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (machine_class, s) = match s.split_once('=') {
+            Some((class, rest)) if !class.contains(':') => {
+                (Some(crate::parse_machine_class(class)?), rest)
+            }
+            _ => (None, s),
+        };
+        match s.split_once(':') {
+            Some((host, dir)) if !host.is_empty() && !dir.is_empty() => Ok(Worker {
+                host: host.to_string(),
+                dir: dir.to_string(),
+                machine_class,
+            }),
+            _ => Err(format!("expected [CLASS=]HOST:DIR, got {:?}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Worker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(class) = &self.machine_class {
+            write!(f, "{}=", class)?;
+        }
+        write!(f, "{}:{}", self.host, self.dir)
+    }
+}
+
+/// `s`, quoted for a POSIX shell on the other end of SSH
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Splits `plan` into `n` plans, dealing out its tests round-robin.
+/// All the cells of a test go to the same plan, so that the tools are
+/// always compared on the same machine.
+pub fn shard(plan: &Plan, n: usize) -> Vec<Plan> {
+    let mut shards: Vec<Plan> = (0..n)
+        .map(|_| Plan {
+            cells: vec![],
+            ..plan.clone()
+        })
+        .collect();
+    let mut tests: Vec<&str> = vec![];
+    for cell in plan.cells.iter() {
+        let i = match tests.iter().position(|t| *t == cell.test) {
+            Some(i) => i,
+            None => {
+                tests.push(&cell.test);
+                tests.len() - 1
+            }
+        };
+        shards[i % n].cells.push(cell.clone());
+    }
+    shards
+}
+
+/// Runs `plan` on `worker`, producing the contents of the data.csv it
+/// recorded. Anything the worker logs is passed through to stderr.
+pub fn run_on(worker: &Worker, plan: &Plan) -> Result<String> {
+    let mut child = std::process::Command::new("ssh")
+        .arg("-o")
+        .arg("BatchMode=yes")
+        .arg(&worker.host)
+        .arg(format!(
+            "cd {} && benchify {} worker",
+            shell_quote(&worker.dir),
+            match &worker.machine_class {
+                Some(class) => format!("--machine-class {}", shell_quote(class)),
+                None => String::new(),
+            }
+        ))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(serde_json::to_string(plan)?.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(eyre!("Worker {} exited with {}", worker, output.status));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
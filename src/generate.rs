@@ -0,0 +1,81 @@
+//! Generation of the input files of tests by a command of theirs, over
+//! a list of sizes, as configured in `[tests.generate]`, so that scaling
+//! benchmarks need no inputs checked in alongside them. Each input is
+//! generated once, before anything is prepared, and kept for later runs
+//! of benchify for as long as the command that generated it is the same.
+
+use crate::{BenchifyConfig, Test};
+use color_eyre::eyre::{eyre, Result};
+use log::{debug, error, trace};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GenerateConfig {
+    /// Shell command that writes the test's input, of size `{SIZE}`, to
+    /// `{FILE}`
+    pub cmd: String,
+    /// Sizes that the test is expanded into, one test per size
+    pub sizes: Vec<toml::Value>,
+}
+
+/// File recording the command that generated `file`, next to it
+fn stamp_path(file: &Path) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    file.with_file_name(format!(".{}.generated-by", name))
+}
+
+impl GenerateConfig {
+    /// Why inputs could not be generated as configured, if they could
+    /// not
+    pub fn problem(&self) -> Option<String> {
+        if !self.cmd.contains("{FILE}") {
+            return Some("its cmd does not write to {FILE}".to_string());
+        }
+        None
+    }
+
+    /// Generates the input file of `test`, unless it was already
+    /// generated by the same command
+    pub fn generate(&self, config: &BenchifyConfig, test: &Test) -> Result<()> {
+        let Some(file) = &test.file else {
+            return Err(eyre!("Test {} has no file to generate", test.name));
+        };
+        let file = Path::new(file);
+        let cmd = test.interpolated_into(&self.cmd);
+        let stamp = stamp_path(file);
+        if file.exists() && std::fs::read_to_string(&stamp).is_ok_and(|s| s == cmd) {
+            debug!("Using the input {:?} generated earlier", file);
+            return Ok(());
+        }
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let ui = config.ui();
+        let pb = ui.spinner();
+        pb.set_style(ui.spinner_style());
+        ui.start_ticking(&pb);
+        let what = format!("[{}] Generating {}", test.name, file.display());
+        pb.set_message(&what);
+        let logged_phase = ui.phase(what);
+        trace!("Generating with `{}`", cmd);
+        let mut command = crate::shell::command(config.shell.as_ref(), &cmd);
+        for vars in [&config.env, &test.env].into_iter().flatten() {
+            command.envs(vars.iter().map(|(k, v)| (k, test.interpolated_into(v))));
+        }
+        let output = command.stdin(std::process::Stdio::null()).output()?;
+        pb.finish_and_clear();
+        if !output.status.success() {
+            error!("Generator `{}` exited with {}", cmd, output.status);
+            error!("STDERR:\n{}\n\n", String::from_utf8_lossy(&output.stderr));
+            return Err(eyre!("Generator exit code {}", output.status));
+        }
+        if !file.exists() {
+            return Err(eyre!("Generator `{}` did not write {:?}", cmd, file));
+        }
+        std::fs::write(&stamp, &cmd)?;
+        logged_phase.finish();
+        Ok(())
+    }
+}
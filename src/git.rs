@@ -0,0 +1,76 @@
+//! Benchmarking revisions of a git repository against each other
+//! (`benchify git <rev>...`). Each revision is checked out into a
+//! worktree of its own, which is kept across invocations like any other
+//! build directory, built there, and registered as a build of a tool.
+
+use color_eyre::eyre::{eyre, Result};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GitConfig {
+    /// Tool whose runners the revisions are run with (by default, the
+    /// main tool, or the only tool)
+    pub tool: Option<String>,
+    /// Shell command that builds a revision, run in its worktree
+    pub build: String,
+    /// Path of the built program, relative to the worktree
+    pub program: String,
+}
+
+fn git(args: &[&str], dir: Option<&Path>) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    trace!("Running git {:?}", args);
+    let output = command
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| eyre!("Could not run git: {}", e))?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The commit that `revision` names, in the repository of the current
+/// directory
+pub fn resolve(revision: &str) -> Result<String> {
+    git(
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", revision)],
+        None,
+    )
+    .map_err(|_| eyre!("{} is not a revision of this repository", revision))
+}
+
+/// Checks `revision` out into the worktree `dir`, adding the worktree if
+/// there is none yet. An existing worktree is checked out in place, so
+/// that its build picks up where the last one left off.
+pub fn checkout(revision: &str, dir: &Path) -> Result<()> {
+    let commit = resolve(revision)?;
+    if dir.join(".git").exists() {
+        git(&["checkout", "--quiet", "--detach", &commit], Some(dir))?;
+    } else {
+        git(
+            &[
+                "worktree",
+                "add",
+                "--quiet",
+                "--detach",
+                &dir.to_string_lossy(),
+                &commit,
+            ],
+            None,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! A compact, GitHub-flavored markdown summary of the results, sized for
+//! a bot to post as a comment on a pull request: the change of each
+//! (test, tool) pair since the loaded baseline (or, without one, its
+//! ratio to the main tool), marked with emoji, and the full summaries
+//! folded away below.
+
+use crate::{baseline::Verdict, BenchifyResults, CellResult};
+use color_eyre::eyre::Result;
+use std::fmt::Write;
+
+/// Longest comment that GitHub accepts, in characters
+const MAX_LENGTH: usize = 65536;
+
+fn emoji(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Regression => ":red_circle:",
+        Verdict::Improvement => ":green_circle:",
+        Verdict::NoChange => ":white_circle:",
+    }
+}
+
+/// Name of the test of `r`, with the class of machine it ran on, if
+/// labeled
+fn test_name(r: &CellResult) -> String {
+    match &r.machine_class {
+        Some(class) => format!("{} [{}]", r.test, class),
+        None => r.test.to_string(),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('|', r"\|")
+}
+
+impl BenchifyResults<'_> {
+    /// Changes since the baseline, as a table with a row for each (test,
+    /// tool) pair that ran both then and now
+    fn format_changes(&self, comment: &mut String) -> Result<()> {
+        let verdicts = self.baseline_verdicts();
+        let count = |verdict| verdicts.iter().filter(|(_, _, v)| *v == verdict).count();
+        let (regressions, improvements) = (count(Verdict::Regression), count(Verdict::Improvement));
+        let baseline = self.baseline.as_ref().unwrap();
+        writeln!(
+            comment,
+            "Compared to baseline `{}`{}: **{} regressions**, **{} improvements**, {} unchanged.",
+            baseline.name,
+            baseline.formatted_note(),
+            regressions,
+            improvements,
+            verdicts.len() - regressions - improvements
+        )?;
+        if verdicts.is_empty() {
+            return Ok(());
+        }
+        writeln!(comment)?;
+        writeln!(
+            comment,
+            "| | Test | Tool | Baseline (ms) | Now (ms) | Change |"
+        )?;
+        writeln!(comment, "|:-:|:--|:--|--:|--:|--:|")?;
+        for (r, base, verdict) in &verdicts {
+            let base = self.report.central_value(base).as_secs_f64();
+            let now = self
+                .report
+                .central_value(&self.report.summarize(&r.timings()?))
+                .as_secs_f64();
+            writeln!(
+                comment,
+                "| {} | {} | {} | {:.3} | {:.3} | {:+.1}% |",
+                emoji(*verdict),
+                escape(&test_name(r)),
+                escape(r.executor),
+                base * 1000.,
+                now * 1000.,
+                (now / base - 1.) * 100.
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Central value of each (test, tool) pair, and its ratio to the main
+    /// tool's on the same test
+    fn format_central_values(&self, comment: &mut String) -> Result<()> {
+        let central_value = |r: &CellResult| -> Option<f64> {
+            let timings = r.timings().ok().filter(|t| !t.is_empty())?;
+            Some(
+                self.report
+                    .central_value(&self.report.summarize(&timings))
+                    .as_secs_f64(),
+            )
+        };
+        writeln!(
+            comment,
+            "| Test | Tool | {} (ms) | Ratio to {} |",
+            self.report.estimator_name(),
+            self.main_tool.unwrap_or("main tool")
+        )?;
+        writeln!(comment, "|:--|:--|--:|--:|")?;
+        for r in self.results.iter() {
+            let Some(value) = central_value(r) else {
+                continue;
+            };
+            let main = self
+                .main_tool
+                .and_then(|main| {
+                    self.results.iter().find(|m| {
+                        m.executor == main && m.test == r.test && m.machine_class == r.machine_class
+                    })
+                })
+                .and_then(central_value);
+            writeln!(
+                comment,
+                "| {} | {} | {:.3} | {} |",
+                escape(&test_name(r)),
+                escape(r.executor),
+                value * 1000.,
+                main.map_or("-".to_string(), |main| format!("{:.3}", value / main))
+            )?;
+        }
+        Ok(())
+    }
+
+    /// A markdown comment for a pull request, summarizing the results
+    pub(crate) fn github_comment(&self) -> Result<String> {
+        let mut comment = String::new();
+        writeln!(comment, "### Benchmark results")?;
+        writeln!(comment)?;
+        if let Some(note) = &self.metadata.note {
+            writeln!(comment, "> {}", note)?;
+            writeln!(comment)?;
+        }
+        if self.baseline.is_some() {
+            self.format_changes(&mut comment)?;
+        } else {
+            self.format_central_values(&mut comment)?;
+        }
+        let failed: Vec<&CellResult> = self.results.iter().filter(|r| r.samples.is_err()).collect();
+        if !failed.is_empty() {
+            writeln!(comment)?;
+            for r in failed {
+                let error = r.samples.as_ref().err().unwrap().to_string();
+                writeln!(
+                    comment,
+                    "- :x: {} failed on {}: {}",
+                    r.executor,
+                    test_name(r),
+                    error.lines().next().unwrap_or_default()
+                )?;
+            }
+        }
+        if let Some(reason) = &self.aborted {
+            writeln!(comment)?;
+            writeln!(comment, ":warning: Stopped early: {}", reason)?;
+        }
+
+        let mut details = String::new();
+        for (class, facet) in self.by_machine_class() {
+            for (test, results) in facet.results_by_test() {
+                writeln!(details)?;
+                match class {
+                    Some(class) => writeln!(details, "**{}** [{}]", test, class)?,
+                    None => writeln!(details, "**{}**", test)?,
+                }
+                writeln!(details)?;
+                write!(
+                    details,
+                    "{}",
+                    crate::format_summary(self.main_tool, &self.report, results)?.1
+                )?;
+                write!(details, "{}", facet.format_skipped(test))?;
+            }
+        }
+        writeln!(comment)?;
+        writeln!(comment, "<details>")?;
+        writeln!(comment, "<summary>Full results</summary>")?;
+        // Left out rather than cut off, if they do not fit
+        if comment.len() + details.len() + 100 < MAX_LENGTH {
+            comment += &details;
+        } else {
+            writeln!(comment)?;
+            writeln!(
+                comment,
+                "Too long for a comment. See `index.md` in the results directory."
+            )?;
+        }
+        writeln!(comment)?;
+        writeln!(comment, "</details>")?;
+        Ok(comment)
+    }
+}
@@ -0,0 +1,107 @@
+//! GPU usage of runs, as reported by NVIDIA's NVML. Only available when
+//! built with the `gpu` feature, and only for NVIDIA GPUs.
+
+/// GPU usage over the course of a run, across all GPUs on the machine
+pub struct GpuUsage {
+    /// Mean (over polls, and over GPUs) utilization, in percent
+    pub mean_utilization: f64,
+    /// Peak of the total memory in use, in bytes
+    pub peak_memory: u64,
+}
+
+#[cfg(feature = "gpu")]
+mod imp {
+    use super::GpuUsage;
+    use lazy_static::lazy_static;
+    use nvml_wrapper::Nvml;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    const INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+    lazy_static! {
+        static ref NVML: Option<Nvml> = Nvml::init().ok();
+    }
+
+    /// Whether there are any GPUs to sample
+    pub fn available() -> bool {
+        NVML.as_ref()
+            .and_then(|nvml| nvml.device_count().ok())
+            .is_some_and(|n| n > 0)
+    }
+
+    /// Polls the usage of all GPUs in a background thread
+    pub struct GpuSampler {
+        stop: Arc<AtomicBool>,
+        handle: std::thread::JoinHandle<Option<GpuUsage>>,
+    }
+
+    impl GpuSampler {
+        pub fn start() -> Self {
+            let stop = Arc::new(AtomicBool::new(false));
+            let handle = {
+                let stop = stop.clone();
+                std::thread::spawn(move || {
+                    let nvml = NVML.as_ref()?;
+                    let devices: Vec<_> = (0..nvml.device_count().ok()?)
+                        .filter_map(|i| nvml.device_by_index(i).ok())
+                        .collect();
+                    let mut utilizations = vec![];
+                    let mut peak_memory = 0;
+                    loop {
+                        // Sample at least once, even for the shortest of runs
+                        let mut memory = 0;
+                        for device in &devices {
+                            if let Ok(u) = device.utilization_rates() {
+                                utilizations.push(u.gpu as f64);
+                            }
+                            if let Ok(m) = device.memory_info() {
+                                memory += m.used;
+                            }
+                        }
+                        peak_memory = peak_memory.max(memory);
+                        if stop.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        std::thread::sleep(INTERVAL);
+                    }
+                    (!utilizations.is_empty()).then(|| GpuUsage {
+                        mean_utilization: utilizations.iter().sum::<f64>()
+                            / utilizations.len() as f64,
+                        peak_memory,
+                    })
+                })
+            };
+            GpuSampler { stop, handle }
+        }
+
+        /// Stops sampling, producing the usage seen, if any
+        pub fn finish(self) -> Option<GpuUsage> {
+            self.stop.store(true, Ordering::SeqCst);
+            self.handle.join().unwrap()
+        }
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+mod imp {
+    use super::GpuUsage;
+
+    pub fn available() -> bool {
+        false
+    }
+
+    pub struct GpuSampler;
+
+    impl GpuSampler {
+        pub fn start() -> Self {
+            GpuSampler
+        }
+
+        pub fn finish(self) -> Option<GpuUsage> {
+            None
+        }
+    }
+}
+
+pub use imp::{available, GpuSampler};
@@ -0,0 +1,265 @@
+//! History of every run across invocations, appended to a SQLite
+//! database in the results directory, for longitudinal analysis that
+//! the per-invocation CSV files make painful. Only available when built
+//! with the `history` feature.
+
+/// A single run, as recorded in the history
+pub struct Run<'a> {
+    pub cell_id: &'a str,
+    pub machine_class: Option<&'a str>,
+    pub test: &'a str,
+    pub tool: &'a str,
+    pub iteration: usize,
+    pub warmup: bool,
+    /// In seconds
+    pub timing: f64,
+    pub exit_status: Option<i32>,
+    pub censored: bool,
+}
+
+/// The measured runs of a (test, tool) pair in a single invocation
+pub struct Entry {
+    /// Seconds since the Unix epoch at which the invocation started
+    pub timestamp: u64,
+    pub hostname: Option<String>,
+    pub config_hash: Option<String>,
+    pub note: Option<String>,
+    /// Labels of the invocation, as `key=value, ...`
+    pub labels: Option<String>,
+    pub test: String,
+    pub tool: String,
+    pub runs: usize,
+    /// Mean and minimum timings, in seconds
+    pub mean: f64,
+    pub min: f64,
+}
+
+#[cfg(feature = "history")]
+mod imp {
+    use super::{Entry, Run};
+    use crate::metadata::Metadata;
+    use color_eyre::eyre::Result;
+    use rusqlite::{params, Connection, ToSql};
+    use std::path::Path;
+
+    /// File name of the database, in the results directory
+    const DATABASE: &str = "history.sqlite";
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS invocations (
+            id INTEGER PRIMARY KEY,
+            timestamp INTEGER NOT NULL,
+            benchify_version TEXT NOT NULL,
+            hostname TEXT,
+            cpu TEXT,
+            kernel TEXT,
+            machine_class TEXT,
+            config_hash TEXT,
+            note TEXT
+        );
+        CREATE TABLE IF NOT EXISTS labels (
+            invocation INTEGER NOT NULL REFERENCES invocations(id),
+            key TEXT NOT NULL,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS tool_versions (
+            invocation INTEGER NOT NULL REFERENCES invocations(id),
+            tool TEXT NOT NULL,
+            version TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS runs (
+            invocation INTEGER NOT NULL REFERENCES invocations(id),
+            cell_id TEXT NOT NULL,
+            machine_class TEXT,
+            test TEXT NOT NULL,
+            tool TEXT NOT NULL,
+            iteration INTEGER NOT NULL,
+            warmup INTEGER NOT NULL,
+            timing REAL NOT NULL,
+            exit_status INTEGER,
+            censored INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS runs_by_cell ON runs (test, tool);
+    ";
+
+    fn open(results_dir: &Path) -> Result<Connection> {
+        std::fs::create_dir_all(results_dir)?;
+        let connection = Connection::open(results_dir.join(DATABASE))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(connection)
+    }
+
+    /// Appends the invocation described by `metadata`, and its `runs`
+    pub fn append<'a>(
+        results_dir: &Path,
+        metadata: &Metadata,
+        runs: impl Iterator<Item = Run<'a>>,
+    ) -> Result<()> {
+        let mut connection = open(results_dir)?;
+        // All or nothing, so that an invocation is never half recorded
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO invocations (timestamp, benchify_version, hostname, cpu, kernel, \
+             machine_class, config_hash, note) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                metadata.timestamp,
+                metadata.benchify_version,
+                metadata.hostname,
+                metadata.cpu,
+                metadata.kernel,
+                metadata.machine_class,
+                metadata.config_hash,
+                metadata.note,
+            ],
+        )?;
+        let invocation = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO tool_versions (invocation, tool, version) VALUES (?1, ?2, ?3)",
+            )?;
+            for (tool, version) in &metadata.tool_versions {
+                insert.execute(params![invocation, tool, version])?;
+            }
+            let mut insert = transaction
+                .prepare("INSERT INTO labels (invocation, key, value) VALUES (?1, ?2, ?3)")?;
+            for (key, value) in &metadata.labels {
+                insert.execute(params![invocation, key, value])?;
+            }
+            let mut insert = transaction.prepare(
+                "INSERT INTO runs (invocation, cell_id, machine_class, test, tool, iteration, \
+                 warmup, timing, exit_status, censored) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for run in runs {
+                insert.execute(params![
+                    invocation,
+                    run.cell_id,
+                    run.machine_class,
+                    run.test,
+                    run.tool,
+                    run.iteration,
+                    run.warmup,
+                    run.timing,
+                    run.exit_status,
+                    run.censored,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The measured runs of each (test, tool) pair in each invocation,
+    /// optionally only of `test` and `tool`, and only of invocations
+    /// with all of `labels`, oldest first
+    pub fn entries(
+        results_dir: &Path,
+        test: Option<&str>,
+        tool: Option<&str>,
+        labels: &[(String, String)],
+    ) -> Result<Vec<Entry>> {
+        let connection = open(results_dir)?;
+        let mut sql = "SELECT i.timestamp, i.hostname, i.config_hash, i.note, \
+                       (SELECT group_concat(l.key || '=' || l.value, ', ') \
+                        FROM labels l WHERE l.invocation = i.id), \
+                       r.test, r.tool, COUNT(*), AVG(r.timing), MIN(r.timing) \
+                       FROM runs r JOIN invocations i ON r.invocation = i.id \
+                       WHERE NOT r.warmup AND (?1 IS NULL OR r.test = ?1) \
+                       AND (?2 IS NULL OR r.tool = ?2)"
+            .to_string();
+        let mut values: Vec<&dyn ToSql> = vec![&test, &tool];
+        for (key, value) in labels {
+            sql += &format!(
+                " AND EXISTS (SELECT 1 FROM labels l WHERE l.invocation = i.id \
+                 AND l.key = ?{} AND l.value = ?{})",
+                values.len() + 1,
+                values.len() + 2
+            );
+            values.push(key);
+            values.push(value);
+        }
+        sql += " GROUP BY i.id, r.test, r.tool ORDER BY r.test, r.tool, i.timestamp, i.id";
+        let mut query = connection.prepare(&sql)?;
+        let entries = query
+            .query_map(values.as_slice(), |row| {
+                Ok(Entry {
+                    timestamp: row.get(0)?,
+                    hostname: row.get(1)?,
+                    config_hash: row.get(2)?,
+                    note: row.get(3)?,
+                    labels: row.get(4)?,
+                    test: row.get(5)?,
+                    tool: row.get(6)?,
+                    runs: row.get(7)?,
+                    mean: row.get(8)?,
+                    min: row.get(9)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+}
+
+#[cfg(not(feature = "history"))]
+mod imp {
+    use super::{Entry, Run};
+    use crate::metadata::Metadata;
+    use color_eyre::eyre::{eyre, Result};
+    use std::path::Path;
+
+    pub fn append<'a>(
+        _results_dir: &Path,
+        _metadata: &Metadata,
+        _runs: impl Iterator<Item = Run<'a>>,
+    ) -> Result<()> {
+        Err(eyre!("benchify was built without the `history` feature"))
+    }
+
+    pub fn entries(
+        _results_dir: &Path,
+        _test: Option<&str>,
+        _tool: Option<&str>,
+        _labels: &[(String, String)],
+    ) -> Result<Vec<Entry>> {
+        Err(eyre!("benchify was built without the `history` feature"))
+    }
+}
+
+pub use imp::{append, entries};
+
+/// Tables of `entries`, one per (test, tool) pair, oldest first
+pub fn format(entries: &[Entry]) -> String {
+    use std::fmt::Write;
+    let mut out = String::new();
+    let mut cell = None;
+    for e in entries {
+        if cell != Some((&e.test, &e.tool)) {
+            cell = Some((&e.test, &e.tool));
+            let _ = writeln!(out, "\n# {} / {}\n", e.test, e.tool);
+            let _ = writeln!(
+                out,
+                "| Timestamp (Unix) | Host | Config hash | Labels | Runs | Mean (ms) | Min (ms) \
+                 | Note |"
+            );
+            let _ = writeln!(out, "|---:|:---|:---|:---|---:|---:|---:|:---|");
+        }
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {:.3} | {:.3} | {} |",
+            e.timestamp,
+            e.hostname.as_deref().unwrap_or("-"),
+            e.config_hash.as_deref().unwrap_or("-"),
+            e.labels.as_deref().unwrap_or(""),
+            e.runs,
+            e.mean * 1000.,
+            e.min * 1000.,
+            e.note.as_deref().unwrap_or(""),
+        );
+    }
+    out
+}
+
+/// Whether the history can be recorded at all
+pub fn available() -> bool {
+    cfg!(feature = "history")
+}
@@ -0,0 +1,72 @@
+//! Hooks: shell commands run once the results are saved, to hand them
+//! to systems that benchify has no exporter of its own for (e.g., a
+//! dashboard or a chat channel). `{RESULTS_DIR}` is interpolated to the
+//! results directory, and a JSON payload describing the event (including
+//! the contents of results.json) is written to the command's stdin.
+
+use color_eyre::eyre::{eyre, Result};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Run once the results are saved
+    pub on_complete: Option<String>,
+    /// Run once the results are saved, if any (test, tool) pair regressed
+    /// since the baseline, or any assertion failed
+    pub on_regression: Option<String>,
+}
+
+/// A (test, tool) pair that regressed since the baseline
+#[derive(Serialize, Debug)]
+pub struct Regression<'a> {
+    pub test: &'a str,
+    pub tool: &'a str,
+    /// Central values, in seconds
+    pub baseline: f64,
+    pub new: f64,
+}
+
+/// What is written to the stdin of a hook
+#[derive(Serialize, Debug)]
+pub struct Payload<'a, R> {
+    /// "complete" or "regression"
+    pub event: &'a str,
+    pub results_dir: &'a Path,
+    pub regressions: &'a [Regression<'a>],
+    pub failed_assertions: &'a [String],
+    /// As in results.json
+    pub results: R,
+}
+
+/// Runs the hook `cmd` with `shell`, writing `payload` to its stdin
+pub fn run<R: Serialize>(
+    cmd: &str,
+    shell: Option<&crate::shell::Shell>,
+    payload: &Payload<'_, R>,
+) -> Result<()> {
+    let cmd = cmd.replace("{RESULTS_DIR}", &payload.results_dir.to_string_lossy());
+    trace!("Running the {} hook `{}`", payload.event, cmd);
+    // Its output goes to stderr, so as not to mix into the results that
+    // `--json` prints on stdout
+    let mut child = crate::shell::command(shell, &cmd)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::io::stderr())
+        .spawn()
+        .map_err(|e| eyre!("Could not run `{}`: {}", cmd, e))?;
+    let mut stdin = child.stdin.take().unwrap();
+    // A hook that does not read its payload closes the pipe early,
+    // which is not an error of its own
+    if serde_json::to_writer(&mut stdin, payload).is_ok() {
+        let _ = stdin.write_all(b"\n");
+    }
+    drop(stdin);
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(eyre!("`{}` failed with status code {}", cmd, status));
+    }
+    Ok(())
+}
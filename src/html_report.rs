@@ -0,0 +1,459 @@
+//! A self-contained `report.html`, with no external scripts, styles or
+//! images, so that it can be shared or attached as a CI artifact as-is.
+
+use crate::{BenchifyResults, CellJson, ResultsJson};
+use color_eyre::eyre::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { padding: 0.2em 0.8em; border-bottom: 1px solid #ccc; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+tr.baseline td:first-child { font-weight: bold; }
+.fail { color: #b00; }
+.note { color: #666; font-size: 0.9em; }
+svg text { font-size: 12px; }
+";
+
+/// Width of the label column of the charts, and of the plot area to its
+/// right
+const LABEL_WIDTH: f64 = 200.;
+const PLOT_WIDTH: f64 = 500.;
+const ROW_HEIGHT: f64 = 24.;
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Horizontal box plot of the measured runs of each cell, in ms: the
+/// whiskers span the min to the max, and the box the interquartile
+/// range, with a line at the median
+fn box_plot(cells: &[&CellJson]) -> String {
+    let distributions: Vec<(&str, Vec<Duration>)> = cells
+        .iter()
+        .map(|c| {
+            let mut timings: Vec<Duration> = c
+                .runs
+                .iter()
+                .filter(|r| !r.warmup)
+                .map(|r| Duration::from_secs_f64(r.timing))
+                .collect();
+            timings.sort();
+            (c.tool.as_str(), timings)
+        })
+        .filter(|(_, t)| !t.is_empty())
+        .collect();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.;
+    let scale_max = distributions
+        .iter()
+        .filter_map(|(_, t)| t.last())
+        .fold(0., |a: f64, b| a.max(ms(*b)));
+    if distributions.is_empty() || scale_max <= 0. {
+        return String::new();
+    }
+    let x = |v: f64| LABEL_WIDTH + v / scale_max * PLOT_WIDTH;
+
+    let mut svg = String::new();
+    let height = ROW_HEIGHT * (distributions.len() + 1) as f64;
+    let _ = writeln!(
+        svg,
+        r#"<svg width="{}" height="{}" role="img">"#,
+        LABEL_WIDTH + PLOT_WIDTH + 20.,
+        height
+    );
+    for (i, (tool, t)) in distributions.iter().enumerate() {
+        let mid = ROW_HEIGHT * (i as f64 + 0.5);
+        let (min, q1, median, q3, max) = (
+            ms(t[0]),
+            ms(crate::percentile_of_sorted(t, 25.)),
+            ms(crate::percentile_of_sorted(t, 50.)),
+            ms(crate::percentile_of_sorted(t, 75.)),
+            ms(t[t.len() - 1]),
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+            LABEL_WIDTH - 8.,
+            mid + 4.,
+            escape(tool)
+        );
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" x2="{:.1}" y1="{mid:.1}" y2="{mid:.1}" stroke="#555"/>"##,
+            x(min),
+            x(max),
+        );
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#8cb4e0" stroke="#355c8c"><title>min {min:.3} / q1 {q1:.3} / median {median:.3} / q3 {q3:.3} / max {max:.3} ms</title></rect>"##,
+            x(q1),
+            mid - ROW_HEIGHT * 0.3,
+            (x(q3) - x(q1)).max(1.),
+            ROW_HEIGHT * 0.6,
+        );
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{m:.1}" x2="{m:.1}" y1="{:.1}" y2="{:.1}" stroke="#123"/>"##,
+            mid - ROW_HEIGHT * 0.3,
+            mid + ROW_HEIGHT * 0.3,
+            m = x(median),
+        );
+    }
+    axis(&mut svg, height, scale_max, "ms");
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Horizontal bar chart of `(label, value)`s, with the values labeled
+fn bar_chart(bars: &[(&str, f64)], unit: &str) -> String {
+    let scale_max = bars.iter().fold(0., |a: f64, (_, v)| a.max(*v));
+    if bars.is_empty() || scale_max <= 0. {
+        return String::new();
+    }
+    let x = |v: f64| LABEL_WIDTH + v / scale_max * PLOT_WIDTH;
+
+    let mut svg = String::new();
+    let height = ROW_HEIGHT * (bars.len() + 1) as f64;
+    let _ = writeln!(
+        svg,
+        r#"<svg width="{}" height="{}" role="img">"#,
+        LABEL_WIDTH + PLOT_WIDTH + 60.,
+        height
+    );
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let mid = ROW_HEIGHT * (i as f64 + 0.5);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+            LABEL_WIDTH - 8.,
+            mid + 4.,
+            escape(label)
+        );
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{LABEL_WIDTH}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#8cb4e0"/>"##,
+            mid - ROW_HEIGHT * 0.3,
+            x(*value) - LABEL_WIDTH,
+            ROW_HEIGHT * 0.6,
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}">{:.3}</text>"#,
+            x(*value) + 4.,
+            mid + 4.,
+            value
+        );
+    }
+    axis(&mut svg, height, scale_max, unit);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// An x axis at the bottom of a chart of the given `height`, from 0 to
+/// `scale_max`
+fn axis(svg: &mut String, height: f64, scale_max: f64, unit: &str) {
+    let y = height - ROW_HEIGHT;
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{LABEL_WIDTH}" x2="{:.1}" y1="{y:.1}" y2="{y:.1}" stroke="#999"/>"##,
+        LABEL_WIDTH + PLOT_WIDTH,
+    );
+    for i in 0..=4 {
+        let v = scale_max * i as f64 / 4.;
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{:.3}{}</text>"#,
+            LABEL_WIDTH + PLOT_WIDTH * i as f64 / 4.,
+            y + 16.,
+            v,
+            if i == 4 {
+                format!(" {}", unit)
+            } else {
+                String::new()
+            },
+        );
+    }
+}
+
+/// Table of the statistics of each cell of a single test
+fn test_table(html: &mut String, cells: &[&CellJson], baseline: Option<&str>) {
+    let _ = writeln!(
+        html,
+        "<table>\n<tr><th>Tool</th><th>Central (ms)</th><th>StdDev (ms)</th>\
+         <th>Min (ms)</th><th>Max (ms)</th><th>Runs</th><th>Ratio to {}</th></tr>",
+        escape(baseline.unwrap_or("baseline"))
+    );
+    for c in cells {
+        let class = if Some(c.tool.as_str()) == baseline {
+            r#" class="baseline""#
+        } else {
+            ""
+        };
+        let name = match &c.partial {
+            Some(_) => format!("{} (partial)", escape(&c.tool)),
+            None => escape(&c.tool),
+        };
+        match (&c.statistics, &c.error) {
+            (Some(s), _) => {
+                let _ = writeln!(
+                    html,
+                    "<tr{class}><td>{name}</td><td class=\"num\">{:.3}</td>\
+                     <td class=\"num\">{:.3}</td><td class=\"num\">{:.3}</td>\
+                     <td class=\"num\">{:.3}</td><td class=\"num\">{}</td>\
+                     <td class=\"num\">{}</td></tr>",
+                    s.central_value * 1000.,
+                    s.sample_stddev * 1000.,
+                    s.min * 1000.,
+                    s.max * 1000.,
+                    s.count,
+                    c.ratio_to_baseline
+                        .map(|r| format!("{:.3}", r))
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+            }
+            (None, error) => {
+                let _ = writeln!(
+                    html,
+                    "<tr{class}><td>{name}</td><td class=\"fail\" colspan=\"6\">FAIL: {}</td></tr>",
+                    escape(error.as_deref().unwrap_or("no runs"))
+                );
+            }
+        }
+    }
+    html.push_str("</table>\n");
+}
+
+/// Geometric means of each tool's ratios to the baseline, over the
+/// tests on which the baseline ran. `None` for tools that failed (or did
+/// not run) on any of those tests.
+pub(crate) fn geomeans<'c>(cells: &[&'c CellJson]) -> Vec<(&'c str, Option<f64>)> {
+    let mut tools: Vec<&str> = vec![];
+    for c in cells {
+        if !tools.contains(&c.tool.as_str()) {
+            tools.push(&c.tool);
+        }
+    }
+    let mut tests: Vec<&str> = vec![];
+    for c in cells.iter().filter(|c| c.ratio_to_baseline.is_some()) {
+        if !tests.contains(&c.test.as_str()) {
+            tests.push(&c.test);
+        }
+    }
+    if tests.is_empty() {
+        return vec![];
+    }
+    let mut res: Vec<(&str, Option<f64>)> = tools
+        .into_iter()
+        .map(|tool| {
+            let product = tests
+                .iter()
+                .map(|test| {
+                    cells
+                        .iter()
+                        .find(|c| c.tool == tool && c.test == *test)
+                        .and_then(|c| c.ratio_to_baseline)
+                })
+                .product::<Option<f64>>();
+            (tool, product.map(|p| p.powf(1. / tests.len() as f64)))
+        })
+        .collect();
+    res.sort_by(|a, b| {
+        a.1.unwrap_or(f64::INFINITY)
+            .partial_cmp(&b.1.unwrap_or(f64::INFINITY))
+            .unwrap()
+    });
+    res
+}
+
+fn geomean_section(html: &mut String, heading: &str, cells: &[&CellJson]) {
+    let geomeans = geomeans(cells);
+    if geomeans.is_empty() {
+        return;
+    }
+    let _ = writeln!(html, "<h3>{}</h3>", escape(heading));
+    let bars: Vec<(&str, f64)> = geomeans
+        .iter()
+        .filter_map(|(tool, g)| Some((*tool, (*g)?)))
+        .collect();
+    html.push_str(&bar_chart(&bars, "×"));
+    let failed: Vec<String> = geomeans
+        .iter()
+        .filter(|(_, g)| g.is_none())
+        .map(|(tool, _)| escape(tool))
+        .collect();
+    if !failed.is_empty() {
+        let _ = writeln!(
+            html,
+            "<p class=\"fail\">Failed on at least one test: {}</p>",
+            failed.join(", ")
+        );
+    }
+}
+
+fn render(json: &ResultsJson) -> Result<String> {
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>benchify results</title>\n<style>{}</style>\n</head>\n<body>",
+        STYLE
+    )?;
+    writeln!(html, "<h1>benchify results</h1>")?;
+
+    let m = json.metadata;
+    writeln!(html, "<table>")?;
+    for (what, value) in [
+        ("benchify version", Some(m.benchify_version.clone())),
+        ("Timestamp (Unix)", Some(m.timestamp.to_string())),
+        ("Hostname", m.hostname.clone()),
+        ("CPU", m.cpu.clone()),
+        ("Kernel", m.kernel.clone()),
+        ("Machine class", m.machine_class.clone()),
+        (
+            "Labels",
+            (!m.labels.is_empty()).then(|| m.formatted_labels()),
+        ),
+        ("Config hash", m.config_hash.clone()),
+        ("Baseline", json.baseline.map(str::to_string)),
+        (
+            "Estimator",
+            Some(serde_json::to_value(json.estimator)?.to_string()),
+        ),
+    ] {
+        if let Some(value) = value {
+            writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                what,
+                escape(value.trim_matches('"'))
+            )?;
+        }
+    }
+    for (tool, version) in &m.tool_versions {
+        writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(tool),
+            escape(version)
+        )?;
+    }
+    writeln!(html, "</table>")?;
+    if let Some(aborted) = json.aborted {
+        writeln!(
+            html,
+            "<p class=\"fail\">The suite was stopped early: {}</p>",
+            escape(aborted)
+        )?;
+    }
+
+    // Facet by machine class, then by group, in the order each first
+    // shows up
+    let mut classes: Vec<Option<&str>> = vec![];
+    for c in &json.cells {
+        if !classes.contains(&c.machine_class.as_deref()) {
+            classes.push(c.machine_class.as_deref());
+        }
+    }
+    let faceted = classes.len() > 1;
+    for class in classes {
+        let cells: Vec<&CellJson> = json
+            .cells
+            .iter()
+            .filter(|c| c.machine_class.as_deref() == class)
+            .collect();
+        if faceted {
+            writeln!(
+                html,
+                "<h1>Machine class: {}</h1>",
+                escape(class.unwrap_or("unlabeled"))
+            )?;
+        }
+        let mut groups: Vec<Option<&str>> = vec![];
+        for c in &cells {
+            if !groups.contains(&c.group.as_deref()) {
+                groups.push(c.group.as_deref());
+            }
+        }
+        let grouped = groups.iter().any(Option::is_some);
+        for group in groups {
+            let group_cells: Vec<&CellJson> = cells
+                .iter()
+                .copied()
+                .filter(|c| c.group.as_deref() == group)
+                .collect();
+            if grouped {
+                writeln!(html, "<h2>{}</h2>", escape(group.unwrap_or("Ungrouped")))?;
+            }
+            let mut tests: BTreeMap<usize, (&str, Vec<&CellJson>)> = BTreeMap::new();
+            let mut order: Vec<&str> = vec![];
+            for c in &group_cells {
+                let i = match order.iter().position(|t| *t == c.test) {
+                    Some(i) => i,
+                    None => {
+                        order.push(&c.test);
+                        order.len() - 1
+                    }
+                };
+                tests.entry(i).or_insert((&c.test, vec![])).1.push(c);
+            }
+            for (test, test_cells) in tests.values() {
+                writeln!(html, "<h3>{}</h3>", escape(test))?;
+                test_table(&mut html, test_cells, json.baseline);
+                let skipped: Vec<String> = json
+                    .skipped
+                    .iter()
+                    .filter(|s| s.test == *test)
+                    .map(|s| format!("{} ({})", escape(&s.tool), s.reason))
+                    .collect();
+                if !skipped.is_empty() {
+                    writeln!(
+                        html,
+                        "<p class=\"note\">Not run: {}</p>",
+                        skipped.join(", ")
+                    )?;
+                }
+                html.push_str(&box_plot(test_cells));
+            }
+            if let Some(group) = group {
+                geomean_section(
+                    &mut html,
+                    &format!("Geo mean of ratios in group {}", group),
+                    &group_cells,
+                );
+            }
+        }
+        if grouped {
+            writeln!(html, "<h2>All tests</h2>")?;
+        }
+        geomean_section(&mut html, "Geo mean of ratios", &cells);
+    }
+
+    // Everything, for anyone wanting to dig deeper. `</` is escaped so
+    // that no string in the data can close the script element early.
+    writeln!(
+        html,
+        "<p class=\"note\">The raw data of every run is embedded in this file, in the \
+         results.json format, as the <code>benchify-data</code> script element.</p>"
+    )?;
+    writeln!(
+        html,
+        "<script type=\"application/json\" id=\"benchify-data\">\n{}\n</script>",
+        serde_json::to_string(json)?.replace("</", "<\\/")
+    )?;
+    writeln!(html, "</body>\n</html>")?;
+    Ok(html)
+}
+
+impl BenchifyResults<'_> {
+    /// A self-contained HTML report of the results, with a table and a
+    /// box plot of the runs for each test, and the raw data inlined
+    pub(crate) fn html_report(&self) -> Result<String> {
+        render(&self.results_json()?)
+    }
+}
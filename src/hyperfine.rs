@@ -0,0 +1,192 @@
+//! Interop with hyperfine's JSON format (as written by its
+//! `--export-json`), which much tooling already speaks: the results can
+//! be exported in it with `--export-hyperfine`, and results previously
+//! collected with hyperfine imported with `--import-hyperfine`, to be
+//! reported as extra tools alongside the ones that were run.
+
+use crate::ResultsJson;
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Parameter that the test of a result is recorded in
+const TEST_PARAMETER: &str = "test";
+/// Parameter that the class of machine of a result is recorded in, if
+/// labeled
+const MACHINE_CLASS_PARAMETER: &str = "machine_class";
+
+#[derive(Serialize, Deserialize)]
+struct Export {
+    results: Vec<BenchmarkResult>,
+}
+
+/// A command's results, in seconds. Only `command` and `times` are
+/// needed on import.
+#[derive(Serialize, Deserialize)]
+struct BenchmarkResult {
+    command: String,
+    #[serde(default)]
+    mean: f64,
+    #[serde(default)]
+    stddev: Option<f64>,
+    #[serde(default)]
+    median: f64,
+    #[serde(default)]
+    user: f64,
+    #[serde(default)]
+    system: f64,
+    #[serde(default)]
+    min: f64,
+    #[serde(default)]
+    max: f64,
+    times: Option<Vec<f64>>,
+    #[serde(default)]
+    exit_codes: Vec<Option<i32>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    parameters: BTreeMap<String, String>,
+}
+
+/// A `[TEST=]FILE` given to `--import-hyperfine`
+#[derive(Debug, Clone)]
+pub struct ImportSpec {
+    test: Option<String>,
+    file: PathBuf,
+}
+
+impl ImportSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (test, file) = match spec.split_once('=') {
+            Some((test, file)) if !test.is_empty() => (Some(test.to_string()), file),
+            _ => (None, spec),
+        };
+        if file.is_empty() {
+            return Err(format!("expected [TEST=]FILE, got {:?}", spec));
+        }
+        Ok(ImportSpec {
+            test,
+            file: PathBuf::from(file),
+        })
+    }
+}
+
+/// The runs of a command imported from hyperfine's results
+#[derive(Debug)]
+pub struct Imported {
+    /// Name or ID of the test that the command was run on
+    pub test: String,
+    /// The command (or the name given to it with `--command-name`),
+    /// which is reported as the tool
+    pub command: String,
+    /// Timings of the runs, in seconds
+    pub times: Vec<f64>,
+    /// Exit code of each run, if known. `None` if terminated by a signal.
+    pub exit_codes: Vec<Option<i32>>,
+}
+
+/// Reads the results of each command in the hyperfine export named by
+/// `spec`. They are of the test in `spec`, or else of the one recorded
+/// in their `test` parameter (as by `--export-hyperfine`).
+pub fn import(spec: &ImportSpec) -> Result<Vec<Imported>> {
+    let file = &spec.file;
+    let reader = std::fs::File::open(file).or(Err(eyre!("Could not read {:?}", file)))?;
+    let export: Export = serde_json::from_reader(std::io::BufReader::new(reader))
+        .map_err(|e| eyre!("{:?} is not an export of hyperfine's: {}", file, e))?;
+    export
+        .results
+        .into_iter()
+        .map(|mut r| {
+            let test = match (spec.test.clone(), r.parameters.remove(TEST_PARAMETER)) {
+                (Some(test), _) | (None, Some(test)) => test,
+                (None, None) => {
+                    return Err(eyre!(
+                        "{:?} does not record the test that {:?} was run on. Give it with \
+                         --import-hyperfine TEST={}",
+                        file,
+                        r.command,
+                        file.display()
+                    ))
+                }
+            };
+            let times = r.times.filter(|t| !t.is_empty()).ok_or(eyre!(
+                "{:?} has no times for {:?}. Export it with hyperfine 1.6 or later.",
+                file,
+                r.command
+            ))?;
+            if let Some(time) = times.iter().find(|t| !t.is_finite() || **t < 0.) {
+                return Err(eyre!(
+                    "{:?} has an invalid time {} for {:?}. Times are in seconds, and cannot \
+                     be negative.",
+                    file,
+                    time,
+                    r.command
+                ));
+            }
+            Ok(Imported {
+                test,
+                command: r.command,
+                times,
+                exit_codes: r.exit_codes,
+            })
+        })
+        .collect()
+}
+
+fn mean(times: &[f64]) -> f64 {
+    times.iter().sum::<f64>() / times.len() as f64
+}
+
+/// Writes the measured runs of every successful cell of `json` into
+/// `path`, in the format of hyperfine's `--export-json`. Each (test,
+/// tool) pair is a command named after the tool, with the test (and the
+/// class of machine, if labeled) as parameters.
+pub fn export(path: &Path, json: &ResultsJson) -> Result<()> {
+    let mut results = vec![];
+    for c in json.cells.iter().filter(|c| c.error.is_none()) {
+        let runs: Vec<_> = c.runs.iter().filter(|r| !r.warmup).collect();
+        if runs.is_empty() {
+            continue;
+        }
+        let times: Vec<f64> = runs.iter().map(|r| r.timing).collect();
+        let mut sorted = times.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mid = (sorted.len() - 1) as f64 / 2.;
+        let median = (sorted[mid.floor() as usize] + sorted[mid.ceil() as usize]) / 2.;
+        let mean = mean(&times);
+        let stddev = (times.len() > 1).then(|| {
+            (times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (times.len() - 1) as f64)
+                .sqrt()
+        });
+        // CPU times are only known where the runs' resource usage was
+        let cpu_time = |metric: &str| {
+            let values: Vec<f64> = runs
+                .iter()
+                .filter_map(|r| r.metrics.get(metric).copied())
+                .collect();
+            if values.is_empty() {
+                0.
+            } else {
+                self::mean(&values)
+            }
+        };
+        let mut parameters = BTreeMap::from([(TEST_PARAMETER.to_string(), c.test.clone())]);
+        if let Some(class) = &c.machine_class {
+            parameters.insert(MACHINE_CLASS_PARAMETER.to_string(), class.clone());
+        }
+        results.push(BenchmarkResult {
+            command: c.tool.clone(),
+            mean,
+            stddev,
+            median,
+            user: cpu_time(crate::rusage::USER_TIME_METRIC),
+            system: cpu_time(crate::rusage::SYSTEM_TIME_METRIC),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            times: Some(times),
+            exit_codes: runs.iter().map(|r| r.exit_status).collect(),
+            parameters,
+        });
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&Export { results })?)?;
+    Ok(())
+}
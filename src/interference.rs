@@ -0,0 +1,108 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// How often the monitor probes for interference
+const INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What to do upon detecting that parallel jobs interfere with each
+/// other
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InterferencePolicy {
+    /// Only warn about it
+    Warn,
+    /// Warn, and run all remaining jobs one at a time
+    #[default]
+    Sequential,
+}
+
+/// Time taken by a fixed amount of CPU-bound work. The fastest of a few
+/// attempts, so that a single unlucky preemption does not count.
+pub fn probe() -> std::time::Duration {
+    (0..3)
+        .map(|_| {
+            let timer = std::time::Instant::now();
+            let mut x: u64 = 1;
+            for i in 0..2_000_000u64 {
+                x = std::hint::black_box(x.wrapping_mul(6364136223846793005).wrapping_add(i));
+            }
+            std::hint::black_box(x);
+            timer.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Periodically re-runs the calibration [`probe`] while parallel jobs
+/// are running, comparing against a baseline taken beforehand, when
+/// nothing else was running.
+pub struct InterferenceMonitor {
+    stop: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<bool>,
+    // Kept from the jobs, for the probes to run on
+    cpu: Option<usize>,
+}
+
+impl InterferenceMonitor {
+    /// Starts monitoring. The parallel jobs must not have been started
+    /// yet, so that the baseline is not affected by them. Probes that
+    /// are slower than the baseline by more than a fraction `threshold`
+    /// (twice in a row, to ride out blips) are reported, and handled as
+    /// per `policy`.
+    pub fn start(threshold: f64, policy: InterferencePolicy) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let cpu = crate::wait_for_free_cpu::reserve_cpu();
+        // Taken on the same CPU as the later probes, which need not be
+        // as fast as the others (e.g., on big.LITTLE machines), and
+        // before returning, so before the jobs start
+        let (baseline_tx, baseline_rx) = std::sync::mpsc::channel();
+        let handle = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                if let Some(cpu) = cpu {
+                    crate::affinity::pin_current_thread(cpu);
+                }
+                let baseline = probe();
+                let _ = baseline_tx.send(());
+                let mut inflated_probes = 0;
+                while !stop.load(Ordering::SeqCst) {
+                    std::thread::sleep(INTERVAL);
+                    let inflation = probe().as_secs_f64() / baseline.as_secs_f64() - 1.;
+                    if inflation <= threshold {
+                        inflated_probes = 0;
+                        continue;
+                    }
+                    inflated_probes += 1;
+                    if inflated_probes < 2 {
+                        continue;
+                    }
+                    crate::warnings::warn(format!(
+                        "Parallel jobs are interfering with each other (calibration probes are \
+                         {:.0}% slower than when run alone).",
+                        inflation * 100.
+                    ));
+                    if policy == InterferencePolicy::Sequential {
+                        info!("Running the remaining jobs one at a time.");
+                        crate::wait_for_free_cpu::restrict_free_cpus_to(1);
+                    }
+                    return true;
+                }
+                false
+            })
+        };
+        let _ = baseline_rx.recv();
+        InterferenceMonitor { stop, handle, cpu }
+    }
+
+    /// Stops monitoring, producing whether interference was detected
+    pub fn finish(self) -> bool {
+        self.stop.store(true, Ordering::SeqCst);
+        let interfered = self.handle.join().unwrap();
+        if let Some(cpu) = self.cpu {
+            crate::wait_for_free_cpu::unreserve_cpu(cpu);
+        }
+        interfered
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// Process group of the run in progress, if it is in a group of its own
+// (0 otherwise), which then does not get the terminal's Ctrl-C
+static RUN_GROUP: AtomicU32 = AtomicU32::new(0);
+
+/// Installs a Ctrl-C handler that requests benchify to wrap up. The
+/// first Ctrl-C lets the current (test, tool) pair finish up (keeping
+/// whatever runs have already completed), and skips everything else. A
+/// second Ctrl-C exits immediately.
+pub fn install_handler() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        eprintln!("\nInterrupted. Wrapping up (press Ctrl-C again to exit immediately).");
+        forward_interrupt();
+    })
+}
+
+#[cfg(unix)]
+fn forward_interrupt() {
+    let group = RUN_GROUP.load(Ordering::SeqCst);
+    if group != 0 {
+        // SAFETY: plain syscall
+        unsafe { libc::kill(-(group as libc::pid_t), libc::SIGINT) };
+    }
+}
+
+#[cfg(not(unix))]
+fn forward_interrupt() {}
+
+/// Records that the run in progress is in the process group `group`
+/// (or in none of its own, if `None`), so that Ctrl-C is forwarded to it
+pub fn set_run_group(group: Option<u32>) {
+    RUN_GROUP.store(group.unwrap_or(0), Ordering::SeqCst);
+}
+
+/// Whether the user has asked benchify to stop.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,153 @@
+//! A `junit.xml` of the results, for CI systems (e.g., Jenkins, GitLab
+//! or Buildkite) to render natively: each test is a test suite, and each
+//! tool on it a test case that took the mean time of its runs. Tools
+//! that failed, or whose runs timed out, are failed test cases, and the
+//! tools that were not run are skipped ones.
+
+use crate::{BenchifyResults, CellJson, ResultsJson};
+use color_eyre::eyre::Result;
+use std::fmt::Write;
+
+fn escape(s: &str) -> String {
+    crate::html_report::escape(s).replace('\'', "&apos;")
+}
+
+/// Name that the test of `cell` is reported under, distinguishing the
+/// classes of machines that it was run on, if labeled
+fn suite_name(cell: &CellJson) -> String {
+    match &cell.machine_class {
+        Some(class) => format!("{} [{}]", cell.test, class),
+        None => cell.test.clone(),
+    }
+}
+
+/// Why `cell` failed, if it did, as a message and a longer description
+fn failure(cell: &CellJson) -> Option<(String, String)> {
+    if let Some(error) = &cell.error {
+        let message = error.lines().next().unwrap_or_default().to_string();
+        return Some((message, error.clone()));
+    }
+    let censored = cell.statistics.as_ref().map_or(0, |s| s.censored);
+    (censored > 0).then(|| {
+        (
+            format!("{} runs timed out", censored),
+            "The runs that timed out are counted at the timeout, so the mean time is a \
+             lower bound."
+                .to_string(),
+        )
+    })
+}
+
+fn render(json: &ResultsJson) -> Result<String> {
+    let mut suites: Vec<(String, Vec<&CellJson>)> = vec![];
+    for c in &json.cells {
+        let name = suite_name(c);
+        match suites.iter_mut().find(|(suite, _)| *suite == name) {
+            Some((_, cells)) => cells.push(c),
+            None => suites.push((name, vec![c])),
+        }
+    }
+    let time = |c: &CellJson| c.statistics.as_ref().map_or(0., |s| s.mean);
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        xml,
+        r#"<testsuites name="{}" tests="{}" failures="{}" skipped="{}" time="{:.6}">"#,
+        crate::PROGRAM_NAME,
+        json.cells.len() + json.skipped.len(),
+        json.cells.iter().filter(|c| failure(c).is_some()).count(),
+        json.skipped.len(),
+        json.cells.iter().map(time).sum::<f64>()
+    )?;
+    let mut tests: Vec<&str> = vec![];
+    for (suite, cells) in &suites {
+        // Under the first suite of their test, whatever its class
+        let test = cells[0].test.as_str();
+        let skipped: Vec<_> = if tests.contains(&test) {
+            vec![]
+        } else {
+            tests.push(test);
+            json.skipped.iter().filter(|s| s.test == test).collect()
+        };
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}" time="{:.6}">"#,
+            escape(suite),
+            cells.len() + skipped.len(),
+            cells.iter().filter(|c| failure(c).is_some()).count(),
+            skipped.len(),
+            cells.iter().map(|c| time(c)).sum::<f64>()
+        )?;
+        for c in cells {
+            write!(
+                xml,
+                r#"    <testcase classname="{}" name="{}" time="{:.6}""#,
+                escape(suite),
+                escape(&c.tool),
+                time(c)
+            )?;
+            let failure = failure(c);
+            let mut out = vec![];
+            if let Some(s) = &c.statistics {
+                out.push(format!(
+                    "Mean: {:.3} ms ± {:.3} ms over {} runs",
+                    s.mean * 1000.,
+                    s.sample_stddev * 1000.,
+                    s.count
+                ));
+            }
+            if let Some(ratio) = c.ratio_to_baseline {
+                out.push(format!(
+                    "Ratio to {}: {:.3}",
+                    json.baseline.unwrap_or("the baseline"),
+                    ratio
+                ));
+            }
+            if let Some(partial) = &c.partial {
+                out.push(format!("Partial results: {}", partial));
+            }
+            if failure.is_none() && out.is_empty() {
+                writeln!(xml, "/>")?;
+                continue;
+            }
+            writeln!(xml, ">")?;
+            if let Some((message, description)) = failure {
+                writeln!(
+                    xml,
+                    r#"      <failure message="{}">{}</failure>"#,
+                    escape(&message),
+                    escape(&description)
+                )?;
+            }
+            if !out.is_empty() {
+                writeln!(
+                    xml,
+                    "      <system-out>{}</system-out>",
+                    escape(&out.join("\n"))
+                )?;
+            }
+            writeln!(xml, "    </testcase>")?;
+        }
+        for s in skipped {
+            writeln!(
+                xml,
+                r#"    <testcase classname="{}" name="{}" time="0"><skipped message="{}"/></testcase>"#,
+                escape(suite),
+                escape(&s.tool),
+                escape(s.reason)
+            )?;
+        }
+        writeln!(xml, "  </testsuite>")?;
+    }
+    writeln!(xml, "</testsuites>")?;
+    Ok(xml)
+}
+
+impl BenchifyResults<'_> {
+    /// A JUnit XML report of the results, with a test case for each
+    /// (test, tool) pair
+    pub(crate) fn junit_xml(&self) -> Result<String> {
+        render(&self.results_json()?)
+    }
+}
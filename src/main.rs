@@ -2,21 +2,67 @@
 
 use clap::Parser;
 use color_eyre::eyre::{self, eyre, Result};
-use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{HumanDuration, MultiProgress, ProgressBar};
 use log::{debug, error, info, trace, warn}; // error >> warn >> info >> debug >> trace
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::io::BufRead;
 use std::path::{Path, PathBuf};
 
+mod adapter;
+mod affinity;
+mod baseline;
+mod bootstrap;
+mod build_matrix;
+mod caches;
+mod cgroup;
+mod ci;
+mod clean;
+mod container;
+mod criterion;
+mod daemon;
+mod distributed;
+mod generate;
+mod git;
+mod github;
+mod gpu_sampler;
+mod history;
+mod hooks;
+mod html_report;
+mod hyperfine;
+mod interference;
+mod interrupt;
+mod junit;
+mod measurement;
+mod memory_sampler;
+mod metadata;
+mod perf;
+mod plan;
+mod plots;
+mod priority;
+mod prometheus;
+mod quiet;
+mod randomize;
+mod redact;
+mod remote;
+mod rusage;
+mod scheduler;
+mod shell;
+mod system;
+mod ui;
+mod validate;
 mod wait_for_free_cpu;
+mod warmup_drift;
+mod warnings;
+mod wasm;
+mod watchdog;
 
-const PROGRAM_NAME: &'static str = env!("CARGO_PKG_NAME", "expected to be built with cargo");
-const PROGRAM_VERSION: &'static str = env!("CARGO_PKG_VERSION", "expected to be built with cargo");
-const PROGRAM_AUTHORS: &'static str = env!("CARGO_PKG_AUTHORS", "expected to be built with cargo");
+const PROGRAM_NAME: &str = env!("CARGO_PKG_NAME", "expected to be built with cargo");
+const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION", "expected to be built with cargo");
+const PROGRAM_AUTHORS: &str = env!("CARGO_PKG_AUTHORS", "expected to be built with cargo");
 
 /// A convenient benchmarking tool
 #[derive(Parser, Debug)]
@@ -41,22 +87,357 @@ struct CmdLineOpts {
     /// `preparation.csv` at some point.
     #[clap(long)]
     store_preparation_time: bool,
+    /// Stop the whole suite on the first failing (test, tool) pair,
+    /// after running its cleanup. By default, failures are recorded
+    /// and benchmarking continues with the remaining pairs.
+    #[clap(long)]
+    fail_fast: bool,
+    /// Resume a suite that was stopped (e.g., interrupted, or by a crash
+    /// or reboot), reusing the runs of every (test, tool) pair that was
+    /// completed, as checkpointed in the results directory, and only
+    /// running the rest
+    #[clap(long, conflicts_with = "worker")]
+    resume: bool,
+    /// Only run the tests in this group. May be given multiple times.
+    #[clap(long, value_name = "NAME")]
+    group: Vec<String>,
+    /// Skip the tests in this group. May be given multiple times.
+    #[clap(long, value_name = "NAME")]
+    skip_group: Vec<String>,
+    /// Only run the tests whose name or ID matches this pattern: a glob
+    /// (e.g., `parse-*`), or a regex between slashes (e.g.,
+    /// `/^parse-(json|xml)$/`). May be given multiple times.
+    #[clap(long, value_name = "PATTERN", value_parser = TestPattern::parse)]
+    test: Vec<TestPattern>,
+    /// Only run this tool. May be given multiple times.
+    #[clap(long, value_name = "NAME")]
+    tool: Vec<String>,
+    /// Skip this tool. May be given multiple times.
+    #[clap(long, value_name = "NAME")]
+    skip_tool: Vec<String>,
+    /// Print the prepare, run and cleanup commands of every (test, tool)
+    /// pair, with placeholders interpolated, and exit without running
+    /// anything
+    #[clap(long, conflicts_with = "emit_plan")]
+    dry_run: bool,
+    /// Write the fully-resolved execution plan (which cells are run, in
+    /// which order, and how many times) to this file, and exit without
+    /// running anything
+    #[clap(long, value_name = "PATH")]
+    emit_plan: Option<PathBuf>,
+    /// Run exactly the execution plan in this file (as written by
+    /// `--emit-plan`), rather than planning afresh
+    #[clap(long, value_name = "PATH", conflicts_with = "emit_plan")]
+    from_plan: Option<PathBuf>,
+    /// Shard the benchmarks across this remote worker, reached over
+    /// SSH, rather than running them locally. The worker must have
+    /// benchify on its PATH, and a copy of the config (and test files)
+    /// in DIR. Its results are labeled with the machine class CLASS, if
+    /// given. May be given multiple times.
+    #[clap(long, value_name = "[CLASS=]HOST:DIR")]
+    worker: Vec<distributed::Worker>,
+    /// Label the results with the class of machine they were recorded
+    /// on (e.g., `desktop`), so that they are never aggregated with
+    /// results from other classes of machines
+    #[clap(long, value_name = "CLASS", value_parser = parse_machine_class)]
+    machine_class: Option<String>,
+    /// Record this note (e.g., "after switching allocator") with the
+    /// results, as context for when they are looked back at
+    #[clap(long, value_name = "TEXT")]
+    note: Option<String>,
+    /// Label the results with KEY=VALUE (e.g., `branch=feature-x`), for
+    /// telling experiments apart, e.g. in `history`. May be given
+    /// multiple times.
+    #[clap(long, value_name = "KEY=VALUE", value_parser = parse_label)]
+    label: Vec<(String, String)>,
+    /// Seed for randomized statistics (e.g., bootstrap confidence
+    /// intervals), overriding `seed` in `[report]`, to reproduce the
+    /// statistics of earlier results exactly
+    #[clap(long, value_name = "SEED")]
+    seed: Option<u64>,
+    /// Strip hostnames, usernames and absolute paths from the exported
+    /// metadata and plans, so that they can be shared publicly
+    #[clap(long)]
+    redact: bool,
+    /// Print the results (as in results.json) on stdout, as a single
+    /// JSON document, and everything meant for humans on stderr
+    #[clap(long)]
+    json: bool,
+    /// Exit with an error if anything casts doubt on the results: any
+    /// warning (e.g., about a noisy environment), partial results, or
+    /// timings that vary by more than `max_cv` in `[ci]`. The results
+    /// are still saved.
+    #[clap(long)]
+    strict: bool,
+    /// Before benchmarking, check the machine's settings that affect
+    /// reproducibility (CPU governor, turbo boost, SMT, ASLR and
+    /// swappiness), warning about any that hurt it, and record them in
+    /// the results' metadata
+    #[clap(long)]
+    check_system: bool,
+    /// Also store the results as the baseline NAME, for later runs to be
+    /// compared against with `--load-baseline`
+    #[clap(long, value_name = "NAME")]
+    save_baseline: Option<String>,
+    /// Compare the results against the baseline NAME (as stored by
+    /// `--save-baseline`), marking regressions and improvements
+    #[clap(long, value_name = "NAME")]
+    load_baseline: Option<String>,
+    /// Exit with an error if a tool got slower than allowed since the
+    /// baseline loaded with `--load-baseline`, on any test, e.g.
+    /// `main_tool <= 1.05x baseline`. May be given multiple times, in
+    /// addition to the `assertions` in `[ci]`.
+    #[clap(long = "assert", value_name = "ASSERTION", value_parser = ci::Assertion::parse)]
+    assertions: Vec<ci::Assertion>,
+    /// Also write the results to this file, in the JSON format of
+    /// hyperfine's `--export-json`
+    #[clap(long, value_name = "PATH")]
+    export_hyperfine: Option<PathBuf>,
+    /// Also write a compact markdown summary of the results to this
+    /// file, for a bot to post as a comment on a GitHub pull request
+    #[clap(long, value_name = "PATH")]
+    export_github_comment: Option<PathBuf>,
+    /// Report the results of each command in this JSON export of
+    /// hyperfine's as an extra tool, on the test TEST (or the one it
+    /// records, if exported with `--export-hyperfine`). May be given
+    /// multiple times.
+    #[clap(long, value_name = "[TEST=]FILE", value_parser = hyperfine::ImportSpec::parse)]
+    import_hyperfine: Vec<hyperfine::ImportSpec>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+/// Parses a machine class, which names a subdirectory of the results
+/// when results of several classes are saved together
+fn parse_machine_class(class: &str) -> Result<String, String> {
+    if class.is_empty()
+        || class.contains(std::path::is_separator)
+        || class.starts_with('.')
+        || class == "unlabeled"
+    {
+        return Err(format!(
+            "invalid machine class {:?}: it must be a file name other than \"unlabeled\"",
+            class
+        ));
+    }
+    Ok(class.to_string())
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Print a command template as it would be interpolated for a
+    /// test, in both the `run_args` and `run_cmd` forms, without
+    /// running anything.
+    Eval {
+        /// Name of the test to interpolate for
+        test: String,
+        /// Command template, e.g. `--arg {FILE} {...}`
+        #[clap(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        template: Vec<String>,
+    },
+    /// Compare the results in two results directories, warning about
+    /// any differences in the environments they were recorded in.
+    Compare {
+        /// Results directory to compare against
+        baseline: PathBuf,
+        /// Results directory to compare
+        other: PathBuf,
+    },
+    /// Run the execution plan read from stdin, writing the raw data of
+    /// its runs to stdout as CSV. Used on remote workers by `--worker`.
+    Worker,
+    /// Check the config for problems, without running any benchmarks.
+    /// Exits with a nonzero status if any are found.
+    Check {
+        /// Emit the problems found as JSON rather than a table
+        #[clap(long)]
+        json: bool,
+    },
+    /// Remove baselines, crash artifacts, run logs, preparation times
+    /// and leftover scratch directories that are older than allowed by
+    /// the `[retention]` policy.
+    Clean {
+        /// Remove what is older than this, e.g. `30d` or `12h`,
+        /// instead of the configured age
+        #[clap(long, value_name = "AGE")]
+        older_than: Option<String>,
+        /// Only list what would be removed
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Import timings of a (test, tool) pair that were measured outside
+    /// of benchify (e.g., on hardware it cannot reach) into the results
+    /// directory's data.csv, replacing any recorded there, to be
+    /// reported along with the rest with `--use-known-csv-data`.
+    ImportSamples {
+        /// CSV file with a `Timing (s)` column (in seconds), and
+        /// optionally any other columns of data.csv
+        file: PathBuf,
+        /// Name or ID of the test the timings are of
+        #[clap(long)]
+        test: String,
+        /// Name or ID of the tool the timings are of
+        #[clap(long)]
+        tool: String,
+    },
+    /// Print the history of the measured runs of each (test, tool) pair,
+    /// across invocations, as recorded with `history = true`.
+    History {
+        /// Only of this test
+        #[clap(long)]
+        test: Option<String>,
+        /// Only of this tool
+        #[clap(long)]
+        tool: Option<String>,
+        /// Only of invocations labeled KEY=VALUE with `--label`. May be
+        /// given multiple times.
+        #[clap(long, value_name = "KEY=VALUE", value_parser = parse_label)]
+        label: Vec<(String, String)>,
+    },
+    /// Benchmark revisions of the git repository of the current
+    /// directory against each other, each checked out into a worktree of
+    /// its own and built there as configured in `[git]`, as the builds of
+    /// a tool in one report. The first revision is the main tool.
+    Git {
+        /// Revisions to benchmark, e.g. `main HEAD~3 v1.2`
+        #[clap(required = true)]
+        revisions: Vec<String>,
+    },
+    /// List the tests, tools and tags of the config (as selected), and
+    /// the runner that each (test, tool) pair would use, without running
+    /// anything.
+    List {
+        /// Only list the names of these, one per line, e.g. for shell
+        /// completion
+        #[clap(value_enum)]
+        only: Option<Listing>,
+    },
+}
+
+/// Parses a `KEY=VALUE` label
+fn parse_label(label: &str) -> Result<(String, String), String> {
+    match label.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got {:?}", label)),
+    }
+}
+
+/// What `list` can be restricted to
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Listing {
+    Tests,
+    Tools,
+    Tags,
+    Groups,
+}
+
+/// Pattern that tests are selected by, with `--test`
+#[derive(Debug, Clone)]
+enum TestPattern {
+    Glob(glob::Pattern),
+    Regex(regex::Regex),
+}
+
+impl TestPattern {
+    fn parse(pattern: &str) -> Result<Self, String> {
+        match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            Some(regex) => regex::Regex::new(regex)
+                .map(TestPattern::Regex)
+                .map_err(|e| e.to_string()),
+            None => glob::Pattern::new(pattern)
+                .map(TestPattern::Glob)
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn matches(&self, test: &Test) -> bool {
+        let is_match = |s: &str| match self {
+            TestPattern::Glob(glob) => glob.matches(s),
+            TestPattern::Regex(regex) => regex.is_match(s),
+        };
+        is_match(&test.name) || is_match(&test.id())
+    }
+}
+
+impl std::fmt::Display for TestPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestPattern::Glob(glob) => write!(f, "{}", glob),
+            TestPattern::Regex(regex) => write!(f, "/{}/", regex),
+        }
+    }
 }
 
 type Args = Vec<String>;
 
 type ShellCommand = String;
 
-#[derive(Deserialize, Serialize, Debug)]
+/// Where a benchmarked run gets its stdin from
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Stdin {
+    Null,
+    Inherit,
+    File(String),
+    Cmd(ShellCommand),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Runner {
     warmup: Option<u32>,
+    /// How long to keep making warmup runs for (e.g., "10s"),
+    /// overriding the global `warmup_time`
+    warmup_time: Option<String>,
+    /// Minimum and maximum number of measured runs, overriding the
+    /// global ones
+    min_runs: Option<u32>,
+    max_runs: Option<u32>,
     prepare: Option<ShellCommand>,
+    /// Shell commands run just before and just after each run,
+    /// overriding the global `before_each_run` and `after_each_run`
+    before_each_run: Option<ShellCommand>,
+    after_each_run: Option<ShellCommand>,
+    /// Whether those also run around warmup runs, overriding the global
+    /// `warmup_hooks`
+    warmup_hooks: Option<bool>,
     run_args: Option<Args>,
     run_cmd: Option<ShellCommand>,
     cleanup: Option<ShellCommand>,
+    expect_failure: Option<bool>,
+    /// Exit codes that a run may exit with and still succeed, e.g. 1
+    /// for a grep that found nothing
+    success_exit_codes: Option<Vec<i32>>,
+    /// Whether a run succeeds whatever code it exits with
+    allow_failure: Option<bool>,
+    stdin: Option<Stdin>,
+    stdout_to: Option<String>,
+    crash_logs: Option<Vec<String>>,
+    timeout: Option<f64>,
+    shell: Option<shell::Shell>,
+    env: Option<BTreeMap<String, String>>,
+    /// CPUs that runs are pinned to, rather than those of the config
+    cpu_affinity: Option<Vec<usize>>,
 }
 
 impl Runner {
+    /// The runner, for the tool's `build`
+    fn for_build(&self, build: &build_matrix::RegisteredBuild) -> Runner {
+        let interpolated = |s: &Option<String>| s.as_deref().map(|s| build.interpolated_into(s));
+        Runner {
+            prepare: interpolated(&self.prepare),
+            before_each_run: interpolated(&self.before_each_run),
+            after_each_run: interpolated(&self.after_each_run),
+            run_args: self
+                .run_args
+                .as_ref()
+                .map(|args| args.iter().map(|a| build.interpolated_into(a)).collect()),
+            run_cmd: interpolated(&self.run_cmd),
+            cleanup: interpolated(&self.cleanup),
+            stdout_to: interpolated(&self.stdout_to),
+            ..self.clone()
+        }
+    }
+
     pub fn needs_file(&self) -> bool {
         if let Some(cmd) = &self.prepare {
             if cmd.contains("{FILE}") {
@@ -68,6 +449,14 @@ impl Runner {
                 return true;
             }
         }
+        for cmd in [&self.before_each_run, &self.after_each_run]
+            .into_iter()
+            .flatten()
+        {
+            if cmd.contains("{FILE}") {
+                return true;
+            }
+        }
         if let Some(run_args) = &self.run_args {
             if run_args.iter().any(|a| a.contains("{FILE}")) {
                 return true;
@@ -78,62 +467,290 @@ impl Runner {
                 return true;
             }
         }
+        if let Some(Stdin::File(s) | Stdin::Cmd(s)) = &self.stdin {
+            if s.contains("{FILE}") {
+                return true;
+            }
+        }
         false
     }
 }
 
 pub type Tag = String;
 
+/// The program for a tool: either a single executable, or an
+/// executable followed by arguments that are always passed to it, such
+/// as an interpreter and the script it should run.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Program {
+    Executable(String),
+    Argv(Args),
+}
+
+impl Program {
+    /// The executable to run, searched on PATH
+    fn executable(&self) -> &str {
+        match self {
+            Program::Executable(exe) => exe,
+            Program::Argv(argv) => argv.first().map(String::as_str).unwrap_or(""),
+        }
+    }
+
+    /// Arguments always passed to the executable, before any others
+    fn args(&self) -> &[String] {
+        match self {
+            Program::Executable(_) => &[],
+            Program::Argv(argv) => argv.get(1..).unwrap_or(&[]),
+        }
+    }
+
+    fn command(&self) -> std::process::Command {
+        let mut command = std::process::Command::new(self.executable());
+        command.args(self.args());
+        command
+    }
+}
+
+impl std::fmt::Display for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Program::Executable(exe) => write!(f, "{}", exe),
+            Program::Argv(argv) => write!(f, "{}", argv.join(" ")),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Tool {
     name: String,
-    program: String,
+    id: Option<String>,
+    aliases: Option<Vec<String>>,
+    program: Option<Program>,
+    /// Ecosystem whose usual commands run `target`, instead of `program`
+    kind: Option<adapter::Kind>,
+    /// What is run, for tools with a `kind` (e.g., a Cargo binary target)
+    target: Option<String>,
+    /// The program that runs `target`, once built
+    #[serde(skip)]
+    built_program: std::sync::Mutex<Option<Program>>,
     existence_confirmation: Option<Args>,
     install_instructions: String,
+    /// Shell that the tool's shell commands are run with, unless set by
+    /// the runner
+    shell: Option<shell::Shell>,
+    /// Environment variables for the tool's processes
+    env: Option<BTreeMap<String, String>>,
+    /// Image whose containers the tool's processes are run in, rather
+    /// than on the host
+    container: Option<String>,
+    /// Engine that runs the containers of `container` (e.g., "podman")
+    container_engine: Option<String>,
+    /// Host that the tool's processes are run on over SSH, rather than
+    /// locally (the global one, if not set)
+    remote: Option<remote::RemoteConfig>,
+    daemon: Option<daemon::DaemonConfig>,
+    /// Shell command run once per suite, before any of the tool's
+    /// preparations (e.g., to build it)
+    setup: Option<ShellCommand>,
+    /// Shell command run once per suite, after all of the tool's runs
+    /// and cleanups
+    teardown: Option<ShellCommand>,
+    /// Wasm runtime that `program` is, to run `module` with
+    runtime: Option<wasm::Runtime>,
+    /// Wasm module that is run, for tools with a `runtime`
+    module: Option<String>,
+    /// Exported function of `module` that is called, rather than its WASI
+    /// entry point
+    invoke: Option<String>,
+    /// `module`, once compiled ahead of time
+    #[serde(skip)]
+    compiled_module: std::sync::Mutex<Option<PathBuf>>,
+    /// Build configurations that the tool is benchmarked under, each as
+    /// a tool of its own
+    builds: Option<Vec<build_matrix::Build>>,
+    /// For tools registered for a build of another, the build
+    #[serde(skip)]
+    registered_build: Option<build_matrix::RegisteredBuild>,
     runners: HashMap<Tag, Runner>,
 }
 
+lazy_static::lazy_static! {
+    /// Scratch directory, private to this invocation of benchify, that
+    /// `{TMPDIR}` is interpolated to
+    static ref SCRATCH_DIR: PathBuf =
+        std::env::temp_dir().join(format!("benchify-{}", std::process::id()));
+}
+
+/// Removes the scratch directory, and anything left in it, if it was
+/// ever used
+fn remove_scratch_dir() {
+    if SCRATCH_DIR.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&*SCRATCH_DIR) {
+            error!("Could not remove {:?}: {}", *SCRATCH_DIR, e);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn core_dumped(status: &std::process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.core_dumped()
+}
+
+#[cfg(not(unix))]
+fn core_dumped(_status: &std::process::ExitStatus) -> bool {
+    false
+}
+
+/// Reads all of `buf`, keeping at most `limit` bytes of it. Anything
+/// beyond that is drained and discarded, and replaced by a marker
+/// noting how much was dropped.
+fn read_capped(mut buf: impl std::io::Read, limit: usize) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut res = vec![];
+    (&mut buf).take(limit as u64).read_to_end(&mut res)?;
+    let dropped = std::io::copy(&mut buf, &mut std::io::sink())?;
+    if dropped > 0 {
+        res.extend_from_slice(format!("\n[... truncated {} bytes ...]\n", dropped).as_bytes());
+    }
+    Ok(res)
+}
+
+fn delayed_blocking_read_capped(
+    buf: impl std::io::Read + std::marker::Send + 'static,
+    limit: usize,
+) -> std::thread::JoinHandle<std::io::Result<Vec<u8>>> {
+    std::thread::spawn(move || read_capped(buf, limit))
+}
+
 fn delayed_blocking_read_lossy_string(
-    mut buf: impl std::io::Read + std::marker::Send + 'static,
+    buf: impl std::io::Read + std::marker::Send + 'static,
+    limit: usize,
 ) -> std::thread::JoinHandle<Result<String>> {
     std::thread::spawn(move || {
-        let mut res = vec![];
-        buf.read_to_end(&mut res)?;
+        let res = read_capped(buf, limit)?;
         Ok(String::from_utf8_lossy(&res).into_owned())
     })
 }
 
 impl Tool {
+    /// The tool, or if it declares builds, a tool for each of them,
+    /// named after the tool and the build (e.g., "foo (lto)")
+    fn expand_builds(self) -> (Vec<Tool>, Option<build_matrix::BuildSet>) {
+        let Some(builds) = &self.builds else {
+            return (vec![self], None);
+        };
+        let tools: Vec<Tool> = builds
+            .iter()
+            .map(|build| {
+                let registered = build_matrix::RegisteredBuild::new(&self.id(), build);
+                let named = |name: &str| format!("{} ({})", name, build.name);
+                let interpolated = |s: &String| registered.interpolated_into(s);
+                Tool {
+                    name: named(&self.name),
+                    id: Some(format!("{}-{}", self.id(), slugify(&build.name))),
+                    aliases: self
+                        .aliases
+                        .as_ref()
+                        .map(|aliases| aliases.iter().map(|a| named(a)).collect()),
+                    program: self.program.as_ref().map(|program| match program {
+                        Program::Executable(exe) => Program::Executable(interpolated(exe)),
+                        Program::Argv(argv) => {
+                            Program::Argv(argv.iter().map(interpolated).collect())
+                        }
+                    }),
+                    kind: self.kind,
+                    target: self.target.clone(),
+                    built_program: Default::default(),
+                    existence_confirmation: self.existence_confirmation.clone(),
+                    install_instructions: self.install_instructions.clone(),
+                    shell: self.shell.clone(),
+                    env: self.env.clone(),
+                    container: self.container.as_ref().map(interpolated),
+                    container_engine: self.container_engine.clone(),
+                    remote: self.remote.clone(),
+                    daemon: self.daemon.clone(),
+                    setup: self.setup.as_ref().map(interpolated),
+                    teardown: self.teardown.as_ref().map(interpolated),
+                    runtime: self.runtime,
+                    module: self.module.as_ref().map(interpolated),
+                    invoke: self.invoke.clone(),
+                    compiled_module: Default::default(),
+                    builds: None,
+                    runners: self
+                        .runners
+                        .iter()
+                        .map(|(tag, runner)| (tag.clone(), runner.for_build(&registered)))
+                        .collect(),
+                    registered_build: Some(registered),
+                }
+            })
+            .collect();
+        let build_set = build_matrix::BuildSet {
+            tool: self.name.clone(),
+            builds: builds
+                .iter()
+                .zip(&tools)
+                .map(|(build, tool)| (build.name.clone(), tool.name.clone()))
+                .collect(),
+        };
+        (tools, Some(build_set))
+    }
+
+    /// Runs `cmd` as the `cmdtype` of the tool for `test` (interpolated
+    /// for it), or for the whole suite
     fn run_cmd(
         &self,
+        config: &BenchifyConfig,
         cmdtype: &str,
-        test: &Test,
+        test: Option<&Test>,
         cmd: &ShellCommand,
         opb: Option<ProgressBar>,
+        cpu: Option<usize>,
     ) -> Result<()> {
+        let ui = config.ui();
         let pb = if let Some(opb) = opb {
             opb
         } else {
-            ProgressBar::new_spinner()
+            ui.spinner()
         };
         pb.reset_elapsed();
-        pb.set_style(
-            ProgressStyle::default_spinner().template("{spinner:.green} {msg} ({elapsed_precise})"),
-        );
-        pb.set_message(&format!("[{}] [{}] {}", test.name, self.name, cmdtype));
+        pb.set_style(ui.spinner_style());
+        ui.start_ticking(&pb);
+        let what = match test {
+            Some(test) => format!("[{}] [{}] {}", test.name, self.name, cmdtype),
+            None => format!("[{}] {}", self.name, cmdtype),
+        };
+        pb.set_message(&what);
+        let logged_phase = ui.phase(what);
 
-        trace!("{} of tool {} for {}", cmdtype, self.name, &test.tag);
-        let cmd = test.interpolated_into(cmd);
+        // For the tag of the test, if any
+        let for_what = test.map_or("the suite", |test| &test.tag);
+        trace!("{} of tool {} for {}", cmdtype, self.name, for_what);
+        let cmd = match test {
+            Some(test) => test.interpolated_into(cmd),
+            None => cmd.clone(),
+        };
         trace!("Running `{}`", cmd);
-        let mut process = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(cmd.clone())
+        let mut process = self.wrapped(test, self.shell_command(config, test, &cmd));
+        if let Some(cpu) = cpu {
+            affinity::pin(&mut process, &[cpu]);
+        }
+        config.priority().prepare().apply(&mut process);
+        let mut process = process
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .spawn()?;
-        let stdout = delayed_blocking_read_lossy_string(process.stdout.take().unwrap());
-        let stderr = delayed_blocking_read_lossy_string(process.stderr.take().unwrap());
+        let stdout = delayed_blocking_read_lossy_string(
+            process.stdout.take().unwrap(),
+            config.max_output_size(),
+        );
+        let stderr = delayed_blocking_read_lossy_string(
+            process.stderr.take().unwrap(),
+            config.max_output_size(),
+        );
         let status = loop {
             match process.try_wait()? {
                 None => {
@@ -150,7 +767,7 @@ impl Tool {
         } else {
             error!(
                 "{} of {} for {} failed with status code {}",
-                cmdtype, self.name, test.tag, status
+                cmdtype, self.name, for_what, status
             );
             error!("COMMAND:\n{}\n\n", cmd);
             error!("STDOUT:\n{}\n\n", stdout.join().unwrap()?);
@@ -159,25 +776,33 @@ impl Tool {
                 "{} of {} for {} failed with status code {}",
                 cmdtype,
                 self.name,
-                test.tag,
+                for_what,
                 status
             ));
         }
         pb.finish_and_clear();
+        logged_phase.finish();
 
         Ok(())
     }
 
     pub fn prepare(
         &self,
+        config: &BenchifyConfig,
         test: &Test,
         opb: Option<ProgressBar>,
+        cpu: Option<usize>,
         store_preparation_time: bool,
     ) -> Result<()> {
+        if let (Some(remote), Some(file)) = (&self.remote, &test.file) {
+            if Path::new(file).is_file() {
+                remote.stage(Path::new(file))?;
+            }
+        }
         let runner = &self.runners[&test.tag];
         if let Some(cmd) = &runner.prepare {
             let timer = std::time::Instant::now();
-            self.run_cmd("Preparation", test, cmd, opb)?;
+            self.run_cmd(config, "Preparation", Some(test), cmd, opb, cpu)?;
             if store_preparation_time {
                 let elapsed = timer.elapsed().as_secs_f64();
                 let prep_dir = Path::new("./preparation");
@@ -192,710 +817,5368 @@ impl Tool {
         }
     }
 
-    pub fn run(&self, test: &Test) -> Result<std::time::Duration> {
-        let stdin = if let Some(cmd) = &test.stdin_from_cmd {
-            use std::os::unix::io::{AsRawFd, FromRawFd};
-            let cmd = std::process::Command::new("sh")
-                .arg("-c")
-                .arg(test.interpolated_into(cmd))
-                .stdin(std::process::Stdio::piped())
-                .stdout(std::process::Stdio::piped())
-                .spawn()?;
-            cmd.stdout.unwrap().into()
-        } else {
-            std::process::Stdio::null()
-        };
-        let runner = &self.runners[&test.tag];
-        let (timer, output) = if let Some(run_args) = &runner.run_args {
-            let args = test.interpolated_into_args(run_args);
-            trace!("Running {} with args {:?}", self.program, args);
-            let timer = std::time::Instant::now();
-            let output = std::process::Command::new(&self.program)
-                .args(args)
-                .stdin(stdin)
-                .output()?;
-            (timer, output)
-        } else if let Some(run_cmd) = &runner.run_cmd {
-            let cmd = test.interpolated_into(run_cmd);
-            trace!("Running {} with shell command {:?}", self.program, cmd);
-            let timer = std::time::Instant::now();
-            let output = std::process::Command::new("sh")
-                .arg("-c")
-                .arg(cmd)
-                .stdin(stdin)
-                .output()?;
-            (timer, output)
-        } else {
-            unreachable!()
+    /// The program for the tool, which for tools with a `kind` is the
+    /// build of their target if they have been built
+    fn program(&self) -> Program {
+        match (self.kind, &self.target) {
+            (Some(kind), Some(target)) => self
+                .built_program
+                .lock()
+                .unwrap()
+                .clone()
+                .unwrap_or_else(|| kind.program(target)),
+            _ => self
+                .program
+                .clone()
+                .unwrap_or_else(|| Program::Executable(String::new())),
+        }
+    }
+
+    /// Builds the target of a tool with a `kind`, if not built yet
+    fn build(&self, config: &BenchifyConfig) -> Result<()> {
+        let (Some(kind), Some(target)) = (self.kind, &self.target) else {
+            return Ok(());
         };
-        let elapsed_time = timer.elapsed();
-        if output.status.success() {
-            trace!("Generated output\n{:?}", output);
-            info!("Ran {} in {} ms", self.name, elapsed_time.as_millis());
-        } else {
-            error!("Command exited with non zero status code {}", output.status);
-            return Err(eyre!("Exit code {}", output.status));
-        }
-        if let Some(true) = test.stdout_is_timing {
-            let timing = std::time::Duration::from_secs_f64(
-                String::from_utf8(output.stdout.to_owned())?
-                    .trim()
-                    .parse()?,
+        // Held while building, so that concurrent runs wait for it
+        let mut built = self.built_program.lock().unwrap();
+        if built.is_none() {
+            info!("Building {} for {}", target, self.name);
+            *built = Some(
+                kind.build(target, &self.env(config, None))?
+                    .unwrap_or_else(|| kind.program(target)),
             );
-            if timing > elapsed_time {
-                Err(eyre!(
-                    "Program lied about elapsed time at stdout: {:?} is not less than {:?}",
-                    timing,
-                    elapsed_time
-                ))
-            } else {
-                Ok(timing)
-            }
-        } else {
-            Ok(elapsed_time)
         }
+        Ok(())
     }
 
-    pub fn cleanup(&self, test: &Test) -> Result<()> {
-        if let Some(cmd) = &self.runners[&test.tag].cleanup {
-            self.run_cmd("Clean up", test, cmd, None)
-        } else {
-            Ok(())
+    /// The tool's Wasm module, compiled ahead of time (on first use), so
+    /// that no run pays for the engine compiling it
+    fn compiled_module(&self) -> Result<Option<PathBuf>> {
+        let (Some(runtime), Some(module)) = (self.runtime, &self.module) else {
+            return Ok(None);
+        };
+        // Held while compiling, so that concurrent runs wait for it
+        let mut compiled = self.compiled_module.lock().unwrap();
+        if compiled.is_none() {
+            trace!("Compiling {} for {} with {}", module, self.name, runtime);
+            *compiled = Some(wasm::precompile(
+                runtime,
+                &self.program(),
+                module,
+                &self.id(),
+            )?);
         }
+        Ok(compiled.clone())
     }
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-pub struct Test {
-    name: String,
-    tag: Tag,
-    file: Option<String>,
-    extra_args: Option<Vec<String>>,
-    stdin_from_cmd: Option<String>,
-    stdout_is_timing: Option<bool>,
-}
 
-impl Test {
-    pub fn interpolated_into(&self, s: &str) -> String {
-        let extra_args = &self.extra_args.as_ref().unwrap_or(&vec![]).join(" ");
-        let extra_args_quoted = &self
-            .extra_args
-            .as_ref()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|x| format!("'{}'", x))
-            .collect::<Vec<_>>()
-            .join(" ");
-        let s = s
-            .replace("{NAME}", &self.name)
-            .replace("{TAG}", &self.tag)
-            .replace("\"{...}\"", &extra_args)
-            .replace("'{...}'", &extra_args)
-            .replace("{...}", &extra_args_quoted);
-        if let Some(file) = &self.file {
-            s.replace("{FILE}", file)
-        } else {
-            s
-        }
+    /// Shell that the shell commands of the tool (or of its runner for
+    /// `test`) are run with, as set by the runner, the tool or the config,
+    /// in that order of precedence
+    fn shell<'a>(
+        &'a self,
+        config: &'a BenchifyConfig,
+        test: Option<&Test>,
+    ) -> Option<&'a shell::Shell> {
+        test.and_then(|test| self.runners[&test.tag].shell.as_ref())
+            .or(self.shell.as_ref())
+            .or(config.shell.as_ref())
     }
 
-    pub fn interpolated_into_args(&self, args: &Args) -> Args {
-        let mut res = vec![];
-        for arg in args {
-            if arg == "{...}" || arg == "..." {
-                res.append(&mut self.extra_args.as_ref().unwrap_or(&vec![]).clone());
-            } else {
-                res.push(self.interpolated_into(arg));
+    /// Environment variables set for the tool's processes (or those of
+    /// its runner for `test`), merged from the config, the tool, the
+    /// runner and the test, in that order, with later ones taking
+    /// precedence. Values are interpolated for `test`.
+    fn env(&self, config: &BenchifyConfig, test: Option<&Test>) -> BTreeMap<String, String> {
+        let mut env = BTreeMap::new();
+        let runner = test.map(|test| &self.runners[&test.tag]);
+        for vars in [
+            &config.env,
+            &self.env,
+            runner.map_or(&None, |r| &r.env),
+            test.map_or(&None, |t| &t.env),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for (key, value) in vars {
+                let value = match test {
+                    Some(test) => test.interpolated_into(value),
+                    None => value.clone(),
+                };
+                env.insert(key.clone(), value);
             }
         }
-        res
+        env
     }
-}
 
-#[derive(Deserialize, Serialize, Debug)]
-pub struct BenchifyConfig {
-    benchify_version: usize,
-    parallel_prep: Option<bool>,
-    warmup: Option<u32>,
-    min_runs: Option<u32>,
-    max_runs: Option<u32>,
-    main_tool: Option<String>,
-    results_dir: Option<PathBuf>,
-    tags: HashSet<Tag>,
-    tools: Vec<Tool>,
-    tests: Vec<Test>,
-}
+    /// A command that runs the shell command `cmd` of the tool (or of
+    /// its runner for `test`), with its shell and environment
+    fn shell_command(
+        &self,
+        config: &BenchifyConfig,
+        test: Option<&Test>,
+        cmd: &str,
+    ) -> std::process::Command {
+        let mut command = shell::command(self.shell(config, test), cmd);
+        command.envs(self.env(config, test));
+        command
+    }
 
-impl BenchifyConfig {
-    fn min_runs(&self) -> u32 {
-        self.min_runs.unwrap_or(10)
+    /// What the tool's processes are run through, if they are not run
+    /// directly. It is then that (e.g., the `docker` or `ssh` client)
+    /// which is started, and measured, locally.
+    fn wrapper(&self) -> Option<&'static str> {
+        if self.remote.is_some() {
+            Some("remote")
+        } else if self.container.is_some() {
+            Some("container")
+        } else {
+            None
+        }
     }
 
-    fn max_runs(&self) -> u32 {
-        self.max_runs.unwrap_or(1000)
+    /// Settings of `config` (or of the tool's runners) that measure, or
+    /// control, the locally started process of each run, which for a
+    /// wrapped tool is not the tool's
+    fn process_settings(&self, config: &BenchifyConfig) -> Vec<&'static str> {
+        let mut settings = vec![];
+        if config.perf_counters().is_some() {
+            settings.push("perf_counters");
+        }
+        if config.cgroup.is_some() {
+            settings.push("[cgroup]");
+        }
+        if config.sample_memory() {
+            settings.push("sample_memory");
+        }
+        if config.cpu_affinity.is_some() || self.runners.values().any(|r| r.cpu_affinity.is_some())
+        {
+            settings.push("cpu_affinity");
+        }
+        if !config.priority().run().is_default() {
+            settings.push("the nice and ionice of [priority]");
+        }
+        settings
     }
 
-    fn results_dir(&self) -> PathBuf {
-        self.results_dir
-            .clone()
-            .unwrap_or(PathBuf::from("./benchify-results/"))
+    /// Engine that runs the containers of the tool's `container`
+    fn container_engine(&self) -> &str {
+        self.container_engine
+            .as_deref()
+            .unwrap_or(container::DEFAULT_ENGINE)
     }
 
-    fn parallel_prep(&self) -> bool {
-        match self.parallel_prep {
-            None => false,
-            Some(b) => b,
+    /// `command`, run wherever the tool's processes are run: inside a
+    /// container of its `container` (with the file of `test`, if any,
+    /// mounted), on the host of its `remote`, or as is
+    fn wrapped(
+        &self,
+        test: Option<&Test>,
+        command: std::process::Command,
+    ) -> std::process::Command {
+        if let Some(remote) = &self.remote {
+            return remote.wrap(&command);
         }
+        let Some(image) = &self.container else {
+            return command;
+        };
+        let mounts: Vec<PathBuf> = test
+            .and_then(|test| test.file.as_ref())
+            .and_then(|file| std::fs::canonicalize(file).ok())
+            .and_then(|file| file.parent().map(Path::to_path_buf))
+            .into_iter()
+            .collect();
+        container::wrap(self.container_engine(), image, &command, &mounts)
     }
 
-    fn confirm_config_sanity(&self) {
-        let mut errored = false;
-        if self.benchify_version != 1 {
-            errored = true;
-            error!(
-                "Found config for version {}. Currently only version 1 is supported.",
-                self.benchify_version
-            )
-        }
+    /// The command that is benchmarked for `test`
+    fn run_command(&self, config: &BenchifyConfig, test: &Test) -> std::process::Command {
+        self.wrapped(Some(test), self.local_run_command(config, test))
+    }
 
-        if self.min_runs() > self.max_runs() {
-            errored = true;
-            error!(
-                "Min runs ({}) is greater than max runs ({}).",
-                self.min_runs(),
-                self.max_runs(),
-            )
+    /// The command that is benchmarked for `test`, were it run locally
+    /// and outside of any container
+    fn local_run_command(&self, config: &BenchifyConfig, test: &Test) -> std::process::Command {
+        let runner = &self.runners[&test.tag];
+        if let Some(run_args) = &runner.run_args {
+            let args = test.interpolated_into_args(run_args);
+            let program = self.program();
+            trace!("Running {} with args {:?}", program, args);
+            let mut command = match (self.runtime, &self.module) {
+                (Some(runtime), Some(module)) => {
+                    // Not compiled yet when only planning
+                    let compiled = self.compiled_module.lock().unwrap().clone();
+                    let precompiled = compiled.is_some();
+                    let module = compiled.unwrap_or_else(|| PathBuf::from(module));
+                    wasm::command(
+                        runtime,
+                        &program,
+                        &module,
+                        precompiled,
+                        self.invoke.as_deref(),
+                    )
+                }
+                _ => program.command(),
+            };
+            command.args(args).envs(self.env(config, Some(test)));
+            command
+        } else if let Some(run_cmd) = &runner.run_cmd {
+            let cmd = test.interpolated_into(run_cmd);
+            trace!("Running {} with shell command {:?}", self.name, cmd);
+            self.shell_command(config, Some(test), &cmd)
+        } else {
+            unreachable!()
         }
+    }
 
-        if self.results_dir().is_file() {
-            errored = true;
-            error!(
-                "Results dir {:?} is already exists as a file.",
-                self.results_dir()
-            )
-        }
+    /// Where runs on `test` get their stdin from
+    fn stdin(&self, test: &Test) -> Stdin {
+        self.runners[&test.tag]
+            .stdin
+            .clone()
+            .or_else(|| test.stdin_from_file.clone().map(Stdin::File))
+            .or_else(|| test.stdin_from_cmd.clone().map(Stdin::Cmd))
+            .unwrap_or(Stdin::Null)
+    }
 
-        if let Some(tool) = &self.main_tool {
-            if !self.tools.iter().any(|t| &t.name == tool) {
-                errored = true;
-                error!(
-                    "Main tool {:?} is not on of the known tools. Expected one of {:?}",
-                    tool,
-                    self.tools.iter().map(|t| &t.name).collect::<Vec<_>>()
-                )
-            }
-        }
+    /// Shell command run before each run on `test`, as set by the runner
+    /// or the config, in that order of precedence
+    fn before_each_run<'a>(
+        &'a self,
+        config: &'a BenchifyConfig,
+        test: &Test,
+    ) -> Option<&'a ShellCommand> {
+        self.runners[&test.tag]
+            .before_each_run
+            .as_ref()
+            .or(config.before_each_run.as_ref())
+    }
 
-        let mut tag_needs_file_due_to = HashMap::new();
+    /// Shell command run after each run on `test`, as set by the runner
+    /// or the config, in that order of precedence
+    fn after_each_run<'a>(
+        &'a self,
+        config: &'a BenchifyConfig,
+        test: &Test,
+    ) -> Option<&'a ShellCommand> {
+        self.runners[&test.tag]
+            .after_each_run
+            .as_ref()
+            .or(config.after_each_run.as_ref())
+    }
 
-        for tool in &self.tools {
-            debug!("Confirming sanity for tool {}", tool.name);
+    /// Whether `before_each_run` and `after_each_run` also run around
+    /// warmup runs on `test`
+    fn warmup_hooks(&self, config: &BenchifyConfig, test: &Test) -> bool {
+        self.runners[&test.tag]
+            .warmup_hooks
+            .or(config.warmup_hooks)
+            .unwrap_or(true)
+    }
 
-            trace!("Confirmer runner commands");
-            for (tag, runner) in &tool.runners {
-                if !(runner.run_cmd.is_some() ^ runner.run_args.is_some()) {
-                    errored = true;
-                    error!(
-                        "Runner {:?} for {:?} should have only one of run_cmd and run_args set. \
-                         Got {:?} and {:?} respectively.",
-                        tag, tool.name, runner.run_cmd, runner.run_args
-                    );
+    /// Runs `cmd`, the `hook` command of the runner (or config) on
+    /// `test`, untimed
+    fn run_hook(&self, config: &BenchifyConfig, test: &Test, hook: &str, cmd: &str) -> Result<()> {
+        let cmd = test.interpolated_into(cmd);
+        trace!("Running {} `{}`", hook, cmd);
+        let mut command = self.wrapped(Some(test), self.shell_command(config, Some(test), &cmd));
+        config.priority().prepare().apply(&mut command);
+        let output = command.stdin(std::process::Stdio::null()).output()?;
+        if !output.status.success() {
+            error!("{} `{}` exited with {}", hook, cmd, output.status);
+            error!("STDERR:\n{}\n\n", String::from_utf8_lossy(&output.stderr));
+            return Err(eyre!("{} exit code {}", hook, output.status));
+        }
+        Ok(())
+    }
+
+    /// Runs the tool once on `test`, as its `iteration`th run
+    pub fn run(&self, config: &BenchifyConfig, test: &Test, iteration: usize) -> Result<Sample> {
+        let runner = &self.runners[&test.tag];
+        let mut stdin = None;
+        let stdin_stdio = match self.stdin(test) {
+            Stdin::Null => std::process::Stdio::null(),
+            Stdin::Inherit => std::process::Stdio::inherit(),
+            Stdin::File(file) => {
+                let file = test.interpolated_into(&file);
+                trace!("Using {} as stdin", file);
+                std::fs::File::open(&file)
+                    .map_err(|e| eyre!("Could not open stdin file {}: {}", file, e))?
+                    .into()
+            }
+            Stdin::Cmd(cmd) => {
+                // The stdin generator is run to completion and its output
+                // buffered _before_ the timer starts, so that a slow
+                // generator does not get counted against the tool being
+                // benchmarked.
+                let cmd = test.interpolated_into(&cmd);
+                trace!("Generating stdin with `{}`", cmd);
+                let output = self
+                    .shell_command(config, Some(test), &cmd)
+                    .stdin(std::process::Stdio::null())
+                    .output()?;
+                if !output.status.success() {
+                    error!("Stdin generator `{}` exited with {}", cmd, output.status);
+                    return Err(eyre!("Stdin generator exit code {}", output.status));
                 }
+                stdin = Some(output.stdout);
+                std::process::Stdio::piped()
             }
-
-            trace!("Confirming tags");
-            let tool_tags = tool.runners.keys().cloned().collect();
-            if !self.tags.is_subset(&tool_tags) {
-                errored = true;
-                error!(
-                    "Not all runners for {} have been defined. Missing: {:?}",
-                    tool.name,
-                    tool_tags.difference(&self.tags)
+        };
+        self.build(config)?;
+        self.compiled_module()?;
+        // Untimed, as is everything before the timer starts
+        if config.drop_caches() {
+            caches::drop()?;
+        }
+        let mut command = self.run_command(config, test);
+        let perf_stat = match config.perf_counters() {
+            Some(events) => {
+                let (perf_command, perf_stat) = perf::PerfStat::wrap(&command, events)?;
+                command = perf_command;
+                Some(perf_stat)
+            }
+            None => None,
+        };
+        if let Some(cpus) = self.cpu_affinity(config, test) {
+            affinity::pin(&mut command, cpus);
+        }
+        config.priority().run().apply(&mut command);
+        let cgroup = match &config.cgroup {
+            Some(cgroup) => {
+                let cgroup = cgroup::RunCgroup::create(cgroup)?;
+                cgroup.enter(&mut command)?;
+                Some(cgroup)
+            }
+            None => None,
+        };
+        // Large outputs can be sent straight to disk, so that buffering
+        // them up does not skew measurements of high-output tools. A
+        // stdout that is validated is sent to where it is saved for
+        // validation, so that it is never cut at `max_output_size`.
+        let stdout_to = runner
+            .stdout_to
+            .as_ref()
+            .map(|path| PathBuf::from(test.interpolated_into(path)))
+            .or_else(|| {
+                test.validation()
+                    .filter(|validation| validation.output.is_none())
+                    .map(|_| self.output_path(config, test))
+            });
+        let stdout_stdio = if let Some(path) = &stdout_to {
+            trace!("Sending stdout to {:?}", path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::File::create(path)
+                .map_err(|e| eyre!("Could not create stdout file {:?}: {}", path, e))?
+                .into()
+        } else {
+            std::process::Stdio::piped()
+        };
+        command
+            .stdin(stdin_stdio)
+            .stdout(stdout_stdio)
+            .stderr(std::process::Stdio::piped());
+        let padding = config.randomize_environment().then(randomize::padding);
+        if let Some(padding) = &padding {
+            command.env(randomize::PADDING_VAR, padding);
+        }
+        let timeout = self.timeout(test);
+        // With a timeout, the run gets a process group of its own, so
+        // that anything it starts is also killed when it times out
+        #[cfg(unix)]
+        if timeout.is_some() {
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        }
+        let command_description = format!("{:?}", command);
+        let timer = std::time::Instant::now();
+        let mut child = command.spawn()?;
+        let spawn_overhead = timer.elapsed();
+        let watchdog = timeout.map(|timeout| {
+            interrupt::set_run_group(Some(child.id()));
+            watchdog::Watchdog::start(child.id(), timeout, config.timeout_grace_period())
+        });
+        let memory_sampler = config
+            .sample_memory()
+            .then(|| memory_sampler::MemorySampler::start(child.id()));
+        let gpu_sampler = config.sample_gpu().then(gpu_sampler::GpuSampler::start);
+        let stdin_writer = stdin.map(|data| {
+            use std::io::Write;
+            let mut pipe = child.stdin.take().unwrap();
+            std::thread::spawn(move || pipe.write_all(&data))
+        });
+        let stdout = child
+            .stdout
+            .take()
+            .map(|out| delayed_blocking_read_capped(out, config.max_output_size()));
+        let stderr =
+            delayed_blocking_read_capped(child.stderr.take().unwrap(), config.max_output_size());
+        let timed_out = match watchdog {
+            Some(watchdog) => {
+                rusage::wait_for_exit(&mut child)?;
+                interrupt::set_run_group(None);
+                watchdog.disarm()
+            }
+            None => false,
+        };
+        let (status, resource_usage) = rusage::wait(&mut child)?;
+        let exited = timer.elapsed();
+        let peak_rss = memory_sampler.and_then(|sampler| sampler.finish());
+        let gpu_usage = gpu_sampler.and_then(|sampler| sampler.finish());
+        let output = std::process::Output {
+            status,
+            stdout: match stdout {
+                Some(stdout) => stdout.join().unwrap()?,
+                None => vec![],
+            },
+            stderr: stderr.join().unwrap()?,
+        };
+        let elapsed_time = timer.elapsed();
+        let collect_overhead = elapsed_time - exited;
+        if let Some(stdin_writer) = stdin_writer {
+            // The tool is free to stop reading its stdin early, so a
+            // broken pipe here is not an error.
+            match stdin_writer.join().unwrap() {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+                _ => {}
+            }
+        }
+        let succeeded = self.exited_successfully(test, output.status);
+        let failed = timed_out || succeeded == self.expects_failure(test);
+        if let Err(e) = self.save_run_log(config, test, iteration, &output, failed) {
+            error!("Could not save the output of {}: {}", self.name, e);
+        }
+        if let (true, Some(timeout)) = (timed_out, timeout) {
+            info!("Stopped {} after timing out ({:?})", self.name, timeout);
+            return Ok(Sample {
+                iteration: 0,
+                warmup: false,
+                timing: timeout,
+                exit_status: None,
+                censored: true,
+                metrics: BTreeMap::from([(
+                    "Wall Time (s)".to_string(),
+                    elapsed_time.as_secs_f64(),
+                )]),
+            });
+        }
+        match (succeeded, self.expects_failure(test)) {
+            (true, false) => {
+                trace!("Generated output\n{:?}", output);
+                info!("Ran {} in {} ms", self.name, elapsed_time.as_millis());
+            }
+            (false, true) => {
+                trace!("Generated output\n{:?}", output);
+                info!(
+                    "Ran {} in {} ms (failed as expected with {})",
+                    self.name,
+                    elapsed_time.as_millis(),
+                    output.status
                 );
             }
-            if !self.tags.is_superset(&tool_tags) {
-                errored = true;
-                error!(
-                    "Invalid set of runner tags found for {}. Found extra: {:?}",
-                    tool.name,
-                    self.tags.difference(&tool_tags)
+            (true, true) => {
+                error!("Command was expected to fail, but exited successfully");
+                self.collect_crash_artifacts(
+                    config,
+                    test,
+                    &command_description,
+                    child.id(),
+                    &output,
                 );
+                return Err(eyre!("Unexpected success"));
             }
-
-            trace!("Confirming runnability");
-            let mut ec_cmd = std::process::Command::new(&tool.program);
-            let ec_cmd = if let Some(ec_args) = &tool.existence_confirmation {
-                ec_cmd.args(ec_args)
-            } else {
-                &mut ec_cmd
+            (false, false) => {
+                error!("Command exited with non zero status code {}", output.status);
+                self.collect_crash_artifacts(
+                    config,
+                    test,
+                    &command_description,
+                    child.id(),
+                    &output,
+                );
+                return Err(eyre!("Exit code {}", output.status));
+            }
+        }
+        if let Some(validation) = test.validation() {
+            self.save_output(
+                config,
+                test,
+                &validation,
+                &output.stdout,
+                stdout_to.as_deref(),
+            )?;
+        }
+        let timing = if let Some(true) = test.stdout_is_timing {
+            let stdout = match &stdout_to {
+                Some(path) => std::fs::read(path)?,
+                None => output.stdout.to_owned(),
             };
+            let timing =
+                std::time::Duration::from_secs_f64(String::from_utf8(stdout)?.trim().parse()?);
+            if timing > elapsed_time {
+                return Err(eyre!(
+                    "Program lied about elapsed time at stdout: {:?} is not less than {:?}",
+                    timing,
+                    elapsed_time
+                ));
+            }
+            timing
+        } else {
+            elapsed_time
+        };
+        // Recorded so that the fidelity of the harness itself can be
+        // audited, e.g., that `stdout_is_timing` timings are plausible.
+        let mut metrics = BTreeMap::from([
+            ("Wall Time (s)".to_string(), elapsed_time.as_secs_f64()),
+            (
+                "Spawn Overhead (s)".to_string(),
+                spawn_overhead.as_secs_f64(),
+            ),
+            (
+                "Collect Overhead (s)".to_string(),
+                collect_overhead.as_secs_f64(),
+            ),
+        ]);
+        if let Some(peak_rss) = peak_rss {
+            metrics.insert("Peak RSS (KiB)".to_string(), peak_rss as f64);
+        }
+        if let Some(padding) = padding {
+            metrics.insert("Environment Padding (B)".to_string(), padding.len() as f64);
+        }
+        // Those of a wrapped tool's client, rather than of the tool
+        if let Some(resource_usage) = resource_usage.filter(|_| self.wrapper().is_none()) {
+            for (name, value) in resource_usage.metrics(config.record_faults_and_switches()) {
+                metrics.insert(name.to_string(), value);
+            }
+        }
+        if let Some(gpu_usage) = gpu_usage {
+            metrics.insert(
+                "GPU Utilization (%)".to_string(),
+                gpu_usage.mean_utilization,
+            );
+            metrics.insert(
+                "Peak GPU Memory (MiB)".to_string(),
+                gpu_usage.peak_memory as f64 / (1024. * 1024.),
+            );
+        }
+        if let Some(perf_stat) = perf_stat {
+            metrics.extend(perf_stat.finish()?);
+        }
+        if let Some(cgroup) = cgroup {
+            metrics.extend(cgroup.finish());
+        }
+        Ok(Sample {
+            iteration: 0,
+            warmup: false,
+            timing,
+            exit_status: output.status.code(),
+            censored: false,
+            metrics,
+        })
+    }
 
-            if ec_cmd.output().is_err() {
-                info!(
-                    "Ran {} with args {:?}",
-                    tool.program, tool.existence_confirmation
-                );
-                errored = true;
-                error!(
-                    "Could not confirm that {} can be executed.\n\t\
-                     Suggested install instructions:\n\t\t\t{}\n",
-                    tool.name, tool.install_instructions,
-                );
+    /// Directory that the stdout and stderr of the tool's runs on `test`
+    /// are saved to, as per `save_run_logs`
+    fn run_logs_dir(&self, config: &BenchifyConfig, test: &Test) -> PathBuf {
+        config
+            .results_dir()
+            .join("logs")
+            .join(slugify(&test.id()))
+            .join(slugify(&self.id()))
+    }
+
+    /// Saves the stdout and stderr of the `iteration`th run on `test` as
+    /// `run_<iteration>.{out,err}`, if `save_run_logs` calls for it
+    fn save_run_log(
+        &self,
+        config: &BenchifyConfig,
+        test: &Test,
+        iteration: usize,
+        output: &std::process::Output,
+        failed: bool,
+    ) -> Result<()> {
+        let dir = self.run_logs_dir(config, test);
+        match config.save_run_logs() {
+            RunLogs::None => return Ok(()),
+            RunLogs::Failed if !failed => return Ok(()),
+            RunLogs::Last if dir.exists() => std::fs::remove_dir_all(&dir)?,
+            _ => {}
+        }
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(format!("run_{}.out", iteration)), &output.stdout)?;
+        std::fs::write(dir.join(format!("run_{}.err", iteration)), &output.stderr)?;
+        Ok(())
+    }
+
+    /// Where the output of the tool's runs on `test` is saved to, for
+    /// validation
+    fn output_path(&self, config: &BenchifyConfig, test: &Test) -> PathBuf {
+        config
+            .results_dir()
+            .join(cell_dir(&test.tag, &cell_id(&test.id(), &self.id())))
+            .join(validate::OUTPUT)
+    }
+
+    /// Saves the output of a run on `test` for validation: its stdout
+    /// (wherever it was sent to), or the file that `validation` names
+    fn save_output(
+        &self,
+        config: &BenchifyConfig,
+        test: &Test,
+        validation: &validate::ValidateConfig,
+        stdout: &[u8],
+        stdout_to: Option<&Path>,
+    ) -> Result<()> {
+        let path = self.output_path(config, test);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let from = validation
+            .output
+            .as_ref()
+            .map(|output| PathBuf::from(test.interpolated_into(output)))
+            .or_else(|| stdout_to.map(Path::to_path_buf));
+        match from {
+            // Already written there by the run
+            Some(from) if from == path => Ok(()),
+            Some(from) => std::fs::copy(&from, &path).map(|_| ()).map_err(|e| {
+                eyre!(
+                    "Could not save the output {:?} of {}: {}",
+                    from,
+                    self.name,
+                    e
+                )
+            }),
+            None => Ok(std::fs::write(&path, stdout)?),
+        }
+    }
+
+    /// Saves whatever could help debug a failed run into the `crashes/`
+    /// subdirectory of the cell's directory, if `collect_crashes` is
+    /// enabled: the command that was run, its output, any core dump it
+    /// left behind, and the runner's `crash_logs`.
+    fn collect_crash_artifacts(
+        &self,
+        config: &BenchifyConfig,
+        test: &Test,
+        command: &str,
+        pid: u32,
+        output: &std::process::Output,
+    ) {
+        if !config.collect_crashes() {
+            return;
+        }
+        let dir = config
+            .results_dir()
+            .join(cell_dir(&test.tag, &cell_id(&test.id(), &self.id())))
+            .join("crashes")
+            .join(pid.to_string());
+        let collect = || -> Result<()> {
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(dir.join("command"), format!("{}\n", command))?;
+            std::fs::write(dir.join("status"), format!("{}\n", output.status))?;
+            std::fs::write(dir.join("stdout"), &output.stdout)?;
+            std::fs::write(dir.join("stderr"), &output.stderr)?;
+            // With the default `core_pattern`, core dumps (if allowed at
+            // all by `ulimit -c`) are left in the working directory.
+            if core_dumped(&output.status) {
+                for core in [format!("core.{}", pid), "core".to_string()] {
+                    let core = Path::new(&core);
+                    if core.is_file() {
+                        let dest = dir.join(core);
+                        if std::fs::rename(core, &dest).is_err() {
+                            std::fs::copy(core, &dest)?;
+                        }
+                    }
+                }
+            }
+            let runner = &self.runners[&test.tag];
+            for pattern in runner.crash_logs.iter().flatten() {
+                let pattern = test.interpolated_into(pattern);
+                for log in glob::glob(&pattern)?.flatten() {
+                    if let Some(name) = log.file_name().filter(|_| log.is_file()) {
+                        std::fs::create_dir_all(dir.join("logs"))?;
+                        std::fs::copy(&log, dir.join("logs").join(name))?;
+                    }
+                }
+            }
+            Ok(())
+        };
+        match collect() {
+            Ok(()) => error!("Saved crash artifacts to {:?}", dir),
+            Err(e) => error!("Could not save crash artifacts to {:?}: {}", dir, e),
+        }
+    }
+
+    /// The program that is confirmed to be executable, and the arguments
+    /// it is run with to do so. For tools with a `kind`, this is the
+    /// ecosystem's toolchain, run with `--version` by default. For tools
+    /// with a `container` or a `remote`, the program is run inside a
+    /// container or on the host (with the arguments, if any).
+    fn existence_confirmation(&self) -> (Program, Option<Args>) {
+        let (program, ec_args) = self.local_existence_confirmation();
+        let Some(remote) = &self.remote else {
+            return (program, ec_args);
+        };
+        let mut command = program.command();
+        command.args(ec_args.iter().flatten());
+        let ssh = remote.wrap(&command);
+        let argv = std::iter::once(ssh.get_program())
+            .chain(ssh.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        (Program::Argv(argv), ec_args.map(|_| vec![]))
+    }
+
+    /// [`Tool::existence_confirmation`], were the tool run locally
+    fn local_existence_confirmation(&self) -> (Program, Option<Args>) {
+        if let Some(image) = &self.container {
+            let program = self.program();
+            let argv = [self.container_engine(), "run", "--rm", image]
+                .into_iter()
+                .map(str::to_string)
+                .chain(std::iter::once(program.executable().to_string()))
+                .chain(program.args().iter().cloned())
+                .collect();
+            return (Program::Argv(argv), self.existence_confirmation.clone());
+        }
+        match self.kind {
+            Some(kind) => (
+                Program::Executable(kind.toolchain().to_string()),
+                Some(
+                    self.existence_confirmation
+                        .clone()
+                        .unwrap_or_else(|| vec!["--version".to_string()]),
+                ),
+            ),
+            None => (self.program(), self.existence_confirmation.clone()),
+        }
+    }
+
+    /// Whether `program` can be executed, as confirmed by running it
+    /// with the `existence_confirmation` arguments.
+    pub fn is_runnable(&self) -> bool {
+        let (program, ec_args) = self.existence_confirmation();
+        let mut ec_cmd = program.command();
+        if let Some(ec_args) = &ec_args {
+            ec_cmd.args(ec_args);
+        }
+        info!("Running {} with args {:?}", program, ec_args);
+        ec_cmd.output().is_ok()
+    }
+
+    /// The version of the tool, as reported by the first line of output
+    /// of its existence confirmation.
+    pub fn version(&self) -> Option<String> {
+        let (program, ec_args) = self.existence_confirmation();
+        let args: Args = program.args().iter().chain(&ec_args?).cloned().collect();
+        metadata::command_output(program.executable(), &args)
+    }
+
+    /// Stable identifier of the tool, for use by automation
+    pub fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| slugify(&self.name))
+    }
+
+    /// Whether `name` is the current or a former name of the tool
+    pub fn is_known_as(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().flatten().any(|a| a == name)
+    }
+
+    /// Runs the tool on `test` as in [`Tool::run`], between
+    /// `before_each_run` and `after_each_run`, unless it is a `warmup`
+    /// run and those are not run around warmup runs
+    fn run_with_hooks(
+        &self,
+        config: &BenchifyConfig,
+        test: &Test,
+        iteration: usize,
+        warmup: bool,
+    ) -> Result<Sample> {
+        if warmup && !self.warmup_hooks(config, test) {
+            return self.run(config, test, iteration);
+        }
+        if let Some(cmd) = self.before_each_run(config, test) {
+            self.run_hook(config, test, "before_each_run", cmd)?;
+        }
+        let sample = self.run(config, test, iteration);
+        if let Some(cmd) = self.after_each_run(config, test) {
+            // Run even after a failed run, so that whatever it resets is
+            // reset, but it is the run's failure that is reported
+            let after = self.run_hook(config, test, "after_each_run", cmd);
+            if sample.is_ok() {
+                after?;
+            }
+        }
+        sample
+    }
+
+    /// Runs the tool on `test` as in [`Tool::run_with_hooks`], handling
+    /// a timeout as per the test's `on_timeout`
+    pub fn run_within_timeout(
+        &self,
+        config: &BenchifyConfig,
+        test: &Test,
+        iteration: usize,
+        warmup: bool,
+    ) -> Result<Sample> {
+        let sample = self.run_with_hooks(config, test, iteration, warmup)?;
+        if !sample.censored {
+            return Ok(sample);
+        }
+        match test.on_timeout.unwrap_or_default() {
+            TimeoutPolicy::Fail => Err(eyre!("Timed out after {:?}", sample.timing)),
+            TimeoutPolicy::Retry => {
+                info!("Retrying {} after a timeout", self.name);
+                let retry = self.run_with_hooks(config, test, iteration, warmup)?;
+                if retry.censored {
+                    return Err(eyre!("Timed out after {:?}, twice", retry.timing));
+                }
+                Ok(retry)
+            }
+            TimeoutPolicy::Censor => Ok(sample),
+        }
+    }
+
+    /// CPUs that the runs of this tool on `test` are pinned to, if any
+    pub fn cpu_affinity<'c>(
+        &'c self,
+        config: &'c BenchifyConfig,
+        test: &Test,
+    ) -> Option<&'c [usize]> {
+        self.runners[&test.tag]
+            .cpu_affinity
+            .as_deref()
+            .or(config.cpu_affinity.as_deref())
+    }
+
+    /// Time after which a run on `test` is stopped, if any
+    pub fn timeout(&self, test: &Test) -> Option<std::time::Duration> {
+        // Invalid timeouts are diagnosed, but are no reason to panic
+        test.timeout
+            .or(self.runners[&test.tag].timeout)
+            .and_then(|t| std::time::Duration::try_from_secs_f64(t).ok())
+    }
+
+    /// Whether a nonzero exit is the expected outcome of running this
+    /// tool on `test`.
+    pub fn expects_failure(&self, test: &Test) -> bool {
+        test.expect_failure
+            .or(self.runners[&test.tag].expect_failure)
+            .unwrap_or(false)
+    }
+
+    /// Whether a run on `test` that exited with `status` succeeded: it
+    /// exited with 0, or with any of its `success_exit_codes`, or with
+    /// any code at all if failure is allowed. Being killed by a signal
+    /// never counts.
+    fn exited_successfully(&self, test: &Test, status: std::process::ExitStatus) -> bool {
+        let runner = &self.runners[&test.tag];
+        if test.allow_failure.or(runner.allow_failure).unwrap_or(false) {
+            return status.code().is_some();
+        }
+        match test
+            .success_exit_codes
+            .as_ref()
+            .or(runner.success_exit_codes.as_ref())
+        {
+            Some(codes) => status.code().is_some_and(|code| codes.contains(&code)),
+            None => status.success(),
+        }
+    }
+
+    /// Whether runs on `test` may exit with a nonzero code and succeed
+    fn accepts_nonzero_exits(&self, test: &Test) -> bool {
+        let runner = &self.runners[&test.tag];
+        test.allow_failure.or(runner.allow_failure).unwrap_or(false)
+            || test
+                .success_exit_codes
+                .as_ref()
+                .or(runner.success_exit_codes.as_ref())
+                .is_some_and(|codes| codes.iter().any(|&code| code != 0))
+    }
+
+    /// Runs the tool's `setup`, if any, before anything else of it in
+    /// the suite
+    pub fn setup(&self, config: &BenchifyConfig) -> Result<()> {
+        match &self.setup {
+            Some(cmd) => self.run_cmd(config, "Setup", None, cmd, None, None),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the tool's `teardown`, if any, once the suite is done with it
+    pub fn teardown(&self, config: &BenchifyConfig) -> Result<()> {
+        match &self.teardown {
+            Some(cmd) => self.run_cmd(config, "Teardown", None, cmd, None, None),
+            None => Ok(()),
+        }
+    }
+
+    pub fn cleanup(&self, config: &BenchifyConfig, test: &Test) -> Result<()> {
+        if let Some(cmd) = &self.runners[&test.tag].cleanup {
+            self.run_cmd(config, "Clean up", Some(test), cmd, None, None)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Test {
+    name: String,
+    id: Option<String>,
+    aliases: Option<Vec<String>>,
+    tag: Tag,
+    group: Option<String>,
+    file: Option<String>,
+    /// Glob pattern of files (e.g., "corpus/*.json"), that the test is
+    /// expanded into, one test per matching file
+    files: Option<String>,
+    /// How the test's input file is generated, over a list of sizes
+    /// that the test is expanded into
+    generate: Option<generate::GenerateConfig>,
+    extra_args: Option<Vec<String>>,
+    stdin_from_cmd: Option<String>,
+    /// File that runs on this test read their stdin from, opened
+    /// directly rather than piped in through a command
+    stdin_from_file: Option<String>,
+    stdout_is_timing: Option<bool>,
+    expect_failure: Option<bool>,
+    /// Exit codes that runs on this test may exit with and still
+    /// succeed, overriding the runner's
+    success_exit_codes: Option<Vec<i32>>,
+    allow_failure: Option<bool>,
+    throughput: Option<Throughput>,
+    timeout: Option<f64>,
+    on_timeout: Option<TimeoutPolicy>,
+    /// Minimum and maximum number of measured runs of each tool on this
+    /// test, overriding the runner's and global ones
+    min_runs: Option<u32>,
+    max_runs: Option<u32>,
+    /// Total time, in seconds, that the measured runs of each tool on
+    /// this test should take, overriding the global `target_time`
+    target_time: Option<f64>,
+    /// Environment variables for the processes of all tools on this test
+    env: Option<BTreeMap<String, String>>,
+    /// How the output of each tool on this test is checked for
+    /// correctness
+    validate: Option<validate::Validate>,
+    /// Values of each parameter, whose cross product the test is
+    /// expanded into, one test per combination
+    params: Option<BTreeMap<String, Vec<toml::Value>>>,
+    /// The combination of parameter values this test was expanded for,
+    /// interpolated as `{<parameter>}`
+    #[serde(skip)]
+    param_values: Vec<(String, String)>,
+}
+
+/// What to do with a run that exceeds its test's timeout, once it has
+/// been killed
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutPolicy {
+    /// Fail the (test, tool) pair
+    #[default]
+    Fail,
+    /// Retry the run once, failing the (test, tool) pair if it times
+    /// out again
+    Retry,
+    /// Record the run as censored, taking the timeout as its timing
+    Censor,
+}
+
+/// Which runs have their stdout and stderr saved, for post-mortem
+/// debugging
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RunLogs {
+    #[default]
+    None,
+    All,
+    /// Only the latest run of each (test, tool) pair
+    Last,
+    /// Only runs that failed or timed out
+    Failed,
+}
+
+/// When a (test, tool) pair has been run enough times, past `min_runs`,
+/// to stop before `max_runs` (rather than after `target_time`)
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopCondition {
+    /// Once the coefficient of variation of the measured runs' timings
+    /// is below this fraction
+    CvBelow(f64),
+    /// Once the relative standard error of their mean is below this
+    /// fraction
+    RelativeStderrBelow(f64),
+}
+
+impl StopCondition {
+    fn threshold(&self) -> f64 {
+        match self {
+            StopCondition::CvBelow(threshold) | StopCondition::RelativeStderrBelow(threshold) => {
+                *threshold
+            }
+        }
+    }
+
+    /// The statistic of `samples` that is compared against the
+    /// threshold, if there are enough of them to compute it
+    fn statistic(&self, samples: &[Sample]) -> Option<f64> {
+        if samples.len() < 2 {
+            return None;
+        }
+        let timings: Vec<std::time::Duration> = samples.iter().map(|s| s.timing).collect();
+        let stats = Statistics::new(&timings);
+        let (mean, stddev) = (stats.mean.as_secs_f64(), stats.sample_stddev.as_secs_f64());
+        let n = stats.count as f64;
+        Some(match self {
+            StopCondition::CvBelow(_) => stddev / mean,
+            StopCondition::RelativeStderrBelow(_) => stddev / n.sqrt() / mean,
+        })
+    }
+
+    /// Whether `samples` are enough
+    fn is_met(&self, samples: &[Sample]) -> bool {
+        self.statistic(samples)
+            .is_some_and(|statistic| statistic < self.threshold())
+    }
+}
+
+impl std::fmt::Display for StopCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopCondition::CvBelow(threshold) => write!(f, "CV below {}", threshold),
+            StopCondition::RelativeStderrBelow(threshold) => {
+                write!(f, "relative stderr below {}", threshold)
+            }
+        }
+    }
+}
+
+/// The amount of work done by a single run of a test, which makes its
+/// results also reportable as a rate (e.g., lines per second)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Throughput {
+    amount: f64,
+    unit: String,
+}
+
+impl Test {
+    /// The tests that `self` expands into, one per file matching its
+    /// `files` (just `self` if it has none)
+    fn expand_files(self) -> Result<Vec<Test>> {
+        let Some(pattern) = &self.files else {
+            return Ok(vec![self]);
+        };
+        if self.file.is_some() {
+            return Err(eyre!(
+                "Test {} should have only one of file and files set",
+                self.name
+            ));
+        }
+        let files: Vec<PathBuf> = glob::glob(pattern)
+            .map_err(|e| {
+                eyre!(
+                    "Invalid files pattern {} of test {}: {}",
+                    pattern,
+                    self.name,
+                    e
+                )
+            })?
+            .collect::<Result<_, _>>()?;
+        if files.is_empty() {
+            return Err(eyre!("No files match {}, for test {}", pattern, self.name));
+        }
+        Ok(files
+            .into_iter()
+            .map(|file| {
+                let file = file.to_string_lossy().to_string();
+                let named = |name: &str| format!("{} ({})", name, file);
+                Test {
+                    name: named(&self.name),
+                    id: Some(format!("{}-{}", self.id(), slugify(&file))),
+                    aliases: self
+                        .aliases
+                        .as_ref()
+                        .map(|aliases| aliases.iter().map(|a| named(a)).collect()),
+                    file: Some(file.clone()),
+                    files: None,
+                    ..self.clone()
+                }
+            })
+            .collect())
+    }
+
+    /// The tests that `self` expands into, one per size of input that
+    /// it generates (just `self` if it generates none), with the size
+    /// interpolated as `{SIZE}`
+    fn expand_sizes(self) -> Result<Vec<Test>> {
+        let Some(generate) = &self.generate else {
+            return Ok(vec![self]);
+        };
+        if generate.sizes.is_empty() {
+            return Err(eyre!("Test {} has no sizes to generate", self.name));
+        }
+        // Otherwise, every size would be generated into the same file, and
+        // all of them benchmarked on the last
+        let Some(file) = self.file.as_ref().filter(|f| f.contains("{SIZE}")) else {
+            return Err(eyre!(
+                "Test {} needs a file that mentions {{SIZE}} to generate each of its inputs \
+                 into, e.g. \"inputs/{}-{{SIZE}}\"",
+                self.name,
+                self.id()
+            ));
+        };
+        Ok(generate
+            .sizes
+            .iter()
+            .map(|size| {
+                let size = match size {
+                    toml::Value::String(s) => s.clone(),
+                    size => size.to_string(),
+                };
+                let named = |name: &str| format!("{} (size={})", name, size);
+                let mut param_values = self.param_values.clone();
+                param_values.push(("SIZE".to_string(), size.clone()));
+                Test {
+                    name: named(&self.name),
+                    id: Some(format!("{}-{}", self.id(), slugify(&size))),
+                    aliases: self
+                        .aliases
+                        .as_ref()
+                        .map(|aliases| aliases.iter().map(|a| named(a)).collect()),
+                    file: Some(file.replace("{SIZE}", &size)),
+                    param_values,
+                    ..self.clone()
+                }
+            })
+            .collect())
+    }
+
+    /// The tests that `self` expands into, one per combination of the
+    /// values of its parameters (just `self` if it has none)
+    fn expand_params(self) -> Result<Vec<Test>> {
+        let Some(params) = &self.params else {
+            return Ok(vec![self]);
+        };
+        let mut combinations: Vec<Vec<(String, String)>> = vec![vec![]];
+        for (param, values) in params {
+            if values.is_empty() {
+                return Err(eyre!(
+                    "Test {} has no values for its parameter {}",
+                    self.name,
+                    param
+                ));
+            }
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    values.iter().map(move |value| {
+                        let value = match value {
+                            toml::Value::String(s) => s.clone(),
+                            value => value.to_string(),
+                        };
+                        let mut combination = combination.clone();
+                        combination.push((param.clone(), value));
+                        combination
+                    })
+                })
+                .collect();
+        }
+        Ok(combinations
+            .into_iter()
+            .map(|param_values| {
+                let bindings = param_values
+                    .iter()
+                    .map(|(param, value)| format!("{}={}", param, value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let named = |name: &str| format!("{} ({})", name, bindings);
+                let interpolated = |s: &String| {
+                    param_values.iter().fold(s.clone(), |s, (param, value)| {
+                        s.replace(&format!("{{{}}}", param), value)
+                    })
+                };
+                Test {
+                    name: named(&self.name),
+                    id: Some(format!("{}-{}", self.id(), slugify(&bindings))),
+                    aliases: self
+                        .aliases
+                        .as_ref()
+                        .map(|aliases| aliases.iter().map(|a| named(a)).collect()),
+                    file: self.file.as_ref().map(interpolated),
+                    extra_args: self
+                        .extra_args
+                        .as_ref()
+                        .map(|args| args.iter().map(interpolated).collect()),
+                    stdin_from_cmd: self.stdin_from_cmd.as_ref().map(interpolated),
+                    stdin_from_file: self.stdin_from_file.as_ref().map(interpolated),
+                    env: self.env.as_ref().map(|env| {
+                        env.iter()
+                            .map(|(name, value)| (name.clone(), interpolated(value)))
+                            .collect()
+                    }),
+                    params: None,
+                    param_values,
+                    ..self.clone()
+                }
+            })
+            .collect())
+    }
+
+    /// Names of the parameters that the test was expanded from, as given
+    /// in its `params` (so not the `SIZE` that `generate` adds last)
+    fn param_names(&self) -> impl Iterator<Item = &str> {
+        let own = self.param_values.len() - usize::from(self.generate.is_some());
+        self.param_values[..own]
+            .iter()
+            .map(|(param, _)| param.as_str())
+    }
+
+    /// Stable identifier of the test, for use by automation
+    pub fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| slugify(&self.name))
+    }
+
+    /// How the output of each tool on this test is validated, if at all
+    fn validation(&self) -> Option<validate::ValidateConfig> {
+        self.validate.as_ref().and_then(validate::Validate::config)
+    }
+
+    /// Whether `name` is the current or a former name of the test
+    pub fn is_known_as(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().flatten().any(|a| a == name)
+    }
+
+    /// Placeholders built into benchify, which parameters of the same
+    /// names would clash with
+    const PLACEHOLDERS: &'static [&'static str] =
+        &["NAME", "TAG", "TMPDIR", "...", "FILE", "FILE_STEM", "SIZE"];
+
+    pub fn interpolated_into(&self, s: &str) -> String {
+        let extra_args = self.extra_args.as_ref().unwrap_or(&vec![]).join(" ");
+        let extra_args_quoted = self
+            .extra_args
+            .as_ref()
+            .unwrap_or(&vec![])
+            .iter()
+            .map(|x| format!("'{}'", x))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let s = s
+            .replace("{NAME}", &self.name)
+            .replace("{TAG}", &self.tag)
+            .replace("{TMPDIR}", &SCRATCH_DIR.to_string_lossy())
+            .replace("\"{...}\"", &extra_args)
+            .replace("'{...}'", &extra_args)
+            .replace("{...}", &extra_args_quoted);
+        let s = if let Some(file) = &self.file {
+            let stem = Path::new(file).file_stem().unwrap_or_default();
+            s.replace("{FILE}", file)
+                .replace("{FILE_STEM}", &stem.to_string_lossy())
+        } else {
+            s
+        };
+        self.param_values.iter().fold(s, |s, (param, value)| {
+            s.replace(&format!("{{{}}}", param), value)
+        })
+    }
+
+    pub fn interpolated_into_args(&self, args: &Args) -> Args {
+        let mut res = vec![];
+        for arg in args {
+            if arg == "{...}" || arg == "..." {
+                res.append(&mut self.extra_args.as_ref().unwrap_or(&vec![]).clone());
+            } else {
+                res.push(self.interpolated_into(arg));
+            }
+        }
+        res
+    }
+}
+
+/// Parses a duration such as "30d" or "500ms": a number followed by a
+/// unit, one of "ms", "s", "m", "h", "d" or "w"
+fn parse_duration(duration: &str) -> Result<std::time::Duration> {
+    let duration = duration.trim();
+    let (amount, seconds) = match duration.strip_suffix("ms") {
+        Some(amount) => (amount, 1e-3),
+        None => {
+            let split = duration.len() - duration.chars().last().map_or(0, char::len_utf8);
+            let (amount, unit) = duration.split_at(split);
+            let seconds = match unit {
+                "s" => 1.,
+                "m" => 60.,
+                "h" => 60. * 60.,
+                "d" => 24. * 60. * 60.,
+                "w" => 7. * 24. * 60. * 60.,
+                _ => {
+                    return Err(eyre!(
+                        "Invalid duration {:?}: expected a unit of ms, s, m, h, d or w",
+                        duration
+                    ))
+                }
+            };
+            (amount, seconds)
+        }
+    };
+    let amount: f64 = amount.parse().map_err(|_| {
+        eyre!(
+            "Invalid duration {:?}: expected a number, e.g. \"30d\"",
+            duration
+        )
+    })?;
+    if !amount.is_finite() || amount < 0. {
+        return Err(eyre!(
+            "Invalid duration {:?}: must be a non-negative number",
+            duration
+        ));
+    }
+    Ok(std::time::Duration::from_secs_f64(amount * seconds))
+}
+
+/// Lowercase alphanumeric version of `name`, with runs of anything else
+/// replaced by a single `-`
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Identifier of the cell for the test and tool with the given
+/// identifiers. Stays the same across renames, as long as the
+/// identifiers do.
+fn cell_id(test_id: &str, tool_id: &str) -> String {
+    format!("{}/{}", test_id, tool_id)
+}
+
+/// Subdirectory of the results directory for the files of the cell
+/// `id`, of a test tagged `tag`: `<tag>/<test id>/<tool id>`
+fn cell_dir(tag: &str, id: &str) -> PathBuf {
+    id.split('/')
+        .fold(PathBuf::from(slugify(tag)), |dir, id| dir.join(slugify(id)))
+}
+
+/// What part of the config a [`Diagnostic`] is about
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum DiagnosticSubject {
+    Config,
+    Tool(String),
+    Test(String),
+}
+
+impl std::fmt::Display for DiagnosticSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticSubject::Config => write!(f, "config"),
+            DiagnosticSubject::Tool(name) => write!(f, "tool {}", name),
+            DiagnosticSubject::Test(name) => write!(f, "test {}", name),
+        }
+    }
+}
+
+/// A problem found in the config during sanity checking
+#[derive(Serialize, Debug)]
+pub struct Diagnostic {
+    subject: DiagnosticSubject,
+    message: String,
+    suggestion: Option<String>,
+    // Whether the problem is that a tool could not be executed, as
+    // opposed to a mistake in the config
+    #[serde(skip)]
+    missing_tool: bool,
+}
+
+impl Diagnostic {
+    fn new(subject: DiagnosticSubject, message: String) -> Self {
+        Diagnostic {
+            subject,
+            message,
+            suggestion: None,
+            missing_tool: false,
+        }
+    }
+
+    fn missing_tool(subject: DiagnosticSubject, message: String) -> Self {
+        Diagnostic {
+            missing_tool: true,
+            ..Diagnostic::new(subject, message)
+        }
+    }
+
+    fn with_suggestion(self, suggestion: &str) -> Self {
+        Diagnostic {
+            suggestion: Some(suggestion.to_string()),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n\tSuggested fix:\n\t\t\t{}\n", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// How the problems in `diagnostics` fail the invocation: if tools are
+/// missing, but the config is otherwise fine, by
+/// [`ci::Outcome::MissingTool`]
+fn diagnostics_outcome(diagnostics: &[Diagnostic]) -> ci::Outcome {
+    if diagnostics.iter().all(|d| d.missing_tool) {
+        ci::Outcome::MissingTool
+    } else {
+        ci::Outcome::ConfigError
+    }
+}
+
+fn format_diagnostics(diagnostics: &[Diagnostic]) -> Result<String> {
+    use std::fmt::Write;
+
+    let mut result = String::new();
+    let rows: Vec<(String, String)> = diagnostics
+        .iter()
+        .map(|d| {
+            let problem = match &d.suggestion {
+                Some(suggestion) => format!("{} (Suggested fix: {})", d.message, suggestion),
+                None => d.message.clone(),
+            };
+            (d.subject.to_string(), problem)
+        })
+        .collect();
+    let subject_length = rows.iter().map(|r| r.0.len()).chain([7]).max().unwrap();
+    let problem_length = rows.iter().map(|r| r.1.len()).chain([7]).max().unwrap();
+
+    writeln!(
+        &mut result,
+        "| {s: <sl$} | {p: <pl$} |",
+        sl = subject_length,
+        s = "Subject",
+        pl = problem_length,
+        p = "Problem",
+    )?;
+    writeln!(
+        &mut result,
+        "|:{dash:-<sl$}-|:{dash:-<pl$}-|",
+        dash = "-",
+        sl = subject_length,
+        pl = problem_length,
+    )?;
+    for (subject, problem) in rows {
+        writeln!(
+            &mut result,
+            "| {s: <sl$} | {p: <pl$} |",
+            sl = subject_length,
+            s = subject,
+            pl = problem_length,
+            p = problem,
+        )?;
+    }
+    Ok(result)
+}
+
+/// Estimator of central tendency used to summarize the timings of a
+/// (test, tool) pair
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Estimator {
+    #[default]
+    Mean,
+    TrimmedMean,
+    WinsorizedMean,
+    Median,
+}
+
+/// Statistic of the runs of a (test, tool) pair that can be shown in
+/// summaries, alongside the central value and standard deviation
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Statistic {
+    Median,
+    P90,
+    P95,
+    P99,
+    /// Median absolute deviation
+    Mad,
+    Min,
+    Max,
+    /// Bounds of the bootstrap confidence interval of the central value
+    CiLow,
+    CiHigh,
+}
+
+impl Statistic {
+    /// Name of the statistic, for use in headers
+    fn name(&self) -> &'static str {
+        match self {
+            Statistic::Median => "Median",
+            Statistic::P90 => "p90",
+            Statistic::P95 => "p95",
+            Statistic::P99 => "p99",
+            Statistic::Mad => "MAD",
+            Statistic::Min => "Min",
+            Statistic::Max => "Max",
+            Statistic::CiLow => "CI low",
+            Statistic::CiHigh => "CI high",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Pushgateway that the statistics of each (test, tool) pair are
+    /// pushed to
+    prometheus: Option<prometheus::PrometheusConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ReportConfig {
+    estimator: Option<Estimator>,
+    trim_fraction: Option<f64>,
+    regression_threshold: Option<f64>,
+    statistics: Option<Vec<Statistic>>,
+    exclude_outliers: Option<bool>,
+    cpu_time: Option<bool>,
+    seed: Option<u64>,
+}
+
+impl ReportConfig {
+    fn estimator(&self) -> Estimator {
+        self.estimator.unwrap_or_default()
+    }
+
+    fn trim_fraction(&self) -> f64 {
+        self.trim_fraction.unwrap_or(0.1)
+    }
+
+    /// Statistics shown in summaries, beyond the central value and
+    /// standard deviation
+    fn statistics(&self) -> &[Statistic] {
+        self.statistics.as_deref().unwrap_or(&[])
+    }
+
+    fn exclude_outliers(&self) -> bool {
+        self.exclude_outliers.unwrap_or_default()
+    }
+
+    /// Whether summaries show the user and system CPU time of runs
+    fn cpu_time(&self) -> bool {
+        self.cpu_time.unwrap_or_default()
+    }
+
+    /// Statistics of the `timings` of a (test, tool) pair, leaving out
+    /// outliers if so configured
+    fn summarize(&self, timings: &[std::time::Duration]) -> Statistics {
+        let stats = Statistics::new(timings);
+        if self.exclude_outliers() {
+            stats.without_outliers()
+        } else {
+            stats
+        }
+    }
+
+    /// Smallest relative change from a baseline that is reported as a
+    /// regression or improvement
+    fn regression_threshold(&self) -> f64 {
+        self.regression_threshold.unwrap_or(0.05)
+    }
+
+    /// Name of the estimator, for use in headers
+    fn estimator_name(&self) -> &'static str {
+        match self.estimator() {
+            Estimator::Mean => "Mean",
+            Estimator::TrimmedMean => "Trimmed mean",
+            Estimator::WinsorizedMean => "Winsorized mean",
+            Estimator::Median => "Median",
+        }
+    }
+
+    /// The central value of `stats`, as per the configured estimator
+    fn central_value(&self, stats: &Statistics) -> std::time::Duration {
+        match self.estimator() {
+            Estimator::Mean => stats.mean,
+            Estimator::TrimmedMean => stats.trimmed_mean(self.trim_fraction()),
+            Estimator::WinsorizedMean => stats.winsorized_mean(self.trim_fraction()),
+            Estimator::Median => stats.median,
+        }
+    }
+
+    /// Whether any statistic shown is randomized, and so depends on the
+    /// seed
+    fn is_randomized(&self) -> bool {
+        self.statistics()
+            .iter()
+            .any(|s| matches!(s, Statistic::CiLow | Statistic::CiHigh))
+    }
+
+    /// Value of the `statistic` of the runs summarized by `stats`
+    fn statistic(&self, stats: &Statistics, statistic: Statistic) -> std::time::Duration {
+        match statistic {
+            Statistic::Median => stats.median,
+            Statistic::P90 => stats.percentile(90.),
+            Statistic::P95 => stats.percentile(95.),
+            Statistic::P99 => stats.percentile(99.),
+            Statistic::Mad => stats.mad,
+            Statistic::Min => stats.min,
+            Statistic::Max => stats.max,
+            Statistic::CiLow | Statistic::CiHigh => {
+                let (low, high) =
+                    bootstrap::interval(&stats.sorted, self.seed.unwrap_or_default(), |data| {
+                        self.central_value(&Statistics::new(data))
+                    });
+                if statistic == Statistic::CiLow {
+                    low
+                } else {
+                    high
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct BenchifyConfig {
+    benchify_version: usize,
+    parallel_prep: Option<bool>,
+    /// Number of (test, tool) pairs that are benchmarked at once
+    parallel_benchmarks: Option<usize>,
+    /// Whether the tools on a test are run one after the other, or
+    /// round-robin
+    run_order: Option<scheduler::RunOrder>,
+    /// CPUs that runs are pinned to
+    cpu_affinity: Option<Vec<usize>>,
+    interference_threshold: Option<f64>,
+    on_interference: Option<interference::InterferencePolicy>,
+    warmup: Option<u32>,
+    /// How long to keep making warmup runs for (e.g., "10s"), once the
+    /// `warmup` ones are done
+    warmup_time: Option<String>,
+    min_runs: Option<u32>,
+    max_runs: Option<u32>,
+    /// When to stop running a (test, tool) pair early, once its timings
+    /// are stable enough
+    stop_when: Option<StopCondition>,
+    /// Total time, in seconds, that the measured runs of a (test, tool)
+    /// pair should take, within `min_runs` and `max_runs`
+    target_time: Option<f64>,
+    main_tool: Option<String>,
+    results_dir: Option<PathBuf>,
+    max_output_size: Option<usize>,
+    collect_crashes: Option<bool>,
+    /// Which runs have their stdout and stderr saved under `logs/` in
+    /// the results directory
+    save_run_logs: Option<RunLogs>,
+    /// Shell commands run just before and just after each run of every
+    /// (test, tool) pair, e.g. to reset state that the runs share
+    before_each_run: Option<ShellCommand>,
+    after_each_run: Option<ShellCommand>,
+    /// Whether those also run around warmup runs
+    warmup_hooks: Option<bool>,
+    /// Whether the kernel's caches are dropped before each run (Linux
+    /// only)
+    drop_caches: Option<bool>,
+    /// Whether the page faults and context switches of each run are
+    /// recorded (Unix only)
+    record_faults_and_switches: Option<bool>,
+    sample_memory: Option<bool>,
+    sample_gpu: Option<bool>,
+    perf_counters: Option<Vec<String>>,
+    timeout_grace_period: Option<f64>,
+    retention: Option<clean::RetentionConfig>,
+    ci: Option<ci::CiConfig>,
+    priority: Option<priority::PriorityConfig>,
+    /// Cgroups that measured runs are isolated in
+    cgroup: Option<cgroup::CgroupConfig>,
+    /// How quiet the machine must be before each measured run
+    wait_for_quiet: Option<quiet::QuietConfig>,
+    /// Host that the processes of all tools are run on over SSH
+    remote: Option<remote::RemoteConfig>,
+    randomize_environment: Option<bool>,
+    /// Whether every run is also appended to the history database in
+    /// the results directory
+    history: Option<bool>,
+    /// Whether box plots and bar charts of the results are rendered into
+    /// the results directory
+    plots: Option<bool>,
+    /// Whether the results are also written in the on-disk format of
+    /// Criterion.rs, under `criterion/` in the results directory
+    criterion: Option<bool>,
+    /// How revisions are built for `benchify git`
+    git: Option<git::GitConfig>,
+    /// Shell commands run once the results are saved
+    hooks: Option<hooks::HooksConfig>,
+    /// Systems that the results are exported to once saved
+    export: Option<ExportConfig>,
+    /// Shell that shell commands are run with, unless set by the tool or
+    /// runner
+    shell: Option<shell::Shell>,
+    /// Environment variables for the processes of all tools
+    env: Option<BTreeMap<String, String>>,
+    report: Option<ReportConfig>,
+    ui: Option<ui::UiConfig>,
+    /// Globs (relative to the config) of files whose tags, tools and
+    /// tests are merged into the config
+    include: Option<Vec<String>>,
+    // Hash of the files the config was loaded from
+    #[serde(skip)]
+    config_hash: Option<String>,
+    // Tools whose builds were registered as tools of their own
+    #[serde(skip)]
+    build_sets: Vec<build_matrix::BuildSet>,
+    // Tools left out by `select_tools`, and why
+    #[serde(skip)]
+    deselected_tools: Vec<(String, &'static str)>,
+    // Optional, as long as they are included from elsewhere
+    #[serde(default)]
+    tags: HashSet<Tag>,
+    #[serde(default)]
+    tools: Vec<Tool>,
+    #[serde(default)]
+    tests: Vec<Test>,
+}
+
+/// Definitions merged into a config from one of the files it includes
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct IncludedConfig {
+    #[serde(default)]
+    tags: HashSet<Tag>,
+    #[serde(default)]
+    tools: Vec<Tool>,
+    #[serde(default)]
+    tests: Vec<Test>,
+}
+
+impl BenchifyConfig {
+    fn load(path: &Path) -> Result<Self> {
+        let mut text = std::fs::read_to_string(path).or(Err(eyre!("Could not read {:?}", path)))?;
+        let mut config: BenchifyConfig = toml::from_str(&text)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for pattern in config.include.clone().into_iter().flatten() {
+            let paths: Vec<PathBuf> =
+                glob::glob(&dir.join(&pattern).to_string_lossy())?.collect::<Result<_, _>>()?;
+            if paths.is_empty() {
+                return Err(eyre!("No files to include match {}", pattern));
+            }
+            for path in paths {
+                let included_text =
+                    std::fs::read_to_string(&path).or(Err(eyre!("Could not read {:?}", path)))?;
+                let included: IncludedConfig = toml::from_str(&included_text)
+                    .map_err(|e| eyre!("Could not load {:?}: {}", path, e))?;
+                config.tags.extend(included.tags);
+                config.tools.extend(included.tools);
+                config.tests.extend(included.tests);
+                // Changes to included files are changes to the config
+                text += &included_text;
+            }
+        }
+        config.config_hash = Some(metadata::stable_hash(text.as_bytes()));
+        for test in std::mem::take(&mut config.tests) {
+            for test in test.expand_files()? {
+                for test in test.expand_params()? {
+                    config.tests.extend(test.expand_sizes()?);
+                }
+            }
+        }
+        for mut tool in std::mem::take(&mut config.tools) {
+            if tool.remote.is_none() {
+                tool.remote = config.remote.clone();
+            }
+            let (tools, build_set) = tool.expand_builds();
+            config.tools.extend(tools);
+            if let Some(build_set) = build_set {
+                // A tool with builds is compared by its first build
+                if config.main_tool.as_ref() == Some(&build_set.tool) {
+                    config.main_tool = build_set.builds.first().map(|(_, tool)| tool.clone());
+                }
+                config.build_sets.push(build_set);
+            }
+        }
+        Ok(config)
+    }
+
+    fn metadata(&self) -> metadata::Metadata {
+        let mut metadata = metadata::Metadata::collect();
+        metadata.config_hash = self.config_hash.clone();
+        metadata.randomize_environment = self.randomize_environment();
+        metadata.tool_versions = self
+            .tools
+            .par_iter()
+            .filter_map(|tool| Some((tool.name.clone(), tool.version()?)))
+            .collect();
+        metadata.cpu_affinity = self
+            .tools
+            .iter()
+            .flat_map(|tool| {
+                tool.runners.iter().filter_map(move |(tag, runner)| {
+                    let cpus = runner
+                        .cpu_affinity
+                        .as_ref()
+                        .or(self.cpu_affinity.as_ref())?;
+                    Some((format!("{} ({})", tool.name, tag), cpus.clone()))
+                })
+            })
+            .collect();
+        metadata
+    }
+
+    fn min_runs(&self) -> u32 {
+        self.min_runs.unwrap_or(10)
+    }
+
+    fn max_runs(&self) -> u32 {
+        self.max_runs.unwrap_or(1000)
+    }
+
+    /// Minimum and maximum number of measured runs of `tool` on `test`
+    fn run_bounds(&self, test: &Test, tool: &Tool) -> (u32, u32) {
+        let runner = &tool.runners[&test.tag];
+        (
+            test.min_runs
+                .or(runner.min_runs)
+                .unwrap_or_else(|| self.min_runs()),
+            test.max_runs
+                .or(runner.max_runs)
+                .unwrap_or_else(|| self.max_runs()),
+        )
+    }
+
+    /// Total time, in seconds, that the measured runs of each tool on
+    /// `test` should take
+    fn target_time(&self, test: &Test) -> f64 {
+        test.target_time.or(self.target_time).unwrap_or(2.5)
+    }
+
+    fn results_dir(&self) -> PathBuf {
+        self.results_dir
+            .clone()
+            .unwrap_or(PathBuf::from("./benchify-results/"))
+    }
+
+    fn ui(&self) -> ui::UiConfig {
+        self.ui.clone().unwrap_or_default()
+    }
+
+    fn report(&self) -> ReportConfig {
+        self.report.clone().unwrap_or_default()
+    }
+
+    fn ci(&self) -> ci::CiConfig {
+        self.ci.clone().unwrap_or_default()
+    }
+
+    fn priority(&self) -> priority::PriorityConfig {
+        self.priority.clone().unwrap_or_default()
+    }
+
+    fn max_output_size(&self) -> usize {
+        self.max_output_size.unwrap_or(16 * 1024 * 1024)
+    }
+
+    fn drop_caches(&self) -> bool {
+        self.drop_caches.unwrap_or_default()
+    }
+
+    fn record_faults_and_switches(&self) -> bool {
+        self.record_faults_and_switches.unwrap_or_default()
+    }
+
+    fn sample_memory(&self) -> bool {
+        self.sample_memory.unwrap_or_default()
+    }
+
+    fn sample_gpu(&self) -> bool {
+        self.sample_gpu.unwrap_or_default()
+    }
+
+    /// Time that a run that timed out is given to exit after SIGTERM,
+    /// before it is killed
+    fn timeout_grace_period(&self) -> std::time::Duration {
+        std::time::Duration::try_from_secs_f64(self.timeout_grace_period.unwrap_or(5.))
+            .unwrap_or(std::time::Duration::from_secs(5))
+    }
+
+    /// Hardware events to count in each run with `perf stat`, if any
+    fn perf_counters(&self) -> Option<&[String]> {
+        self.perf_counters
+            .as_deref()
+            .filter(|events| !events.is_empty())
+    }
+
+    fn randomize_environment(&self) -> bool {
+        self.randomize_environment.unwrap_or_default()
+    }
+
+    fn history(&self) -> bool {
+        self.history.unwrap_or_default()
+    }
+
+    fn plots(&self) -> bool {
+        self.plots.unwrap_or_default()
+    }
+
+    fn criterion(&self) -> bool {
+        self.criterion.unwrap_or_default()
+    }
+
+    fn collect_crashes(&self) -> bool {
+        self.collect_crashes.unwrap_or_default()
+    }
+
+    fn save_run_logs(&self) -> RunLogs {
+        self.save_run_logs.unwrap_or_default()
+    }
+
+    fn parallel_prep(&self) -> bool {
+        self.parallel_prep.unwrap_or_default()
+    }
+
+    fn parallel_benchmarks(&self) -> usize {
+        self.parallel_benchmarks.unwrap_or(1)
+    }
+
+    fn run_order(&self) -> scheduler::RunOrder {
+        self.run_order.unwrap_or_default()
+    }
+
+    fn interference_threshold(&self) -> f64 {
+        self.interference_threshold.unwrap_or(0.25)
+    }
+
+    /// Restricts the tests to those in any of the groups in `only` (or
+    /// all of them, if it is empty) that are in none of the groups in
+    /// `skip`
+    fn select_groups(&mut self, only: &[String], skip: &[String]) -> Result<()> {
+        let known: BTreeSet<&str> = self
+            .tests
+            .iter()
+            .filter_map(|t| t.group.as_deref())
+            .collect();
+        if let Some(unknown) = only
+            .iter()
+            .chain(skip)
+            .find(|g| !known.contains(g.as_str()))
+        {
+            return Err(eyre!(
+                "Unknown group {:?}. Expected one of {:?}",
+                unknown,
+                known
+            ));
+        }
+        self.tests.retain(|t| {
+            let in_any = |groups: &[String]| groups.iter().any(|g| t.group.as_ref() == Some(g));
+            (only.is_empty() || in_any(only)) && !in_any(skip)
+        });
+        Ok(())
+    }
+
+    /// Replaces the tools with one for each of `revisions`, as builds of
+    /// the tool configured in `[git]`, the first of which is the main tool
+    fn benchmark_revisions(&mut self, revisions: &[String]) -> Result<()> {
+        let git = self.git.clone().ok_or(eyre!(
+            "Benchmarking revisions needs a [git] section in the config"
+        ))?;
+        let name = git
+            .tool
+            .or_else(|| self.main_tool.clone())
+            .or_else(|| match &self.tools[..] {
+                [tool] => Some(tool.name.clone()),
+                _ => None,
+            })
+            .ok_or(eyre!(
+                "Which tool to benchmark revisions with is ambiguous; set `tool` in [git]"
+            ))?;
+        let index = self
+            .tools
+            .iter()
+            .position(|t| t.name == name && t.registered_build.is_none())
+            .ok_or(eyre!(
+                "No tool {} (without builds) to benchmark revisions with",
+                name
+            ))?;
+        for revision in revisions {
+            git::resolve(revision)?;
+        }
+        let mut tool = self.tools.swap_remove(index);
+        tool.kind = None;
+        tool.program = Some(Program::Executable(format!(
+            "{{BUILD_DIR}}/{}",
+            git.program
+        )));
+        tool.builds = Some(
+            revisions
+                .iter()
+                .map(|revision| build_matrix::Build {
+                    name: revision.clone(),
+                    cmd: git.build.clone(),
+                    checkout: Some(revision.clone()),
+                })
+                .collect(),
+        );
+        let (tools, build_set) = tool.expand_builds();
+        self.main_tool = tools.first().map(|t| t.name.clone());
+        self.tools = tools;
+        self.build_sets = build_set.into_iter().collect();
+        Ok(())
+    }
+
+    /// Keeps only the tools in `only` (if any are given), and not in
+    /// `skip`, so that only those have to be runnable. If the main tool
+    /// is left out, ratios are computed against the fastest tool
+    /// instead.
+    fn select_tools(&mut self, only: &[String], skip: &[String]) -> Result<()> {
+        if let Some(unknown) = only
+            .iter()
+            .chain(skip)
+            .find(|name| !self.tools.iter().any(|t| t.is_known_as(name)))
+        {
+            return Err(eyre!(
+                "Unknown tool {:?}. Expected one of {:?}",
+                unknown,
+                self.tools.iter().map(|t| &t.name).collect::<Vec<_>>()
+            ));
+        }
+        let in_any = |t: &Tool, names: &[String]| names.iter().any(|name| t.is_known_as(name));
+        for t in &self.tools {
+            if !only.is_empty() && !in_any(t, only) {
+                self.deselected_tools
+                    .push((t.name.clone(), "not selected with --tool"));
+            } else if in_any(t, skip) {
+                self.deselected_tools
+                    .push((t.name.clone(), "excluded with --skip-tool"));
+            }
+        }
+        self.tools
+            .retain(|t| (only.is_empty() || in_any(t, only)) && !in_any(t, skip));
+        if self.tools.is_empty() {
+            return Err(eyre!("No tools left to run"));
+        }
+        if let Some(main_tool) = &self.main_tool {
+            if !self.tools.iter().any(|t| &t.name == main_tool) {
+                info!("Main tool {} is not selected", main_tool);
+                self.main_tool = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps only the tests that match any of `patterns`, if any are
+    /// given
+    fn select_tests(&mut self, patterns: &[TestPattern]) -> Result<()> {
+        if patterns.is_empty() {
+            return Ok(());
+        }
+        if let Some(unmatched) = patterns
+            .iter()
+            .find(|p| !self.tests.iter().any(|t| p.matches(t)))
+        {
+            return Err(eyre!(
+                "No test matches {}. Expected a pattern matching one of {:?}",
+                unmatched,
+                self.tests.iter().map(|t| &t.name).collect::<Vec<_>>()
+            ));
+        }
+        self.tests.retain(|t| patterns.iter().any(|p| p.matches(t)));
+        Ok(())
+    }
+
+    fn eval(&self, test: &str, template: &Args) -> Result<()> {
+        let test = self.tests.iter().find(|t| t.name == test).ok_or(eyre!(
+            "Unknown test {:?}. Expected one of {:?}",
+            test,
+            self.tests.iter().map(|t| &t.name).collect::<Vec<_>>()
+        ))?;
+        println!("run_args: {:?}", test.interpolated_into_args(template));
+        println!("run_cmd:  {}", test.interpolated_into(&template.join(" ")));
+        Ok(())
+    }
+
+    /// Imports the samples in `file` as the runs of `tool` on `test`,
+    /// into data.csv, replacing any runs of theirs recorded there.
+    /// Returns the number of samples imported.
+    fn import_samples(
+        &self,
+        file: &Path,
+        test: &str,
+        tool: &str,
+        machine_class: Option<&str>,
+    ) -> Result<usize> {
+        let test = self
+            .tests
+            .iter()
+            .find(|t| t.is_known_as(test) || t.id() == test)
+            .ok_or(eyre!("Unknown test {:?}", test))?;
+        let tool = self
+            .tools
+            .iter()
+            .find(|t| t.is_known_as(tool) || t.id() == tool)
+            .ok_or(eyre!("Unknown tool {:?}", tool))?;
+        let reader = std::fs::File::open(file).or(Err(eyre!("Could not read {:?}", file)))?;
+        let samples = parse_samples_csv(reader, &format!("{:?}", file))?;
+        if samples.is_empty() {
+            return Err(eyre!("{:?} has no samples", file));
+        }
+        let imported = samples.len();
+
+        let data_csv = self.results_dir().join("data.csv");
+        let records = if data_csv.exists() {
+            read_data_csv(&data_csv)?
+        } else {
+            vec![]
+        };
+        let id = cell_id(&test.id(), &tool.id());
+        let mut results: Vec<CellResult> = records
+            .iter()
+            .filter(|r| {
+                r.cell_id() != id && !(test.is_known_as(&r.test) && tool.is_known_as(&r.executor))
+            })
+            .map(|r| CellResult {
+                id: r.cell_id(),
+                machine_class: r.machine_class.clone(),
+                test: &r.test,
+                tag: r.tag.as_deref().unwrap_or_default(),
+                group: None,
+                executor: &r.executor,
+                throughput: None,
+                samples: Ok(vec![r.sample.clone()]),
+                partial: None,
+            })
+            .collect();
+        results.push(CellResult {
+            id,
+            machine_class: machine_class.map(str::to_string),
+            test: &test.name,
+            tag: &test.tag,
+            group: test.group.as_deref(),
+            executor: &tool.name,
+            throughput: test.throughput.as_ref(),
+            samples: Ok(samples),
+            partial: None,
+        });
+        std::fs::create_dir_all(self.results_dir())?;
+        write_data_csv(&results, std::fs::File::create(&data_csv)?)?;
+        Ok(imported)
+    }
+
+    /// The results imported from hyperfine, as cells of the selected
+    /// tests, with each command as a tool of its own
+    fn hyperfine_cells<'a>(
+        &'a self,
+        imported: &'a [hyperfine::Imported],
+    ) -> Result<Vec<CellResult<'a>>> {
+        imported
+            .iter()
+            .map(|i| {
+                let test = self
+                    .tests
+                    .iter()
+                    .find(|t| t.is_known_as(&i.test) || t.id() == i.test)
+                    .ok_or(eyre!(
+                        "Unknown (or unselected) test {:?}, of the imported results of {:?}",
+                        i.test,
+                        i.command
+                    ))?;
+                if self.tools.iter().any(|t| t.is_known_as(&i.command)) {
+                    return Err(eyre!(
+                        "The imported results of {:?} would be mistaken for those of the \
+                         tool of the same name",
+                        i.command
+                    ));
+                }
+                let samples = i
+                    .times
+                    .iter()
+                    .enumerate()
+                    .map(|(iteration, timing)| Sample {
+                        iteration,
+                        warmup: false,
+                        timing: std::time::Duration::from_secs_f64(*timing),
+                        exit_status: i.exit_codes.get(iteration).copied().unwrap_or(Some(0)),
+                        censored: false,
+                        metrics: BTreeMap::new(),
+                    })
+                    .collect();
+                Ok(CellResult {
+                    id: cell_id(&test.id(), &slugify(&i.command)),
+                    machine_class: None,
+                    test: &test.name,
+                    tag: &test.tag,
+                    group: test.group.as_deref(),
+                    executor: &i.command,
+                    throughput: test.throughput.as_ref(),
+                    samples: Ok(samples),
+                    partial: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Prints the selected tests and tools, or with `only`, just the
+    /// names of those
+    fn list(&self, only: Option<Listing>) {
+        let tags: Vec<&str> = self
+            .tags
+            .iter()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let groups: Vec<&str> = self
+            .tests
+            .iter()
+            .filter_map(|t| t.group.as_deref())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        match only {
+            Some(Listing::Tests) => self.tests.iter().for_each(|t| println!("{}", t.name)),
+            Some(Listing::Tools) => self.tools.iter().for_each(|t| println!("{}", t.name)),
+            Some(Listing::Tags) => tags.iter().for_each(|t| println!("{}", t)),
+            Some(Listing::Groups) => groups.iter().for_each(|g| println!("{}", g)),
+            None => {
+                println!("Tags: {}", tags.join(", "));
+                if !groups.is_empty() {
+                    println!("Groups: {}", groups.join(", "));
+                }
+                println!("\nTools:");
+                for tool in &self.tools {
+                    println!(
+                        "  {} (id: {}, program: {})",
+                        tool.name,
+                        tool.id(),
+                        tool.program()
+                    );
+                }
+                println!("\nTests:");
+                for test in &self.tests {
+                    let group = match &test.group {
+                        Some(group) => format!(", group: {}", group),
+                        None => String::new(),
+                    };
+                    println!(
+                        "  {} (id: {}, tag: {}{})",
+                        test.name,
+                        test.id(),
+                        test.tag,
+                        group
+                    );
+                    for tool in &self.tools {
+                        match tool.runners.get(&test.tag) {
+                            Some(runner) => match (&runner.run_args, &runner.run_cmd) {
+                                (Some(run_args), _) => {
+                                    println!("    {}: run_args = {:?}", tool.name, run_args)
+                                }
+                                (None, Some(run_cmd)) => {
+                                    println!("    {}: run_cmd = {:?}", tool.name, run_cmd)
+                                }
+                                (None, None) => {
+                                    println!("    {}: runner without command", tool.name)
+                                }
+                            },
+                            None => println!("    {}: no runner for {}", tool.name, test.tag),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Prints the commands that would be run for each cell of `plan`,
+    /// with all placeholders interpolated, without running any of them
+    fn dry_run(&self, plan: &plan::Plan) -> Result<()> {
+        let cells = self.resolve_plan(plan)?;
+        let mut tools: Vec<&Tool> = vec![];
+        for (_, tool, _) in &cells {
+            if !tools.iter().any(|t| t.name == tool.name) {
+                tools.push(tool);
+            }
+        }
+        let mut tests: Vec<&Test> = vec![];
+        for (test, _, _) in &cells {
+            if test.generate.is_some() && !tests.iter().any(|t| t.name == test.name) {
+                tests.push(test);
+            }
+        }
+        for test in &tests {
+            if let Some(generate) = &test.generate {
+                println!("[{}]", test.name);
+                println!("  generate: {}", test.interpolated_into(&generate.cmd));
+            }
+        }
+        for tool in &tools {
+            if let Some(setup) = &tool.setup {
+                println!("[{}]", tool.name);
+                println!("  setup:   {}", setup);
+            }
+        }
+        for (test, tool, _policy) in cells {
+            let runner = &tool.runners[&test.tag];
+            println!("[{}] [{}]", test.name, tool.name);
+            if let Some(prepare) = &runner.prepare {
+                println!("  prepare: {}", test.interpolated_into(prepare));
+            }
+            if let Some(before) = tool.before_each_run(self, test) {
+                println!("  before each run: {}", test.interpolated_into(before));
+            }
+            if self.drop_caches() {
+                println!("  before each run: drop caches");
+            }
+            if let Some(after) = tool.after_each_run(self, test) {
+                println!("  after each run: {}", test.interpolated_into(after));
+            }
+            println!("  run:     {:?}", tool.run_command(self, test));
+            match tool.stdin(test) {
+                Stdin::Null => {}
+                Stdin::Inherit => println!("  stdin:   inherited"),
+                Stdin::File(file) => println!("  stdin:   {}", test.interpolated_into(&file)),
+                Stdin::Cmd(cmd) => {
+                    println!("  stdin:   output of {}", test.interpolated_into(&cmd))
+                }
+            }
+            if let Some(stdout_to) = &runner.stdout_to {
+                println!("  stdout:  {}", test.interpolated_into(stdout_to));
+            }
+            if let Some(cleanup) = &runner.cleanup {
+                println!("  cleanup: {}", test.interpolated_into(cleanup));
+            }
+        }
+        for tool in &tools {
+            if let Some(teardown) = &tool.teardown {
+                println!("[{}]", tool.name);
+                println!("  teardown: {}", teardown);
+            }
+        }
+        Ok(())
+    }
+
+    fn confirm_config_sanity(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        if self.benchify_version != 1 {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Found config for version {}. Currently only version 1 is supported.",
+                    self.benchify_version
+                ),
+            ));
+        }
+
+        if self.shell.as_ref().is_some_and(shell::Shell::is_empty) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                "Shell should not be empty.".to_string(),
+            ));
+        }
+
+        if self.min_runs() > self.max_runs() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Min runs ({}) is greater than max runs ({}).",
+                    self.min_runs(),
+                    self.max_runs(),
+                ),
+            ));
+        }
+
+        if let Some(stop_when) = self.stop_when.filter(|s| s.threshold() <= 0.) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Stopping with {} can never happen, as the threshold should be positive.",
+                    stop_when
+                ),
+            ));
+        }
+
+        if !(0. ..0.5).contains(&self.report().trim_fraction()) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Trim fraction ({}) should be at least 0 and less than 0.5.",
+                    self.report().trim_fraction()
+                ),
+            ));
+        }
+
+        if self.target_time.is_some_and(|t| t <= 0.) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Target time ({}) should be positive.",
+                    self.target_time.unwrap()
+                ),
+            ));
+        }
+
+        if let Some(grace_period) = self
+            .timeout_grace_period
+            .filter(|t| !(t.is_finite() && *t >= 0.))
+        {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Timeout grace period ({}) should not be negative.",
+                    grace_period
+                ),
+            ));
+        }
+
+        if let Some(Err(e)) = self.warmup_time.as_deref().map(parse_duration) {
+            diagnostics.push(
+                Diagnostic::new(DiagnosticSubject::Config, format!("Warmup time: {}.", e))
+                    .with_suggestion("Give it as, e.g., \"10s\" or \"500ms\"."),
+            );
+        }
+
+        if self.interference_threshold() <= 0. {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Interference threshold ({}) should be positive.",
+                    self.interference_threshold()
+                ),
+            ));
+        }
+
+        if self.sample_gpu() && !gpu_sampler::available() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    "GPU sampling is enabled, but no GPUs could be found through NVML.".to_string(),
+                )
+                .with_suggestion(
+                    "Make sure the NVIDIA driver is installed, and that benchify was built with \
+                     `--features gpu`.",
+                ),
+            );
+        }
+
+        if self.history() && !history::available() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    "History is enabled, but benchify was built without it.".to_string(),
+                )
+                .with_suggestion("Build benchify with `--features history`."),
+            );
+        }
+
+        if self.plots() && !plots::available() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    "Plots are enabled, but benchify was built without them.".to_string(),
+                )
+                .with_suggestion("Build benchify with `--features plots`."),
+            );
+        }
+
+        if let Some(problem) = self.drop_caches().then(caches::problem).flatten() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    format!("Dropping caches: {}.", problem),
+                )
+                .with_suggestion("Run benchify as root, or set `drop_caches = false`."),
+            );
+        }
+
+        if let Some(problem) = self.ui().problem() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!("UI: {}.", problem),
+            ));
+        }
+
+        if let Some(problem) = self
+            .wait_for_quiet
+            .as_ref()
+            .and_then(quiet::QuietConfig::problem)
+        {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!("Waiting for a quiet system: {}.", problem),
+            ));
+        }
+
+        // The load of benchify's own concurrent pairs counts too, so each
+        // could wait on the others forever
+        if self
+            .wait_for_quiet
+            .as_ref()
+            .is_some_and(|quiet| quiet.max_wait.is_none())
+            && self.parallel_benchmarks() > 1
+        {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    format!(
+                        "Waiting for a quiet system with no max_wait, while benchmarking {} \
+                         pairs at once, can wait forever on the load of those pairs \
+                         themselves.",
+                        self.parallel_benchmarks()
+                    ),
+                )
+                .with_suggestion("Set a max_wait in [wait_for_quiet]."),
+            );
+        }
+
+        if let Some(problem) = self.cgroup.as_ref().and_then(cgroup::CgroupConfig::problem) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!("Cgroups: {}.", problem),
+            ));
+        }
+
+        if let Some(problem) = self
+            .export
+            .as_ref()
+            .and_then(|e| e.prometheus.as_ref())
+            .and_then(prometheus::PrometheusConfig::problem)
+        {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!("Prometheus export: {}.", problem),
+            ));
+        }
+
+        let mut remotes: Vec<&remote::RemoteConfig> = vec![];
+        for remote in self.tools.iter().filter_map(|tool| tool.remote.as_ref()) {
+            if !remotes
+                .iter()
+                .any(|r| r.host == remote.host && r.dir == remote.dir)
+            {
+                remotes.push(remote);
+            }
+        }
+        for remote in remotes {
+            if let Some(problem) = remote.problem() {
+                diagnostics.push(
+                    Diagnostic::new(
+                        DiagnosticSubject::Config,
+                        format!("Remote host {}: {}.", remote.host, problem),
+                    )
+                    .with_suggestion(
+                        "Make sure that `ssh` logs into it without a password prompt \
+                         (e.g., with an SSH key loaded into ssh-agent).",
+                    ),
+                );
+            }
+        }
+
+        let priority = self.priority();
+        for (of, priority) in [
+            ("measured runs", priority.run()),
+            ("preparations and cleanups", priority.prepare()),
+        ] {
+            if let Some(problem) = priority.problem() {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    format!("Priority of {}: {}.", of, problem),
+                ));
+            }
+        }
+
+        if let Some(problem) = self.cpu_affinity.as_deref().and_then(affinity::problem) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!("CPU affinity: {}.", problem),
+            ));
+        }
+
+        if self.perf_counters().is_some() && !perf::available() {
+            diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    "Performance counters are enabled, but `perf stat` could not count \
+                     instructions."
+                        .to_string(),
+                )
+                .with_suggestion(if cfg!(target_os = "linux") {
+                    "Install perf (e.g., linux-tools), and allow counting with `sudo sysctl \
+                     kernel.perf_event_paranoid=1`."
+                } else if cfg!(target_os = "macos") {
+                    "perf is only available on Linux. On macOS, the instructions and cycles \
+                     of each run are recorded without it, as `Instructions (rusage)` and \
+                     `Cycles (rusage)`; remove `perf_counters`."
+                } else {
+                    "perf is only available on Linux; remove `perf_counters`."
+                }),
+            );
+        }
+
+        if self.results_dir().is_file() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticSubject::Config,
+                format!(
+                    "Results dir {:?} is already exists as a file.",
+                    self.results_dir()
+                ),
+            ));
+        }
+
+        if let Some(tool) = &self.main_tool {
+            if !self.tools.iter().any(|t| &t.name == tool) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticSubject::Config,
+                    format!(
+                        "Main tool {:?} is not on of the known tools. Expected one of {:?}",
+                        tool,
+                        self.tools.iter().map(|t| &t.name).collect::<Vec<_>>()
+                    ),
+                ));
+            }
+        }
+
+        let mut cell_ids = HashSet::new();
+        for test in &self.tests {
+            for tool in &self.tools {
+                let id = cell_id(&test.id(), &tool.id());
+                if !cell_ids.insert(id.clone()) {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticSubject::Test(test.name.clone()),
+                        format!(
+                            "Cell ID {:?} (for tool {}) is not unique. Set an `id` to \
+                             disambiguate.",
+                            id, tool.name
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // The existence confirmations are independent of each other
+        // (and can be slow, e.g. for JVM-based tools), so they are all
+        // run at once, and all missing tools are reported together.
+        trace!("Confirming runnability");
+        // Builds may not have been built yet, and are confirmed by
+        // building them instead
+        let missing_tools: Vec<&Tool> = self
+            .tools
+            .par_iter()
+            .filter(|tool| tool.registered_build.is_none() && !tool.is_runnable())
+            .collect();
+        for tool in &missing_tools {
+            diagnostics.push(
+                Diagnostic::missing_tool(
+                    DiagnosticSubject::Tool(tool.name.clone()),
+                    format!("Could not confirm that {} can be executed.", tool.name),
+                )
+                .with_suggestion(&tool.install_instructions),
+            );
+        }
+
+        for build_set in &self.build_sets {
+            if build_set.builds.is_empty() {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticSubject::Tool(build_set.tool.clone()),
+                    format!("{} has an empty list of builds.", build_set.tool),
+                ));
+            }
+        }
+
+        let mut tag_needs_file_due_to = HashMap::new();
+
+        for tool in &self.tools {
+            debug!("Confirming sanity for tool {}", tool.name);
+            let subject = || DiagnosticSubject::Tool(tool.name.clone());
+
+            match (&tool.program, tool.kind, &tool.target) {
+                (Some(_), Some(_), _) => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "{} should have only one of program and kind set.",
+                        tool.name
+                    ),
+                )),
+                (None, Some(_), None) => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("{} has a kind, but no target.", tool.name),
+                )),
+                (None, None, _) => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("Program for {} should be set.", tool.name),
+                )),
+                (Some(program), None, _) if program.executable().is_empty() => {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!("Program for {} should not be empty.", tool.name),
+                    ))
+                }
+                _ => {}
+            }
+            if tool.shell.as_ref().is_some_and(shell::Shell::is_empty) {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("Shell for {} should not be empty.", tool.name),
+                ));
+            }
+            if tool.target.is_some() && tool.kind.is_none() {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("{} has a target, but no kind.", tool.name),
+                ));
+            }
+
+            match (&tool.runtime, &tool.module) {
+                (Some(runtime), None) => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("{} runs on {}, but has no module.", tool.name, runtime),
+                )),
+                (None, Some(_)) => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("{} has a module, but no runtime to run it.", tool.name),
+                )),
+                (Some(_), Some(module)) if !Path::new(module).is_file() => {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!("Module {} of {} does not exist.", module, tool.name),
+                    ))
+                }
+                _ => {}
+            }
+            if tool.invoke.is_some() && tool.runtime.is_none() {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("{} has a function to invoke, but no runtime.", tool.name),
+                ));
+            }
+            match (&tool.container, &tool.container_engine) {
+                (Some(_), _) if tool.kind.is_some() => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "{} should have only one of kind and container set.",
+                        tool.name
+                    ),
+                )),
+                (Some(_), _) => {
+                    let engine = tool.container_engine();
+                    let available = std::process::Command::new(engine)
+                        .arg("--version")
+                        .stdin(std::process::Stdio::null())
+                        .output()
+                        .is_ok_and(|output| output.status.success());
+                    if !available {
+                        diagnostics.push(
+                            Diagnostic::new(
+                                subject(),
+                                format!(
+                                    "Could not run the container engine {} of {}.",
+                                    engine, tool.name
+                                ),
+                            )
+                            .with_suggestion(
+                                "Install it, or set container_engine to one that is installed \
+                                 (e.g., \"podman\").",
+                            ),
+                        );
+                    }
+                }
+                (None, Some(_)) => diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!("{} has a container engine, but no container.", tool.name),
+                )),
+                (None, None) => {}
+            }
+            if tool.container.is_some() && tool.remote.is_some() {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "{} should have only one of container and remote set.",
+                        tool.name
+                    ),
+                ));
+            }
+
+            if let Some(wrapper) = tool.wrapper() {
+                let settings = tool.process_settings(self);
+                if !settings.is_empty() {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            subject(),
+                            format!(
+                                "{} runs through its {}, so {} would apply to the local client \
+                                 that benchify starts, rather than to the tool.",
+                                tool.name,
+                                wrapper,
+                                settings.join(", ")
+                            ),
+                        )
+                        .with_suggestion("Unset those settings, or run the tool directly."),
+                    );
+                }
+            }
+
+            trace!("Confirmer runner commands");
+            for (tag, runner) in &tool.runners {
+                if !(runner.run_cmd.is_some() ^ runner.run_args.is_some()) {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Runner {:?} for {:?} should have only one of run_cmd and run_args set. \
+                             Got {:?} and {:?} respectively.",
+                            tag, tool.name, runner.run_cmd, runner.run_args
+                        ),
+                    ));
+                }
+                if let Some(problem) = runner.cpu_affinity.as_deref().and_then(affinity::problem) {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "CPU affinity of runner {:?} for {:?}: {}.",
+                            tag, tool.name, problem
+                        ),
+                    ));
+                }
+                if let Some(Err(e)) = runner.warmup_time.as_deref().map(parse_duration) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            subject(),
+                            format!(
+                                "Warmup time of runner {:?} for {:?}: {}.",
+                                tag, tool.name, e
+                            ),
+                        )
+                        .with_suggestion("Give it as, e.g., \"10s\" or \"500ms\"."),
+                    );
+                }
+                if let Some(timeout) = runner.timeout.filter(|t| !(t.is_finite() && *t > 0.)) {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Timeout ({}) of runner {:?} for {:?} should be positive.",
+                            timeout, tag, tool.name
+                        ),
+                    ));
+                }
+                if runner.shell.as_ref().is_some_and(shell::Shell::is_empty) {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Shell of runner {:?} for {:?} should not be empty.",
+                            tag, tool.name
+                        ),
+                    ));
+                }
+                let run_args_only = match (&tool.runtime, &tool.kind) {
+                    (Some(_), _) => Some("module"),
+                    (None, Some(_)) => Some("target"),
+                    (None, None) => None,
+                };
+                if let (Some(what), Some(_)) = (run_args_only, &runner.run_cmd) {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Runner {:?} for {:?} should use run_args, since its {} is \
+                             only run with run_args.",
+                            tag, tool.name, what
+                        ),
+                    ));
+                }
+            }
+
+            trace!("Confirming tags");
+            let tool_tags = tool.runners.keys().cloned().collect();
+            if !self.tags.is_subset(&tool_tags) {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Not all runners for {} have been defined. Missing: {:?}",
+                        tool.name,
+                        self.tags.difference(&tool_tags)
+                    ),
+                ));
+            }
+            if !self.tags.is_superset(&tool_tags) {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Invalid set of runner tags found for {}. Found extra: {:?}",
+                        tool.name,
+                        tool_tags.difference(&self.tags)
+                    ),
+                ));
             }
 
             trace!("Collecting tags that require files");
             for tag in &self.tags {
-                let runner = &tool.runners[tag];
-                if runner.needs_file() {
-                    tag_needs_file_due_to
-                        .entry(tag)
-                        .or_insert(vec![])
-                        .push(&tool.name);
+                if let Some(runner) = tool.runners.get(tag) {
+                    if runner.needs_file() {
+                        tag_needs_file_due_to
+                            .entry(tag)
+                            .or_insert(vec![])
+                            .push(&tool.name);
+                    }
                 }
             }
         }
 
         for test in &self.tests {
             debug!("Confirming sanity for test {}", test.name);
+            let subject = || DiagnosticSubject::Test(test.name.clone());
 
             trace!("Confirming tags");
             if !self.tags.contains(&test.tag) {
-                errored = true;
-                error!(
-                    "Invalid tag {} for test {}. Expected one of {:?}",
-                    test.tag, test.name, self.tags
-                );
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Invalid tag {} for test {}. Expected one of {:?}",
+                        test.tag, test.name, self.tags
+                    ),
+                ));
+            }
+
+            if let Some(problem) = test.generate.as_ref().and_then(|g| g.problem()) {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Generation of the input of test {}: {}.",
+                        test.name, problem
+                    ),
+                ));
             }
 
             if let Some(file) = &test.file {
                 trace!("Confirming file existence");
-                if !std::path::Path::new(file).exists() {
-                    errored = true;
-                    error!(
-                        "Could not find file {} for test {}. Are you sure it exists?",
-                        file, test.name
-                    );
+                // Generated inputs only exist once benchmarking starts
+                if test.generate.is_none() && !std::path::Path::new(file).exists() {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Could not find file {} for test {}. Are you sure it exists?",
+                            file, test.name
+                        ),
+                    ));
                 }
             } else if tag_needs_file_due_to.contains_key(&test.tag) {
-                errored = true;
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Test {} needs a file specified due to runner(s): {:?}",
+                        test.name, tag_needs_file_due_to[&test.tag]
+                    ),
+                ));
+            }
+
+            for param in test.param_names() {
+                if Test::PLACEHOLDERS.contains(&param) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            subject(),
+                            format!(
+                                "Parameter {} of test {} is shadowed by the built-in \
+                                 placeholder {{{}}}.",
+                                param, test.name, param
+                            ),
+                        )
+                        .with_suggestion("Rename the parameter."),
+                    );
+                }
+            }
+
+            if test.stdin_from_cmd.is_some() && test.stdin_from_file.is_some() {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Test {} should have only one of stdin_from_cmd and stdin_from_file set.",
+                        test.name
+                    ),
+                ));
+            }
+
+            for tool in self
+                .tools
+                .iter()
+                .filter(|tool| tool.runners.contains_key(&test.tag))
+            {
+                if tool.expects_failure(test) && tool.accepts_nonzero_exits(test) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            subject(),
+                            format!(
+                                "{} on test {} both expects failure and accepts nonzero exit \
+                                 codes as success.",
+                                tool.name, test.name
+                            ),
+                        )
+                        .with_suggestion(
+                            "Use either expect_failure, or success_exit_codes/allow_failure.",
+                        ),
+                    );
+                }
+                let (min_runs, max_runs) = self.run_bounds(test, tool);
+                if min_runs > max_runs && self.min_runs() <= self.max_runs() {
+                    diagnostics.push(Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Min runs ({}) of {} on test {} is greater than its max runs ({}).",
+                            min_runs, tool.name, test.name, max_runs
+                        ),
+                    ));
+                }
+            }
+
+            if let Some(timeout) = test.timeout.filter(|t| !(t.is_finite() && *t > 0.)) {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Timeout ({}) of test {} should be positive.",
+                        timeout, test.name
+                    ),
+                ));
+            }
+
+            if test.target_time.is_some_and(|t| t <= 0.) {
+                diagnostics.push(Diagnostic::new(
+                    subject(),
+                    format!(
+                        "Target time ({}) of test {} should be positive.",
+                        test.target_time.unwrap(),
+                        test.name
+                    ),
+                ));
+            }
+
+            if test
+                .validation()
+                .is_some_and(|validation| validation.cmd.is_none())
+                && self.main_tool.is_none()
+            {
+                diagnostics.push(
+                    Diagnostic::new(
+                        subject(),
+                        format!(
+                            "Test {} validates outputs against the main tool's, but there is \
+                             no main tool.",
+                            test.name
+                        ),
+                    )
+                    .with_suggestion("Set main_tool, or give validate a cmd."),
+                );
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Exits if the config has any problems, after reporting all of
+    /// them.
+    fn exit_if_insane(&self) {
+        let diagnostics = self.confirm_config_sanity();
+        for diagnostic in &diagnostics {
+            error!("{}", diagnostic);
+        }
+        if !diagnostics.is_empty() {
+            std::process::exit(self.ci().exit_code(diagnostics_outcome(&diagnostics)));
+        }
+    }
+
+    /// File that the runs of a suite in progress are checkpointed to, for
+    /// `--resume`
+    fn checkpoint(&self) -> PathBuf {
+        self.results_dir().join("checkpoint.csv")
+    }
+
+    /// File recording the hash of the config that the checkpoint was
+    /// made with, next to it
+    fn checkpoint_config_hash(&self) -> PathBuf {
+        self.results_dir().join("checkpoint.config-hash")
+    }
+
+    /// Fails unless the checkpoint was made with the same config, so
+    /// that runs of an edited config are never resumed
+    fn confirm_checkpoint_config(&self) -> Result<()> {
+        let hash = std::fs::read_to_string(self.checkpoint_config_hash()).ok();
+        if hash.as_ref() != self.config_hash.as_ref() {
+            return Err(eyre!(
+                "The checkpoint was made with a different benchify.toml, so its runs \
+                 may not be comparable. Run without --resume to start over."
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checkpoints the runs of every (test, tool) pair of `results` that
+    /// was completed. Written to a temporary file first, so that a crash
+    /// while writing never loses the previous checkpoint.
+    fn write_checkpoint<'b>(
+        &self,
+        results: impl Iterator<Item = &'b CellResult<'b>>,
+    ) -> Result<()> {
+        let completed: Vec<CellResult> = results
+            .filter(|r| r.samples.is_ok() && r.partial.is_none())
+            .cloned()
+            .collect();
+        let path = self.checkpoint();
+        let temporary = path.with_extension("csv.tmp");
+        std::fs::create_dir_all(self.results_dir())?;
+        // A checkpoint of another config is never left to be mistaken
+        // for one of this config
+        let hash = self.config_hash.clone().unwrap_or_default();
+        let hash_path = self.checkpoint_config_hash();
+        if std::fs::read_to_string(&hash_path).ok() != Some(hash.clone()) {
+            let _ = std::fs::remove_file(&path);
+            std::fs::write(&hash_path, hash)?;
+        }
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&temporary)?);
+        write_data_csv(&completed, &mut file)?;
+        file.into_inner()?.sync_all()?;
+        std::fs::rename(&temporary, &path)?;
+        Ok(())
+    }
+
+    /// Removes the checkpoint, once there is nothing left to resume
+    fn remove_checkpoint(&self) {
+        for path in [self.checkpoint(), self.checkpoint_config_hash()] {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    error!("Could not remove {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    fn get_timings_from_csv(&self, test: &Test, tool: &Tool) -> Result<Vec<Sample>> {
+        let results_dir = self.results_dir();
+        let res: Vec<_> = read_data_csv(&results_dir.join("data.csv"))?
+            .into_iter()
+            .filter(|r| {
+                r.cell_id() == cell_id(&test.id(), &tool.id())
+                    || (test.is_known_as(&r.test) && tool.is_known_as(&r.executor))
+            })
+            .map(|r| r.sample)
+            .collect();
+
+        if res.is_empty() {
+            Err(eyre!(
+                "timing for {} -- {} not found. should test for it",
+                test.name,
+                tool.name
+            ))
+        } else {
+            Ok(res)
+        }
+    }
+
+    /// How many times `tool` is run on `test`
+    fn iteration_policy(&self, test: &Test, tool: &Tool) -> plan::IterationPolicy {
+        let (min_runs, max_runs) = self.run_bounds(test, tool);
+        let runner = &tool.runners[&test.tag];
+        plan::IterationPolicy {
+            warmup: runner.warmup.or(self.warmup).unwrap_or_default(),
+            warmup_time: runner
+                .warmup_time
+                .as_ref()
+                .or(self.warmup_time.as_ref())
+                .and_then(|t| parse_duration(t).ok())
+                .map(|t| t.as_secs_f64()),
+            min_runs,
+            max_runs,
+            target_time: self.target_time(test),
+            stop_when: self.stop_when,
+        }
+    }
+
+    /// Plans out running every (test, tool) cell, in order
+    fn plan(&self) -> plan::Plan {
+        plan::Plan {
+            benchify_version: PROGRAM_VERSION.to_string(),
+            config_hash: self.config_hash.clone(),
+            cells: self
+                .tests
+                .iter()
+                .flat_map(|test| {
+                    self.tools.iter().map(move |tool| plan::PlannedCell {
+                        id: cell_id(&test.id(), &tool.id()),
+                        test: test.name.clone(),
+                        tool: tool.name.clone(),
+                        tag: test.tag.clone(),
+                        command: format!("{:?}", tool.run_command(self, test)),
+                        policy: self.iteration_policy(test, tool),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// The test and tool of each cell of `plan`, along with how many
+    /// times it is to be run
+    fn resolve_plan(
+        &self,
+        plan: &plan::Plan,
+    ) -> Result<Vec<(&Test, &Tool, plan::IterationPolicy)>> {
+        if plan.config_hash != self.config_hash {
+            warnings::warn(
+                "The plan was made from a different config. Its cells will be run as they are \
+                 currently configured.",
+            );
+        }
+        plan.cells
+            .iter()
+            .map(|cell| {
+                let (test, tool) = self
+                    .tests
+                    .iter()
+                    .flat_map(|test| self.tools.iter().map(move |tool| (test, tool)))
+                    .find(|(test, tool)| cell_id(&test.id(), &tool.id()) == cell.id)
+                    .ok_or(eyre!(
+                        "Plan has cell {:?}, which is not in the (selected) config",
+                        cell.id
+                    ))?;
+                Ok((test, tool, cell.policy.clone()))
+            })
+            .collect()
+    }
+
+    fn get_timings(
+        &self,
+        test: &Test,
+        tool: &Tool,
+        policy: &plan::IterationPolicy,
+    ) -> Result<Vec<Sample>> {
+        let ui = self.ui();
+        let mut measurement = measurement::Measurement::new(self, &ui, test, tool, policy, None);
+        while measurement.step()? {}
+        measurement.into_samples()
+    }
+
+    /// Benchmarks each of the (test, tool) pairs `cells` a run at a time,
+    /// round-robin, so that drift of the machine over time affects them
+    /// alike. Each pair's samples are produced in the same manner as by
+    /// [`Self::get_timings`].
+    fn get_timings_round_robin(
+        &self,
+        cells: &[(&Test, &Tool, &plan::IterationPolicy)],
+    ) -> Vec<Result<Vec<Sample>>> {
+        let ui = self.ui();
+        let pb = ui.spinner();
+        pb.set_style(ui.spinner_style());
+        ui.start_ticking(&pb);
+        let mut measurements: Vec<_> = cells
+            .iter()
+            .map(|(test, tool, policy)| {
+                measurement::Measurement::new(self, &ui, test, tool, policy, Some(pb.clone()))
+            })
+            .collect();
+        let mut outcomes: Vec<Option<Result<()>>> = cells.iter().map(|_| None).collect();
+        let mut round = 0;
+        while outcomes.iter().any(Option::is_none) {
+            round += 1;
+            for (measurement, outcome) in measurements.iter_mut().zip(&mut outcomes) {
+                if outcome.is_some() {
+                    continue;
+                }
+                pb.set_message(&format!(
+                    "Round-robin, round {}: {}",
+                    round,
+                    measurement.label()
+                ));
+                match measurement.step() {
+                    Ok(true) => {}
+                    Ok(false) => *outcome = Some(Ok(())),
+                    Err(e) => *outcome = Some(Err(e)),
+                }
+            }
+        }
+        pb.finish_and_clear();
+        // Left behind as the bars of pairs run one at a time are
+        for summary in measurements.iter().filter_map(|m| m.summary()) {
+            let pb = ui.spinner();
+            pb.set_style(ui.message_style());
+            pb.finish_with_message(summary);
+        }
+        measurements
+            .into_iter()
+            .zip(outcomes)
+            .map(|(measurement, outcome)| {
+                outcome.unwrap().and_then(|()| measurement.into_samples())
+            })
+            .collect()
+    }
+
+    pub fn execute(
+        &self,
+        plan: &plan::Plan,
+        use_known_csv_data: bool,
+        store_preparation_time: bool,
+        fail_fast: bool,
+        resume: bool,
+    ) -> Result<BenchifyResults<'_>> {
+        self.exit_if_insane();
+        let cells = self.resolve_plan(plan)?;
+        let checkpointed = if resume {
+            match read_data_csv(&self.checkpoint()) {
+                Ok(records) => {
+                    self.confirm_checkpoint_config()?;
+                    records
+                }
+                Err(e) => {
+                    info!("Nothing to resume ({}), so running everything.", e);
+                    vec![]
+                }
+            }
+        } else {
+            vec![]
+        };
+
+        // Builds are built before any preparation (and before their
+        // versions are recorded), each just once
+        let mut built = HashSet::new();
+        for (_, tool, _) in &cells {
+            if let Some(build) = &tool.registered_build {
+                if built.insert(&tool.name) {
+                    if let Err(e) = build.build(&self.ui(), &tool.name, |cmd| {
+                        tool.shell_command(self, None, cmd)
+                    }) {
+                        error!("Preparation failed: {}", e);
+                        std::process::exit(self.ci().exit_code(ci::Outcome::BenchmarkFailure));
+                    }
+                }
+            }
+        }
+
+        // Inputs are generated once per test, before anything is prepared
+        let mut generated: HashSet<&str> = HashSet::new();
+        for (test, _, _) in &cells {
+            if let Some(generate) = &test.generate {
+                if generated.insert(&test.name) {
+                    if let Err(e) = generate.generate(self, test) {
+                        error!("Generation failed: {}", e);
+                        std::process::exit(self.ci().exit_code(ci::Outcome::BenchmarkFailure));
+                    }
+                }
+            }
+        }
+
+        // Each tool is set up once, before any of its preparations, and
+        // torn down once every cell is done
+        let mut tools: Vec<&Tool> = vec![];
+        for (_, tool, _) in &cells {
+            if !tools.iter().any(|t| t.name == tool.name) {
+                tools.push(tool);
+            }
+        }
+        let teardown = |tools: &[&Tool]| {
+            for tool in tools {
+                if let Err(e) = tool.teardown(self) {
+                    error!("Teardown failed: {}", e);
+                }
+            }
+        };
+        for (i, tool) in tools.iter().enumerate() {
+            if let Err(e) = tool.setup(self) {
+                error!("Setup failed: {}", e);
+                teardown(&tools[..i]);
+                std::process::exit(self.ci().exit_code(ci::Outcome::BenchmarkFailure));
+            }
+        }
+
+        let mut metadata = self.metadata();
+
+        if self.parallel_prep() {
+            // Run all preparation in parallel
+            let ui = self.ui();
+            let mpb = ui.multi();
+            let mut t_t_pb = cells
+                .iter()
+                .map(|(test, tool, _)| (*test, *tool, Some(mpb.add(ui.spinner()))))
+                .collect::<Vec<(_, _, _)>>();
+            let monitor = interference::InterferenceMonitor::start(
+                self.interference_threshold(),
+                self.on_interference.unwrap_or_default(),
+            );
+            let mpb_thread = std::thread::spawn(move || mpb.join_and_clear());
+            if let Err(e) = t_t_pb
+                .par_iter_mut()
+                .map(|(test, tool, pb)| {
+                    // Each concurrent preparation is pinned to a CPU of its
+                    // own, so that their (optionally stored) timings are
+                    // not skewed by them sharing cores
+                    wait_for_free_cpu::and_run_on_cpu(|cpu| {
+                        tool.prepare(self, test, pb.take(), Some(cpu), store_preparation_time)
+                    })
+                })
+                .collect::<Result<()>>()
+            {
+                error!("Preparation failed: {}", e);
+                teardown(&tools);
+                std::process::exit(self.ci().exit_code(ci::Outcome::BenchmarkFailure));
+            }
+            mpb_thread.join().unwrap()?;
+            metadata.interference = Some(monitor.finish());
+        }
+
+        // Daemons of tools that have them, started the first time one
+        // of their cells is measured, and stopped once the suite is done
+        let daemons: std::sync::Mutex<HashMap<String, daemon::Daemon>> = Default::default();
+        let start_daemon = |tool: &Tool| -> Result<()> {
+            if let Some(config) = &tool.daemon {
+                let mut daemons = daemons.lock().unwrap();
+                if !daemons.contains_key(&tool.name) {
+                    daemons.insert(
+                        tool.name.clone(),
+                        daemon::Daemon::start(&tool.name, config, |cmd| {
+                            tool.shell_command(self, None, cmd)
+                        })?,
+                    );
+                }
+            }
+            Ok(())
+        };
+        let measure = |test: &Test, tool: &Tool, policy: &plan::IterationPolicy| {
+            start_daemon(tool)?;
+            self.get_timings(test, tool, policy)
+        };
+        // The runs of the `i`th cell that were already recorded, if
+        // asked to use them
+        let known_timings = |i: usize| -> Option<Vec<Sample>> {
+            let (test, tool, _) = &cells[i];
+            if !use_known_csv_data {
+                return None;
+            }
+            self.get_timings_from_csv(test, tool)
+                .inspect_err(|e| {
+                    info!(
+                        "Re-analyzing timing info for {}/{} because: {}",
+                        test.name, tool.name, e
+                    )
+                })
+                .ok()
+        };
+
+        let cell = |i: usize, samples, partial| {
+            let (test, tool, _) = &cells[i];
+            CellResult {
+                id: cell_id(&test.id(), &tool.id()),
+                machine_class: None,
+                test: &test.name,
+                tag: &test.tag,
+                group: test.group.as_deref(),
+                executor: &tool.name,
+                throughput: test.throughput.as_ref(),
+                samples,
+                partial,
+            }
+        };
+        // An outcome of a cell: its result (unless it was never started),
+        // and why the suite should stop after it, if it should
+        type Outcome<'c> = (Option<CellResult<'c>>, Option<String>);
+
+        // Readies the `i`th cell to be measured, producing its outcome
+        // instead if it needs no measuring
+        let start_cell = |i: usize| -> Result<Option<Outcome>> {
+            let (test, tool, _) = &cells[i];
+            info!("Running tests for {}", test.name);
+            debug!("Test: {:?}", test);
+
+            if interrupt::interrupted() {
+                return Ok(Some((None, Some("Interrupted".to_string()))));
+            }
+
+            info!("Testing tool {}", tool.name);
+            trace!("Tool: {:?}", tool.runners[&test.tag]);
+
+            let id = cell_id(&test.id(), &tool.id());
+            let resumed: Vec<Sample> = checkpointed
+                .iter()
+                .filter(|r| r.cell_id() == id)
+                .map(|r| r.sample.clone())
+                .collect();
+            if !resumed.is_empty() {
+                info!("Resuming with the checkpointed runs of {}", tool.name);
+                if self.parallel_prep() {
+                    tool.cleanup(self, test)?;
+                }
+                return Ok(Some((Some(cell(i, Ok(resumed), None)), None)));
+            }
+
+            if test.validation().is_some() {
+                // Never validated against a previous invocation's output
+                let _ = std::fs::remove_file(tool.output_path(self, test));
+            }
+            // Nor mistaken for the logs of a previous invocation's runs
+            let _ = std::fs::remove_dir_all(tool.run_logs_dir(self, test));
+            if !self.parallel_prep() {
+                if let Err(e) = tool.prepare(self, test, None, None, store_preparation_time) {
+                    if interrupt::interrupted() {
+                        return Ok(Some((None, Some("Interrupted".to_string()))));
+                    }
+                    return Err(e);
+                }
+            }
+            Ok(None)
+        };
+
+        // Cleans up after the `i`th cell was measured, producing its
+        // outcome
+        let finish_cell = |i: usize, samples: Result<Vec<Sample>>| -> Result<Outcome> {
+            let (test, tool, _) = &cells[i];
+            tool.cleanup(self, test)?;
+
+            let partial =
+                (samples.is_ok() && interrupt::interrupted()).then(|| "interrupted".to_string());
+            let mut aborted = None;
+            if fail_fast {
+                if let Err(e) = &samples {
+                    aborted = Some(format!(
+                        "[{}] [{}] failed, stopping due to --fail-fast: {}",
+                        test.name, tool.name, e
+                    ));
+                }
+            }
+            Ok((Some(cell(i, samples, partial)), aborted))
+        };
+
+        // Runs the cells of a unit of work, producing the outcome of each
+        let run_unit = |unit: &[usize]| -> Result<Vec<(usize, Outcome)>> {
+            let mut outcomes = vec![];
+            let mut to_measure = vec![];
+            for &i in unit {
+                if let Some(outcome) = start_cell(i)? {
+                    outcomes.push((i, outcome));
+                } else if let Some(samples) = known_timings(i) {
+                    outcomes.push((i, finish_cell(i, Ok(samples))?));
+                } else {
+                    to_measure.push(i);
+                }
+            }
+            if let [i] = to_measure[..] {
+                let (test, tool, policy) = &cells[i];
+                outcomes.push((i, finish_cell(i, measure(test, tool, policy))?));
+            } else if !to_measure.is_empty() {
+                // Pairs whose daemon did not start fail without a run
+                let mut started = vec![];
+                for i in to_measure {
+                    match start_daemon(cells[i].1) {
+                        Ok(()) => started.push(i),
+                        Err(e) => outcomes.push((i, finish_cell(i, Err(e))?)),
+                    }
+                }
+                let measured: Vec<_> = started
+                    .iter()
+                    .map(|&i| (cells[i].0, cells[i].1, &cells[i].2))
+                    .collect();
+                for (i, samples) in started
+                    .into_iter()
+                    .zip(self.get_timings_round_robin(&measured))
+                {
+                    outcomes.push((i, finish_cell(i, samples)?));
+                }
+            }
+            Ok(outcomes)
+        };
+
+        // Each cell on its own, or, run round-robin, those of each test
+        // together
+        let units: Vec<Vec<usize>> = match self.run_order() {
+            scheduler::RunOrder::Sequential => (0..cells.len()).map(|i| vec![i]).collect(),
+            scheduler::RunOrder::RoundRobin => {
+                let mut units: Vec<Vec<usize>> = vec![];
+                for (i, (test, _, _)) in cells.iter().enumerate() {
+                    match units.iter_mut().find(|u| cells[u[0]].0.name == test.name) {
+                        Some(unit) => unit.push(i),
+                        None => units.push(vec![i]),
+                    }
+                }
+                units
+            }
+        };
+
+        let parallelism = self.parallel_benchmarks().clamp(1, units.len().max(1));
+        let scheduler = std::sync::Mutex::new(scheduler::Scheduler::new(
+            units
+                .iter()
+                .map(|unit| unit.iter().map(|&i| cells[i].1.name.as_str()).collect()),
+        ));
+        let done = std::sync::Mutex::new(cells.iter().map(|_| None).collect::<Vec<_>>());
+        let aborted: std::sync::Mutex<Option<String>> = Default::default();
+        let worker = || -> Result<()> {
+            while aborted.lock().unwrap().is_none() {
+                // Each concurrent unit holds a CPU of its own, which is
+                // taken before the unit is, so that units start in the
+                // order of the plan
+                let ran = wait_for_free_cpu::and_run(|| {
+                    let u = scheduler::Scheduler::next(&scheduler)?;
+                    let ran = run_unit(&units[u]);
+                    scheduler.lock().unwrap().finish(u);
+                    Some(ran)
+                });
+                let Some(ran) = ran else {
+                    break;
+                };
+                let outcomes = ran.inspect_err(|_| {
+                    aborted
+                        .lock()
+                        .unwrap()
+                        .get_or_insert_with(|| "A (test, tool) pair errored".to_string());
+                })?;
+                let mut done = done.lock().unwrap();
+                for (i, (result, stop)) in outcomes {
+                    done[i] = result;
+                    if let Some(reason) = stop {
+                        aborted.lock().unwrap().get_or_insert(reason);
+                    }
+                }
+                if let Err(e) = self.write_checkpoint(done.iter().flatten()) {
+                    error!("Could not checkpoint the results so far: {}", e);
+                }
+            }
+            Ok(())
+        };
+        let monitor = (parallelism > 1).then(|| {
+            interference::InterferenceMonitor::start(
+                self.interference_threshold(),
+                self.on_interference.unwrap_or_default(),
+            )
+        });
+        let shared_bars = (parallelism > 1).then(|| self.ui().share_bars());
+        let ran = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..parallelism).map(|_| scope.spawn(worker)).collect();
+            workers.into_iter().try_for_each(|w| w.join().unwrap())
+        });
+        drop(shared_bars);
+        if let Some(monitor) = monitor {
+            *metadata.interference.get_or_insert(false) |= monitor.finish();
+        }
+        teardown(&tools);
+        ran?;
+        // In the order of the plan, whatever order they finished in
+        let mut results: Vec<CellResult> =
+            done.into_inner().unwrap().into_iter().flatten().collect();
+        let aborted = aborted.into_inner().unwrap();
+        self.validate_outputs(&cells, &mut results);
+
+        Ok(BenchifyResults {
+            skipped: self.skipped_cells(&results, aborted.is_some()),
+            results,
+            main_tool: self.main_tool.as_ref().map(String::as_ref),
+            report: self.report(),
+            metadata,
+            aborted,
+            baseline: None,
+            build_sets: &self.build_sets,
+        })
+    }
+
+    /// Fails the cells of `results` whose tool's output on their test
+    /// does not pass the test's validation: either its `cmd`, or being
+    /// identical to the output of the main tool
+    fn validate_outputs(
+        &self,
+        cells: &[(&Test, &Tool, plan::IterationPolicy)],
+        results: &mut [CellResult],
+    ) {
+        for (test, tool, _) in cells {
+            let Some(validation) = test.validation() else {
+                continue;
+            };
+            let id = cell_id(&test.id(), &tool.id());
+            // Only validated against a main tool that succeeded
+            let expected = cells
+                .iter()
+                .find(|(t, reference, _)| {
+                    t.name == test.name && self.main_tool.as_ref() == Some(&reference.name)
+                })
+                .map(|(_, reference, _)| reference)
+                .filter(|reference| {
+                    let reference_id = cell_id(&test.id(), &reference.id());
+                    results
+                        .iter()
+                        .any(|r| r.id == reference_id && r.samples.is_ok())
+                })
+                .map(|reference| reference.output_path(self, test));
+            let Some(result) = results.iter_mut().find(|r| r.id == id && r.samples.is_ok()) else {
+                continue;
+            };
+            let output = tool.output_path(self, test);
+            let problem = if !output.is_file() {
+                Some("no output was saved to validate".to_string())
+            } else if let Some(cmd) = &validation.cmd {
+                let cmd = test
+                    .interpolated_into(cmd)
+                    .replace("{OUTPUT}", &output.to_string_lossy())
+                    .replace(
+                        "{EXPECTED}",
+                        &expected
+                            .as_ref()
+                            .map_or(String::new(), |e| e.to_string_lossy().into_owned()),
+                    );
+                match tool
+                    .shell_command(self, Some(test), &cmd)
+                    .stdin(std::process::Stdio::null())
+                    .output()
+                {
+                    Ok(o) if o.status.success() => None,
+                    Ok(o) => {
+                        let report = String::from_utf8_lossy(&o.stdout);
+                        let report = report.trim();
+                        Some(format!(
+                            "its output failed validation by `{}`{}{}",
+                            cmd,
+                            if report.is_empty() { "" } else { ": " },
+                            report
+                        ))
+                    }
+                    Err(e) => Some(format!("could not run `{}`: {}", cmd, e)),
+                }
+            } else {
+                match &expected {
+                    Some(expected)
+                        if std::fs::read(expected).ok() != std::fs::read(&output).ok() =>
+                    {
+                        Some(format!(
+                            "its output differs from that of {}",
+                            self.main_tool.as_deref().unwrap_or_default()
+                        ))
+                    }
+                    _ => None,
+                }
+            };
+            if let Some(problem) = problem {
                 error!(
-                    "Test {} needs a file specified due to runner(s): {:?}",
-                    test.name, tag_needs_file_due_to[&test.tag]
+                    "[{}] [{}] failed validation: {}",
+                    test.name, tool.name, problem
                 );
+                result.samples = Err(eyre!("Invalid output: {}", problem));
+            }
+        }
+    }
+
+    /// Runs `plan` by sharding its cells across `workers`, and collects
+    /// up the results they recorded. Cells that a worker has no results
+    /// for are recorded as failures.
+    pub fn execute_distributed(
+        &self,
+        plan: &plan::Plan,
+        workers: &[distributed::Worker],
+    ) -> Result<BenchifyResults<'_>> {
+        let cells = self.resolve_plan(plan)?;
+        let metadata = self.metadata();
+
+        let shards = distributed::shard(plan, workers.len());
+        let outputs: Vec<Result<String>> = workers
+            .par_iter()
+            .zip(shards.par_iter())
+            .map(|(worker, shard)| {
+                info!("Running {} cells on {}", shard.cells.len(), worker);
+                distributed::run_on(worker, shard)
+            })
+            .collect();
+        let mut records = vec![];
+        for (worker, output) in workers.iter().zip(outputs) {
+            match output.and_then(|csv| {
+                parse_data_csv(csv.as_bytes(), &format!("Output of worker {}", worker))
+            }) {
+                Ok(r) => records.extend(r),
+                Err(e) => error!("{}", e),
+            }
+        }
+
+        let mut results = vec![];
+        for (test, tool, _) in &cells {
+            let id = cell_id(&test.id(), &tool.id());
+            // Runs from different classes of machines are kept apart
+            let mut by_class: Vec<(Option<String>, Vec<Sample>)> = vec![];
+            for r in records.iter().filter(|r| r.cell_id() == id) {
+                match by_class.iter_mut().find(|(c, _)| *c == r.machine_class) {
+                    Some((_, samples)) => samples.push(r.sample.clone()),
+                    None => by_class.push((r.machine_class.clone(), vec![r.sample.clone()])),
+                }
+            }
+            let cell = |machine_class, samples| CellResult {
+                id: id.clone(),
+                machine_class,
+                test: &test.name,
+                tag: &test.tag,
+                group: test.group.as_deref(),
+                executor: &tool.name,
+                throughput: test.throughput.as_ref(),
+                samples,
+                partial: None,
+            };
+            if by_class.is_empty() {
+                results.push(cell(None, Err(eyre!("No results from worker"))));
+            }
+            for (machine_class, samples) in by_class {
+                results.push(cell(machine_class, Ok(samples)));
             }
         }
 
-        if errored {
-            std::process::exit(1);
-        }
+        Ok(BenchifyResults {
+            skipped: self.skipped_cells(&results, false),
+            results,
+            main_tool: self.main_tool.as_ref().map(String::as_ref),
+            report: self.report(),
+            metadata,
+            aborted: None,
+            baseline: None,
+            build_sets: &self.build_sets,
+        })
     }
 
-    fn get_timings_from_csv(&self, test: &Test, tool: &Tool) -> Result<Vec<std::time::Duration>> {
-        let results_dir = self.results_dir();
-        let csv_file = results_dir.join("data.csv");
-        let data_reader = csv::Reader::from_path(csv_file)?;
-
-        #[derive(Debug, Deserialize)]
-        struct Record {
-            #[serde(rename = "Test")]
-            test: String,
-            #[serde(rename = "Executor")]
-            tool: String,
-            #[serde(rename = "Timing (s)")]
-            timing: f64,
-        }
-
-        let res: Vec<_> = data_reader
-            .into_deserialize()
-            .filter_map(|r: csv::Result<Record>| {
-                let r: Record = r.ok()?;
-                (r.test == test.name && r.tool == tool.name)
-                    .then(|| std::time::Duration::from_secs_f64(r.timing))
-            })
-            .collect();
-
-        if res.len() == 0 {
-            Err(eyre!(
-                "timing for {} -- {} not found. should test for it",
-                test.name,
-                tool.name
-            ))
-        } else {
-            Ok(res)
+    /// The (test, tool) pairs of the tests in `results` that were not
+    /// run, and why, so that reports can tell why a tool is absent from
+    /// a test's table. If the suite was `aborted`, that is why any cell
+    /// of the selected tools is missing.
+    fn skipped_cells(&self, results: &[CellResult], aborted: bool) -> Vec<SkippedCell> {
+        let mut skipped = vec![];
+        for test in &self.tests {
+            if !results.iter().any(|r| r.test == test.name) {
+                continue;
+            }
+            let not_run = self.tools.iter().filter(|tool| {
+                !results
+                    .iter()
+                    .any(|r| r.test == test.name && r.executor == tool.name)
+            });
+            for tool in not_run {
+                skipped.push(SkippedCell {
+                    test: test.name.clone(),
+                    tool: tool.name.clone(),
+                    reason: if !tool.runners.contains_key(&test.tag) {
+                        "unsupported (no runner for this tag)"
+                    } else if aborted {
+                        "the suite was stopped before it was run"
+                    } else {
+                        "not in the plan"
+                    },
+                });
+            }
+            for (tool, reason) in &self.deselected_tools {
+                skipped.push(SkippedCell {
+                    test: test.name.clone(),
+                    tool: tool.clone(),
+                    reason,
+                });
+            }
         }
+        skipped
     }
+}
 
-    fn get_timings(
-        &self,
-        test: &Test,
-        tool: &Tool,
-        global_warmup: Option<u32>,
-    ) -> Result<Vec<std::time::Duration>> {
-        let num_initial_estimates = self.max_runs().min(2) as usize;
+/// A (test, tool) pair that was not run, and why
+#[derive(Serialize, Debug, Clone)]
+pub struct SkippedCell {
+    test: String,
+    tool: String,
+    reason: &'static str,
+}
 
-        let expected_time_seconds = 2.5f32;
+/// A single run of a tool on a test
+#[derive(Debug, Clone)]
+pub struct Sample {
+    // Index of the run, counting warmup runs
+    iteration: usize,
+    warmup: bool,
+    timing: std::time::Duration,
+    // `None` if the process was terminated by a signal
+    exit_status: Option<i32>,
+    // Whether the run was killed for exceeding the timeout, in which
+    // case `timing` is the timeout: a lower bound of the actual time
+    censored: bool,
+    // Any extra metrics collected for the run, by name
+    metrics: BTreeMap<String, f64>,
+}
 
-        let pb_style = ProgressStyle::default_bar()
-            .template(
-                "{spinner:.green} {msg} \
-                     [{wide_bar:.cyan/blue}] {pos}/{len} ({elapsed} -- ETA {eta})",
-            )
-            .progress_chars("#>-");
-
-        if let Some(warmup_runs) = tool.runners[&test.tag].warmup.or(global_warmup) {
-            let pb = ProgressBar::new(warmup_runs as u64);
-            pb.set_style(pb_style.clone());
-            pb.set_message(&format!("[{}] [{}] Warmup runs", test.name, tool.name));
-            for _ in 0..warmup_runs {
-                pb.inc(1);
-                tool.run(test).map_err(|e| {
-                    pb.set_style(ProgressStyle::default_bar().template("{spinner:.green} {msg}"));
-                    pb.finish_with_message(&format!(
-                        "[{}] [{}] Failure during warmup: {}",
-                        test.name, tool.name, e
-                    ));
-                    e
-                })?;
+/// Version of the data.csv schema, recorded in a `#` comment on its
+/// first line. Files without it are version 1, which only have the
+/// Test, Executor and Timing columns. Version 2 has no Cell ID column,
+/// version 3 no Machine Class column, and version 4 no Censored column.
+const DATA_CSV_VERSION: u32 = 5;
+
+/// Columns of data.csv, in order. Extra metrics follow as additional
+/// columns.
+const DATA_CSV_COLUMNS: [&str; 10] = [
+    "Cell ID",
+    "Machine Class",
+    "Test",
+    "Tag",
+    "Executor",
+    "Iteration",
+    "Warmup",
+    "Exit Status",
+    "Censored",
+    "Timing (s)",
+];
+
+/// A row of data.csv
+#[derive(Debug)]
+struct DataRecord {
+    cell_id: Option<String>,
+    machine_class: Option<String>,
+    test: String,
+    tag: Option<String>,
+    executor: String,
+    sample: Sample,
+}
+
+/// Writes out the raw data of every run of `results`, in the data.csv
+/// format
+fn write_data_csv(results: &[CellResult], mut writer: impl std::io::Write) -> Result<()> {
+    writeln!(writer, "# benchify-data-version: {}", DATA_CSV_VERSION)?;
+    let mut data_writer = csv::Writer::from_writer(writer);
+    let metric_names: BTreeSet<&str> = results
+        .iter()
+        .filter_map(|r| r.samples.as_ref().ok())
+        .flatten()
+        .flat_map(|s| s.metrics.keys().map(String::as_str))
+        .collect();
+    data_writer.write_record(DATA_CSV_COLUMNS.iter().chain(metric_names.iter()))?;
+    for r in results.iter() {
+        if let Ok(samples) = &r.samples {
+            for sample in samples.iter() {
+                let mut record = vec![
+                    r.id.clone(),
+                    r.machine_class.clone().unwrap_or_default(),
+                    r.test.to_string(),
+                    r.tag.to_string(),
+                    r.executor.to_string(),
+                    sample.iteration.to_string(),
+                    sample.warmup.to_string(),
+                    sample
+                        .exit_status
+                        .map(|c| c.to_string())
+                        .unwrap_or_default(),
+                    sample.censored.to_string(),
+                    sample.timing.as_secs_f64().to_string(),
+                ];
+                record.extend(metric_names.iter().map(|m| {
+                    sample
+                        .metrics
+                        .get(*m)
+                        .map(|v| v.to_string())
+                        .unwrap_or_default()
+                }));
+                data_writer.write_record(record)?;
             }
-            pb.finish_and_clear();
         }
+    }
+    data_writer.flush()?;
+    Ok(())
+}
 
-        let pb = ProgressBar::new(num_initial_estimates as u64);
-        pb.set_style(pb_style.clone());
-        pb.set_message(&format!(
-            "[{}] [{}] Initial estimates",
-            test.name, tool.name
-        ));
-        let initial_estimates = (0..num_initial_estimates)
-            .map(|_| {
-                pb.inc(1);
-                tool.run(test).map_err(|e| {
-                    pb.set_style(ProgressStyle::default_bar().template("{spinner:.green} {msg}"));
-                    pb.finish_with_message(&format!(
-                        "[{}] [{}] Failure during initial estimates: {}",
-                        test.name, tool.name, e
-                    ));
-                    e
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-        pb.finish_and_clear();
+fn read_data_csv(csv_file: &Path) -> Result<Vec<DataRecord>> {
+    parse_data_csv(std::fs::File::open(csv_file)?, &format!("{:?}", csv_file))
+}
 
-        let mean_estimated_time_per_iter_secs = initial_estimates
-            .iter()
-            .map(|t| t.as_secs_f32())
-            .sum::<f32>()
-            / num_initial_estimates as f32;
+/// Parses data.csv formatted data out of `reader`, naming it `source`
+/// in errors
+fn parse_data_csv(reader: impl std::io::Read, source: &str) -> Result<Vec<DataRecord>> {
+    let mut data_reader = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(reader);
 
-        let preferred_number_of_iterations = self.max_runs().min(
-            self.min_runs()
-                .max((expected_time_seconds / mean_estimated_time_per_iter_secs) as _),
-        );
+    // Older versions of data.csv only have the Test, Executor and
+    // Timing columns, so everything else is optional. Any columns that
+    // are not known are extra metrics.
+    let headers = data_reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let test_col = column("Test").ok_or(eyre!("{} is missing a Test column", source))?;
+    let tool_col = column("Executor").ok_or(eyre!("{} is missing an Executor column", source))?;
+    let sample_cols = SampleColumns::new(&headers, source)?;
+    let cell_id_col = column("Cell ID");
+    let machine_class_col = column("Machine Class");
+    let tag_col = column("Tag");
 
-        let pb = ProgressBar::new(preferred_number_of_iterations as u64);
-        pb.set_message(&format!("[{}] [{}] Benchmarking", test.name, tool.name));
-        pb.set_style(pb_style);
-        let remaining_iterations = (num_initial_estimates..preferred_number_of_iterations as usize)
-            .map(|i| {
-                pb.set_position(i as u64);
-                tool.run(test).map_err(|e| {
-                    pb.set_style(ProgressStyle::default_bar().template("{spinner:.green} {msg}"));
-                    pb.finish_with_message(&format!(
-                        "[{}] [{}] Failure during benchmarking run#{}: {}",
-                        test.name, tool.name, i, e
-                    ));
-                    e
-                })
+    Ok(data_reader
+        .records()
+        .enumerate()
+        .filter_map(|(i, r)| {
+            let r = r.ok()?;
+            let field = |col: Option<usize>| col.and_then(|c| r.get(c)).filter(|f| !f.is_empty());
+            Some(DataRecord {
+                cell_id: field(cell_id_col).map(str::to_string),
+                machine_class: field(machine_class_col).map(str::to_string),
+                test: r.get(test_col)?.to_string(),
+                tag: field(tag_col).map(str::to_string),
+                executor: r.get(tool_col)?.to_string(),
+                sample: sample_cols.parse(&r, i)?,
             })
-            .collect::<Result<Vec<_>>>()?;
-
-        let timings: Vec<_> = initial_estimates
-            .into_iter()
-            .chain(remaining_iterations.into_iter())
-            .collect();
-        let mean_timing = timings.iter().sum::<std::time::Duration>() / (timings.len() as u32);
-        pb.set_style(ProgressStyle::default_bar().template("{spinner:.green} {msg}"));
-        pb.finish_with_message(&format!(
-            "[{}] [{}]\tMean {:?} in {} runs",
-            test.name,
-            tool.name,
-            mean_timing,
-            timings.len()
-        ));
+        })
+        .collect())
+}
 
-        Ok(timings)
-    }
+/// Parses the samples of a single (test, tool) pair, measured outside
+/// of benchify, out of `reader`, naming it `source` in errors. Only the
+/// Timing (s) column of the data.csv format is required, so that a
+/// single column of timings will do.
+fn parse_samples_csv(reader: impl std::io::Read, source: &str) -> Result<Vec<Sample>> {
+    let mut data_reader = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(reader);
+    let headers = data_reader.headers()?.clone();
+    let sample_cols = SampleColumns::new(&headers, source)?;
+    data_reader
+        .records()
+        .enumerate()
+        .map(|(i, r)| {
+            sample_cols
+                .parse(&r?, i)
+                .ok_or_else(|| eyre!("Row {} of {} is not a valid sample", i + 1, source))
+        })
+        .collect()
+}
 
-    pub fn execute(
-        &self,
-        use_known_csv_data: bool,
-        store_preparation_time: bool,
-    ) -> Result<BenchifyResults> {
-        self.confirm_config_sanity();
+/// Columns of data.csv formatted data that make up a [`Sample`]. Only
+/// the timing is required; any columns that are not known are extra
+/// metrics.
+struct SampleColumns {
+    timing: usize,
+    iteration: Option<usize>,
+    warmup: Option<usize>,
+    exit_status: Option<usize>,
+    censored: Option<usize>,
+    metrics: Vec<(usize, String)>,
+}
 
-        if self.parallel_prep() {
-            // Run all preparation in parallel
-            let mpb = MultiProgress::new();
-            let mut t_t_pb = self
-                .tests
+impl SampleColumns {
+    fn new(headers: &csv::StringRecord, source: &str) -> Result<Self> {
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        Ok(SampleColumns {
+            timing: column("Timing (s)")
+                .ok_or(eyre!("{} is missing a Timing (s) column", source))?,
+            iteration: column("Iteration"),
+            warmup: column("Warmup"),
+            exit_status: column("Exit Status"),
+            censored: column("Censored"),
+            metrics: headers
                 .iter()
-                .map(|test| {
-                    self.tools
-                        .iter()
-                        .map(|tool| (test, tool, Some(mpb.add(ProgressBar::new_spinner()))))
-                        .collect::<Vec<(_, _, _)>>()
-                })
-                .flatten()
-                .collect::<Vec<(_, _, _)>>();
-            let mpb_thread = std::thread::spawn(move || mpb.join_and_clear());
-            if let Err(e) = t_t_pb
-                .par_iter_mut()
-                .map(|(test, tool, pb)| {
-                    wait_for_free_cpu::and_run(|| {
-                        tool.prepare(test, pb.take(), store_preparation_time)
-                    })
-                })
-                .collect::<Result<()>>()
-            {
-                error!("Preparation failed: {}", e);
-                std::process::exit(1);
-            }
-            mpb_thread.join().unwrap()?;
-        }
+                .enumerate()
+                .filter(|(_, h)| !DATA_CSV_COLUMNS.contains(h))
+                .map(|(c, h)| (c, h.to_string()))
+                .collect(),
+        })
+    }
 
-        Ok(BenchifyResults {
-            results: self
-                .tests
+    /// The sample in the `i`th record `r`, if it is valid
+    fn parse(&self, r: &csv::StringRecord, i: usize) -> Option<Sample> {
+        let field = |col: Option<usize>| col.and_then(|c| r.get(c)).filter(|f| !f.is_empty());
+        Some(Sample {
+            iteration: field(self.iteration).map_or(Some(i), |f| f.parse().ok())?,
+            warmup: field(self.warmup).map_or(Some(false), |f| f.parse().ok())?,
+            timing: std::time::Duration::try_from_secs_f64(r.get(self.timing)?.parse().ok()?)
+                .ok()?,
+            exit_status: field(self.exit_status).and_then(|f| f.parse().ok()),
+            censored: field(self.censored).map_or(Some(false), |f| f.parse().ok())?,
+            metrics: self
+                .metrics
                 .iter()
-                .map(|test| {
-                    info!("Running tests for {}", test.name);
-                    debug!("Test: {:?}", test);
+                .filter_map(|(c, name)| Some((name.clone(), field(Some(*c))?.parse().ok()?)))
+                .collect(),
+        })
+    }
+}
 
-                    self.tools.iter().map(move |tool| {
-                        info!("Testing tool {}", tool.name);
-                        trace!("Tool: {:?}", tool.runners[&test.tag]);
+impl DataRecord {
+    /// The recorded cell ID, or for versions of data.csv that predate
+    /// them, the default one for the test and tool names
+    fn cell_id(&self) -> String {
+        self.cell_id
+            .clone()
+            .unwrap_or_else(|| cell_id(&slugify(&self.test), &slugify(&self.executor)))
+    }
+}
 
-                        if !self.parallel_prep() {
-                            tool.prepare(test, None, store_preparation_time)?;
-                        }
+/// All the runs of a single (test, tool) pair
+#[derive(Debug)]
+pub struct CellResult<'a> {
+    id: String,
+    // Class of machine the runs were made on, if labeled
+    machine_class: Option<String>,
+    test: &'a str,
+    tag: &'a str,
+    group: Option<&'a str>,
+    executor: &'a str,
+    throughput: Option<&'a Throughput>,
+    samples: Result<Vec<Sample>>,
+    // If the runs were cut short, the reason why. Only the runs that
+    // had already completed are in `samples`.
+    partial: Option<String>,
+}
 
-                        let timings = if use_known_csv_data {
-                            self.get_timings_from_csv(test, tool).or_else(|e| {
-                                info!(
-                                    "Re-analyzing timing info for {}/{} because: {}",
-                                    test.name, tool.name, e
-                                );
-                                self.get_timings(test, tool, self.warmup)
-                            })
-                        } else {
-                            self.get_timings(test, tool, self.warmup)
-                        };
-                        tool.cleanup(test)?;
+impl<'a> Clone for CellResult<'a> {
+    fn clone(&self) -> Self {
+        CellResult {
+            id: self.id.clone(),
+            machine_class: self.machine_class.clone(),
+            test: self.test,
+            tag: self.tag,
+            group: self.group,
+            executor: self.executor,
+            throughput: self.throughput,
+            samples: match &self.samples {
+                Ok(samples) => Ok(samples.clone()),
+                Err(e) => Err(eyre!("{}", e)),
+            },
+            partial: self.partial.clone(),
+        }
+    }
+}
 
-                        Ok((test.name.as_ref(), tool.name.as_ref(), timings))
-                    })
-                })
-                .flatten()
-                .collect::<Result<Vec<_>>>()?,
-            main_tool: self.main_tool.as_ref().map(String::as_ref),
+impl<'a> CellResult<'a> {
+    /// Mean of the metric `name` over the measured runs that have it
+    fn mean_metric(&self, name: &str) -> Option<f64> {
+        let values: Vec<f64> = self
+            .samples
+            .as_ref()
+            .ok()?
+            .iter()
+            .filter(|s| !s.warmup)
+            .filter_map(|s| s.metrics.get(name).copied())
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    /// Mean user and system CPU times of the measured runs, along with
+    /// the ratio of their sum to the mean wall time (i.e., the number of
+    /// cores kept busy on average)
+    fn cpu_time(&self) -> Option<(f64, f64, f64)> {
+        let user = self.mean_metric(rusage::USER_TIME_METRIC)?;
+        let system = self.mean_metric(rusage::SYSTEM_TIME_METRIC)?;
+        let wall = self.mean_metric("Wall Time (s)")?;
+        Some((user, system, (user + system) / wall))
+    }
+
+    /// Number of warmup runs that would have been enough to avoid the
+    /// drift of the measured runs, if they drifted (see [`warmup_drift`])
+    fn suggested_warmup(&self) -> Option<usize> {
+        let samples = self.samples.as_ref().ok()?;
+        let mut measured: Vec<&Sample> = samples.iter().filter(|s| !s.warmup).collect();
+        measured.sort_by_key(|s| s.iteration);
+        let timings: Vec<std::time::Duration> = measured.iter().map(|s| s.timing).collect();
+        let warmup = samples.len() - measured.len();
+        warmup_drift::drifting_runs(&timings).map(|drifting| warmup + drifting)
+    }
+
+    /// Number of measured runs that were censored by a timeout
+    fn censored_runs(&self) -> usize {
+        self.samples.as_ref().map_or(0, |samples| {
+            samples.iter().filter(|s| !s.warmup && s.censored).count()
         })
     }
+
+    /// Timings of the measured (i.e., non-warmup) runs
+    fn timings(&self) -> Result<Vec<std::time::Duration>> {
+        match &self.samples {
+            Ok(samples) => Ok(samples
+                .iter()
+                .filter(|s| !s.warmup)
+                .map(|s| s.timing)
+                .collect()),
+            Err(e) => Err(eyre!("{}", e)),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct BenchifyResults<'a> {
-    // (test, executor, [timing])
-    results: Vec<(&'a str, &'a str, Result<Vec<std::time::Duration>>)>,
+    results: Vec<CellResult<'a>>,
     main_tool: Option<&'a str>,
+    report: ReportConfig,
+    metadata: metadata::Metadata,
+    // If the suite was stopped before running all (test, tool) pairs,
+    // the reason why
+    aborted: Option<String>,
+    // Earlier results to compare against, if any
+    baseline: Option<baseline::Baseline>,
+    // Tools whose builds were benchmarked as tools of their own
+    build_sets: &'a [build_matrix::BuildSet],
+    // (test, tool) pairs of the tests above that were not run
+    skipped: Vec<SkippedCell>,
+}
+
+/// Version of the results.json schema
+const RESULTS_JSON_VERSION: u32 = 1;
+
+/// Contents of results.json. All times are in seconds.
+#[derive(Serialize, Debug)]
+struct ResultsJson<'a> {
+    format_version: u32,
+    metadata: &'a metadata::Metadata,
+    /// Tool that ratios are computed against
+    baseline: Option<&'a str>,
+    /// Estimator used for `central_value` and ratios
+    estimator: Estimator,
+    aborted: Option<&'a str>,
+    cells: Vec<CellJson>,
+    /// (test, tool) pairs of the tests above that were not run, and why
+    skipped: &'a [SkippedCell],
+}
+
+#[derive(Serialize, Debug)]
+struct CellJson {
+    id: String,
+    machine_class: Option<String>,
+    test: String,
+    tag: String,
+    group: Option<String>,
+    tool: String,
+    throughput: Option<Throughput>,
+    error: Option<String>,
+    partial: Option<String>,
+    /// Ratio of the central value to the baseline's for the same test,
+    /// if the baseline ran successfully
+    ratio_to_baseline: Option<f64>,
+    /// Number of warmup runs that would have avoided a significant
+    /// drift of the first measured runs, if they drifted
+    suggested_warmup: Option<usize>,
+    /// Statistics of the measured (i.e., non-warmup) runs
+    statistics: Option<StatisticsJson>,
+    runs: Vec<RunJson>,
+}
+
+#[derive(Serialize, Debug)]
+struct StatisticsJson {
+    central_value: f64,
+    mean: f64,
+    sample_stddev: f64,
+    min: f64,
+    max: f64,
+    median: f64,
+    p90: f64,
+    p95: f64,
+    p99: f64,
+    /// Median absolute deviation
+    mad: f64,
+    count: usize,
+    /// Number of runs flagged as outliers. Not included in `count` (or
+    /// any of the statistics) if outliers are excluded.
+    outliers: usize,
+    /// Number of runs that timed out, and are counted at the timeout.
+    /// If nonzero, the statistics are lower bounds.
+    censored: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct RunJson {
+    iteration: usize,
+    warmup: bool,
+    timing: f64,
+    exit_status: Option<i32>,
+    /// Whether the run timed out, `timing` then being the timeout
+    censored: bool,
+    metrics: BTreeMap<String, f64>,
 }
 
 type Ratios = Option<HashMap<String, f64>>;
 
+/// Results of a tool on a single test, as summarized
+struct ToolResults<'a> {
+    executor: &'a str,
+    timings: Result<Vec<std::time::Duration>>,
+    partial: bool,
+    // Mean max RSS in KiB, if recorded
+    max_rss: Option<f64>,
+    // Mean user and system CPU times in seconds, and the ratio of their
+    // sum to the mean wall time, if recorded
+    cpu_time: Option<(f64, f64, f64)>,
+    // Number of measured runs that were censored by a timeout
+    censored: usize,
+    // Number of warmup runs suggested by warm-up drift, if any
+    suggested_warmup: Option<usize>,
+}
+
+type TestResults<'a> = Vec<ToolResults<'a>>;
+
+// [(test, [timing])]
+type ExecutorResults<'a> = Vec<(&'a str, Vec<std::time::Duration>)>;
+
 fn format_summary(
     main: Option<&str>,
-    results: Vec<(&str, Result<&[std::time::Duration]>)>,
+    report: &ReportConfig,
+    results: TestResults,
 ) -> Result<(Ratios, String)> {
     use std::fmt::Write;
 
     let mut result = String::new();
-    let summaries = results
-        .iter()
-        .map(|(n, t)| (n, t.as_ref().map(|t| Statistics::new(t))));
-    let comparison_point = if let Some(main) = main {
+    let extra_statistics = report.statistics();
+    let summaries = results.iter().map(|r| {
+        (
+            &r.executor,
+            r.timings.as_ref().map(|t| report.summarize(t)),
+            &r.partial,
+        )
+    });
+    let results_of = |n: &str| results.iter().find(|r| r.executor == n).unwrap();
+    // Memory use is only shown if it was recorded at all
+    let show_memory = results.iter().any(|r| r.max_rss.is_some());
+    let show_cpu_time = report.cpu_time();
+    let extra_columns = extra_statistics.len() + show_memory as usize + 3 * show_cpu_time as usize;
+    let main_point = main.and_then(|main| {
         summaries
             .clone()
-            .find(|(t, _s)| *t == &main)
-            .map(|(t, s)| (t, s.unwrap()))
-            .unwrap()
-    } else {
+            .find(|(t, _s, _p)| *t == &main)
+            .and_then(|(t, s, _p)| Some((t, s.ok()?)))
+    });
+    // If the main tool did not run successfully (or was not run at
+    // all, say due to an interruption), fall back to the fastest tool
+    // for this test. Ratios are then not meaningful across tests.
+    let main_ran = main_point.is_some();
+    let comparison_point = main_point.or_else(|| {
         summaries
             .clone()
-            .filter(|(_t, s)| s.is_ok())
-            .map(|(t, s)| (t, s.unwrap()))
-            .min_by_key(|(_t, s)| s.mean)
-            .unwrap()
-    };
+            .filter(|(_t, s, _p)| s.is_ok())
+            .map(|(t, s, _p)| (t, s.unwrap()))
+            .min_by_key(|(_t, s)| report.central_value(s))
+    });
     let mut ratios = HashMap::new();
-    let summaries: Vec<(String, String, String, String)> = summaries
-        .map(|(n, stats)| {
-            let name = if comparison_point.0 == n {
+    // [(tool, outliers, runs)]
+    let mut outliers = vec![];
+    // [(tool, censored runs, runs)]
+    let mut censored = vec![];
+    let drifting: Vec<String> = results
+        .iter()
+        .filter_map(|r| {
+            Some(format!(
+                "{} (try warmup = {})",
+                r.executor, r.suggested_warmup?
+            ))
+        })
+        .collect();
+    let summaries: Vec<(String, String, String, Vec<String>, String)> = summaries
+        .map(|(n, stats, partial)| {
+            let name = if comparison_point.as_ref().map(|c| c.0) == Some(n) {
                 format!("**{}**", n)
             } else {
                 n.to_string()
             };
-            match stats {
-                Ok(stats) => {
-                    let mean = format!("{:.3}", stats.mean.as_secs_f64() * 1000.);
+            let name = if *partial {
+                format!("{} (partial)", name)
+            } else {
+                name
+            };
+            match (stats, &comparison_point) {
+                (Ok(stats), Some(comparison_point)) => {
+                    let central = report.central_value(&stats).as_secs_f64();
+                    // Censored runs are counted at the timeout, so the
+                    // statistics are only lower bounds
+                    let censored_runs = results_of(n).censored;
+                    let mean = if censored_runs > 0 {
+                        censored.push(format!("{} {}/{}", n, censored_runs, stats.count));
+                        format!(">={:.3}", central * 1000.)
+                    } else {
+                        format!("{:.3}", central * 1000.)
+                    };
                     let stddev = format!("{:.3}", stats.sample_stddev.as_secs_f64() * 1000.);
-                    let ratio = stats.mean.as_secs_f64() / comparison_point.1.mean.as_secs_f64();
-                    if main.is_some() {
+                    let mut extras: Vec<String> = extra_statistics
+                        .iter()
+                        .map(|s| {
+                            format!("{:.3}", report.statistic(&stats, *s).as_secs_f64() * 1000.)
+                        })
+                        .collect();
+                    if show_memory {
+                        extras.push(match results_of(n).max_rss {
+                            Some(rss) => format!("{:.1}", rss / 1024.),
+                            None => "-".to_string(),
+                        });
+                    }
+                    if show_cpu_time {
+                        match results_of(n).cpu_time {
+                            Some((user, system, utilization)) => extras.extend([
+                                format!("{:.3}", user * 1000.),
+                                format!("{:.3}", system * 1000.),
+                                format!("{:.2}", utilization),
+                            ]),
+                            None => extras.extend(vec!["-".to_string(); 3]),
+                        }
+                    }
+                    let ratio = central / report.central_value(&comparison_point.1).as_secs_f64();
+                    if main_ran {
                         ratios.insert(n.to_string(), ratio);
                     }
+                    if stats.outliers > 0 {
+                        let runs = if report.exclude_outliers() {
+                            stats.count + stats.outliers
+                        } else {
+                            stats.count
+                        };
+                        outliers.push(format!("{} {}/{}", n, stats.outliers, runs));
+                    }
                     let ratio = format!("{:.3}", ratio);
-                    (name, mean, stddev, ratio)
+                    (name, mean, stddev, extras, ratio)
                 }
-                Err(e) => (name, "FAIL".to_string(), "FAIL".to_string(), e.to_string()),
+                (Ok(_), None) => unreachable!(),
+                (Err(e), _) => (
+                    name,
+                    "FAIL".to_string(),
+                    "FAIL".to_string(),
+                    vec!["FAIL".to_string(); extra_columns],
+                    e.to_string(),
+                ),
             }
         })
         .collect();
-    let ratio_to_header = format!("Ratio to {}", comparison_point.0);
-    let lengths = summaries
+    let mean_header = format!("{} (ms)", report.estimator_name());
+    let mut extra_headers: Vec<String> = extra_statistics
         .iter()
-        .cloned()
-        .chain(std::iter::once((
-            "".to_string(),
-            "Mean (ms)".to_string(),
-            "StdDev (ms)".to_string(),
-            ratio_to_header.clone(),
-        )))
-        .map(|(t, m, s, r)| (t.len(), m.len(), s.len(), r.len()));
+        .map(|s| format!("{} (ms)", s.name()))
+        .collect();
+    if show_memory {
+        extra_headers.push("Max RSS (MiB)".to_string());
+    }
+    if show_cpu_time {
+        extra_headers.extend([
+            "User (ms)".to_string(),
+            "Sys (ms)".to_string(),
+            "CPU/Wall".to_string(),
+        ]);
+    }
+    let ratio_to_header = match &comparison_point {
+        Some(comparison_point) => format!("Ratio to {}", comparison_point.0),
+        None => "Error".to_string(),
+    };
+    let rows = summaries.iter().cloned().chain(std::iter::once((
+        "".to_string(),
+        mean_header.clone(),
+        "StdDev (ms)".to_string(),
+        extra_headers.clone(),
+        ratio_to_header.clone(),
+    )));
+    let lengths = rows
+        .clone()
+        .map(|(t, m, s, _e, r)| (t.len(), m.len(), s.len(), r.len()));
     let name_length = lengths.clone().map(|l| l.0).max().unwrap();
     let mean_length = lengths.clone().map(|l| l.1).max().unwrap();
     let stddev_length = lengths.clone().map(|l| l.2).max().unwrap();
     let ratio_length = lengths.clone().map(|l| l.3).max().unwrap();
+    let extra_lengths: Vec<usize> = (0..extra_headers.len())
+        .map(|i| {
+            rows.clone()
+                .map(|(_, _, _, e, _)| e[i].len())
+                .max()
+                .unwrap()
+        })
+        .collect();
+    let extra_cells = |extras: &[String], header: bool| -> String {
+        extras
+            .iter()
+            .zip(&extra_lengths)
+            .map(|(e, l)| {
+                if header {
+                    format!(" {: <l$} |", e, l = l)
+                } else {
+                    format!(" {: >l$} |", e, l = l)
+                }
+            })
+            .collect()
+    };
 
     writeln!(
         &mut result,
-        "| {n: <nl$} | {m: <ml$} ± {s: <sl$} | {r: <rl$} |",
+        "| {n: <nl$} | {m: <ml$} ± {s: <sl$} |{e} {r: <rl$} |",
         nl = name_length,
         n = "",
         ml = mean_length,
-        m = "Mean (ms)",
+        m = mean_header,
         sl = stddev_length,
         s = "StdDev (ms)",
+        e = extra_cells(&extra_headers, true),
         rl = ratio_length,
         r = ratio_to_header,
     )?;
     writeln!(
         &mut result,
-        "|:{dash:-<nl$}-|-{dash:-<ml$}---{dash:-<sl$}:|-{dash:-<rl$}:|",
+        "|:{dash:-<nl$}-|-{dash:-<ml$}---{dash:-<sl$}:|{e}-{dash:-<rl$}:|",
         dash = "-",
         nl = name_length,
         ml = mean_length,
         sl = stddev_length,
+        e = extra_lengths
+            .iter()
+            .map(|l| format!("-{:-<l$}:|", "-", l = l))
+            .collect::<String>(),
         rl = ratio_length,
     )?;
-    for (name, mean, stddev, ratio) in summaries {
+    for (name, mean, stddev, extras, ratio) in summaries {
         writeln!(
             &mut result,
-            "| {n: <nl$} | {m: >ml$} ± {s: >sl$} | {r: >rl$} |",
+            "| {n: <nl$} | {m: >ml$} ± {s: >sl$} |{e} {r: >rl$} |",
             nl = name_length,
             n = name,
             ml = mean_length,
             m = mean,
             sl = stddev_length,
             s = stddev,
+            e = extra_cells(&extras, false),
             rl = ratio_length,
             r = ratio,
         )?;
     }
-    Ok((main.is_some().then(|| ratios), result))
+    if !outliers.is_empty() {
+        writeln!(
+            &mut result,
+            "\nOutliers (by modified z-score){}: {}",
+            if report.exclude_outliers() {
+                ", excluded from the statistics"
+            } else {
+                ""
+            },
+            outliers.join(", ")
+        )?;
+    }
+    if !censored.is_empty() {
+        writeln!(
+            &mut result,
+            "\nCensored runs (timed out, and counted at the timeout, so the \
+             statistics are lower bounds): {}",
+            censored.join(", ")
+        )?;
+    }
+    if !drifting.is_empty() {
+        writeln!(
+            &mut result,
+            "\nWarm-up drift (the first measured runs are significantly slower \
+             than later ones, inflating the statistics): {}",
+            drifting.join(", ")
+        )?;
+    }
+    if report.is_randomized() {
+        writeln!(
+            &mut result,
+            "\n{}% confidence intervals: percentile bootstrap, with seed {} (pass \
+             `--seed {}` to reproduce them)",
+            bootstrap::CONFIDENCE,
+            report.seed.unwrap_or_default(),
+            report.seed.unwrap_or_default()
+        )?;
+    }
+    Ok((main_ran.then_some(ratios), result))
 }
 
 impl<'a> BenchifyResults<'a> {
+    /// Scrubs the hostname, username and absolute paths out of the
+    /// metadata and errors, as per `--redact`
+    fn redact(&mut self) {
+        self.metadata = self.metadata.redacted();
+        for r in self.results.iter_mut() {
+            if let Err(e) = &r.samples {
+                r.samples = Err(eyre!("{}", redact::redact(&e.to_string())));
+            }
+        }
+        self.aborted = self.aborted.as_deref().map(redact::redact);
+    }
+
+    /// Labels all the results as having been recorded on `machine_class`
+    fn label_machine_class(&mut self, machine_class: &str) {
+        for r in self.results.iter_mut() {
+            r.machine_class = Some(machine_class.to_string());
+        }
+        self.metadata.machine_class = Some(machine_class.to_string());
+    }
+
+    /// The results, split up by the class of machine they were recorded
+    /// on, in the order the classes first show up
+    fn by_machine_class(&self) -> Vec<(Option<&str>, BenchifyResults<'a>)> {
+        let mut classes: Vec<Option<&str>> = vec![];
+        for r in self.results.iter() {
+            if !classes.contains(&r.machine_class.as_deref()) {
+                classes.push(r.machine_class.as_deref());
+            }
+        }
+        classes
+            .into_iter()
+            .map(|class| {
+                let facet = BenchifyResults {
+                    results: self
+                        .results
+                        .iter()
+                        .filter(|r| r.machine_class.as_deref() == class)
+                        .cloned()
+                        .collect(),
+                    main_tool: self.main_tool,
+                    report: self.report.clone(),
+                    metadata: self.metadata.clone(),
+                    aborted: self.aborted.clone(),
+                    baseline: self.baseline.clone(),
+                    build_sets: self.build_sets,
+                    skipped: self.skipped.clone(),
+                };
+                (class, facet)
+            })
+            .collect()
+    }
+
     fn save_to_directory(&self, results_dir: &Path) -> Result<()> {
         // Make sure the results directory exists
         std::fs::create_dir_all(results_dir)?;
         assert!(results_dir.is_dir());
 
+        // Write out all the data
+        self.write_data_csv(std::fs::File::create(results_dir.join("data.csv"))?)?;
+
+        std::fs::write(
+            results_dir.join("metadata.json"),
+            serde_json::to_string_pretty(&self.metadata)? + "\n",
+        )?;
+
+        std::fs::write(
+            results_dir.join("results.json"),
+            serde_json::to_string_pretty(&self.results_json()?)? + "\n",
+        )?;
+
+        std::fs::write(results_dir.join("report.html"), self.html_report()?)?;
+        std::fs::write(results_dir.join("junit.xml"), self.junit_xml()?)?;
+
+        let mut index = String::new();
         {
-            // Write out all the data
-            let mut data_writer = csv::Writer::from_path(results_dir.join("data.csv"))?;
-            data_writer.write_record(&["Test", "Executor", "Timing (s)"])?;
-            for (test, executor, timings) in self.results.iter() {
-                if let Ok(timings) = timings {
-                    for timing in timings.iter() {
-                        data_writer.serialize((test, executor, timing.as_secs_f64()))?;
+            use std::fmt::Write;
+            writeln!(index, "# Benchmark results")?;
+            writeln!(index)?;
+            if let Some(note) = &self.metadata.note {
+                writeln!(index, "Note: {}", note)?;
+                writeln!(index)?;
+            }
+            if !self.metadata.labels.is_empty() {
+                writeln!(index, "Labels: {}", self.metadata.formatted_labels())?;
+                writeln!(index)?;
+            }
+            if let Some(system) = &self.metadata.system {
+                if system.warnings.is_empty() {
+                    writeln!(index, "System checks: passed")?;
+                } else {
+                    writeln!(index, "System checks:")?;
+                    writeln!(index)?;
+                    for warning in &system.warnings {
+                        writeln!(index, "- {}", warning)?;
                     }
                 }
+                writeln!(index)?;
+            }
+            writeln!(
+                index,
+                "Raw data of every run: [data.csv](data.csv) and \
+                 [results.json](results.json). Environment: \
+                 [metadata.json](metadata.json). Plots: [report.html](report.html). \
+                 For CI: [junit.xml](junit.xml)."
+            )?;
+        }
+
+        // Results from different classes of machines are summarized
+        // separately, each in their own subdirectory
+        let facets = self.by_machine_class();
+        if facets.len() > 1 {
+            for (class, facet) in facets {
+                let class = class.unwrap_or("unlabeled");
+                std::fs::create_dir_all(results_dir.join(class))?;
+                facet.save_summaries(&results_dir.join(class))?;
+                index += &facet.format_index(results_dir, Path::new(class), Some(class))?;
+            }
+        } else {
+            self.save_summaries(results_dir)?;
+            index += &self.format_index(results_dir, Path::new(""), None)?;
+        }
+        if !self.build_sets.is_empty() {
+            std::fs::write(results_dir.join("builds.md"), self.format_build_impact()?)?;
+            index += "\nImpact of build configurations: [builds.md](builds.md)\n";
+        }
+        std::fs::write(results_dir.join("index.md"), index)?;
+        Ok(())
+    }
+
+    /// Subdirectory of the results directory for the files of `test`:
+    /// `<tag>/<test id>`
+    fn test_dir(&self, test: &str) -> PathBuf {
+        let r = self.results.iter().find(|r| r.test == test).unwrap();
+        let dir = cell_dir(r.tag, &r.id);
+        dir.parent().unwrap().to_path_buf()
+    }
+
+    /// Links to the summaries under `prefix` (relative to
+    /// `results_dir`), and to the directories of the cells that left
+    /// any files behind (e.g., crash artifacts), for index.md
+    fn format_index(
+        &self,
+        results_dir: &Path,
+        prefix: &Path,
+        machine_class: Option<&str>,
+    ) -> Result<String> {
+        use std::fmt::Write;
+
+        let link = |path: &Path| path.to_string_lossy().replace('\\', "/");
+        let mut index = String::new();
+        if let Some(class) = machine_class {
+            writeln!(index)?;
+            writeln!(index, "## Machine class {}", class)?;
+        }
+        let mut tags: Vec<&str> = vec![];
+        for r in self.results.iter() {
+            if !tags.contains(&r.tag) {
+                tags.push(r.tag);
+            }
+        }
+        for tag in tags {
+            writeln!(index)?;
+            writeln!(
+                index,
+                "{} Tag {}",
+                if machine_class.is_some() { "###" } else { "##" },
+                tag
+            )?;
+            writeln!(index)?;
+            for (test, _) in self.results_by_test() {
+                let cells: Vec<&CellResult> = self
+                    .results
+                    .iter()
+                    .filter(|r| r.test == test && r.tag == tag)
+                    .collect();
+                if cells.is_empty() {
+                    continue;
+                }
+                write!(
+                    index,
+                    "- [{}]({})",
+                    test,
+                    link(&prefix.join(self.test_dir(test)).join("summary.md"))
+                )?;
+                let cell_dirs: Vec<String> = cells
+                    .iter()
+                    .map(|r| cell_dir(r.tag, &r.id))
+                    .filter(|dir| results_dir.join(dir).is_dir())
+                    .map(|dir| {
+                        format!(
+                            "[{}]({}/)",
+                            dir.file_name().unwrap().to_string_lossy(),
+                            link(&dir)
+                        )
+                    })
+                    .collect();
+                if !cell_dirs.is_empty() {
+                    write!(index, " (files of {})", cell_dirs.join(", "))?;
+                }
+                writeln!(index)?;
+            }
+        }
+        let groups: Vec<&str> = self.groups().into_iter().flatten().collect();
+        if !groups.is_empty() {
+            writeln!(index)?;
+            writeln!(
+                index,
+                "{} Groups",
+                if machine_class.is_some() { "###" } else { "##" }
+            )?;
+            writeln!(index)?;
+            for group in groups {
+                writeln!(
+                    index,
+                    "- [{}]({})",
+                    group,
+                    link(&prefix.join(format!("summary_group_{}.md", group)))
+                )?;
+            }
+        }
+        Ok(index)
+    }
+
+    fn save_summaries(&self, results_dir: &Path) -> Result<()> {
+        for (test, results) in self.results_by_test() {
+            // Write out data for each test, in its own directory
+            use std::io::Write;
+            let dir = results_dir.join(self.test_dir(test));
+            std::fs::create_dir_all(&dir)?;
+            let mut file = std::fs::File::create(dir.join("summary.md"))?;
+            writeln!(file, "# Summary of runs for {}", test)?;
+            writeln!(file)?;
+            if let Some(group) = self.group_of(test) {
+                writeln!(file, "Group: {}", group)?;
+                writeln!(file)?;
+            }
+            write!(
+                file,
+                "{}",
+                format_summary(self.main_tool, &self.report, results)?.1
+            )?;
+            write!(file, "{}", self.format_skipped(test))?;
+            write!(file, "{}", self.format_baseline_comparison(test)?)?;
+        }
+
+        for group in self.groups().into_iter().flatten() {
+            // Write out a section for each test in the group, along
+            // with the aggregate over the group
+            use std::io::Write;
+            let mut file =
+                std::fs::File::create(results_dir.join(format!("summary_group_{}.md", group)))?;
+            writeln!(file, "# Summary of runs for group {}", group)?;
+            let mut tests = vec![];
+            for (test, results) in self.results_by_test() {
+                if self.group_of(test) == Some(group) {
+                    writeln!(file)?;
+                    writeln!(file, "## {}", test)?;
+                    writeln!(file)?;
+                    write!(
+                        file,
+                        "{}",
+                        format_summary(self.main_tool, &self.report, results)?.1
+                    )?;
+                    write!(file, "{}", self.format_skipped(test))?;
+                    write!(file, "{}", self.format_baseline_comparison(test)?)?;
+                    tests.push(test);
+                }
+            }
+            writeln!(file)?;
+            write!(
+                file,
+                "{}",
+                self.format_geomeans("Sorted tools by geo mean of ratios:", &tests)?
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Everything known about the results, in a form suitable for
+    /// post-processing
+    fn results_json(&self) -> Result<ResultsJson<'_>> {
+        let mut cells = vec![];
+        for (_, facet) in self.by_machine_class() {
+            let mut ratios = HashMap::new();
+            for (test, results) in facet.results_by_test() {
+                if let (Some(r), _) = format_summary(self.main_tool, &self.report, results)? {
+                    ratios.insert(test, r);
+                }
+            }
+            for r in facet.results.iter() {
+                let statistics = r.timings().ok().map(|t| {
+                    let stats = self.report.summarize(&t);
+                    StatisticsJson {
+                        central_value: self.report.central_value(&stats).as_secs_f64(),
+                        mean: stats.mean.as_secs_f64(),
+                        sample_stddev: stats.sample_stddev.as_secs_f64(),
+                        min: stats.min.as_secs_f64(),
+                        max: stats.max.as_secs_f64(),
+                        median: stats.median.as_secs_f64(),
+                        p90: stats.percentile(90.).as_secs_f64(),
+                        p95: stats.percentile(95.).as_secs_f64(),
+                        p99: stats.percentile(99.).as_secs_f64(),
+                        mad: stats.mad.as_secs_f64(),
+                        count: stats.count,
+                        outliers: stats.outliers,
+                        censored: r.censored_runs(),
+                    }
+                });
+                cells.push(CellJson {
+                    id: r.id.clone(),
+                    machine_class: r.machine_class.clone(),
+                    test: r.test.to_string(),
+                    tag: r.tag.to_string(),
+                    group: r.group.map(str::to_string),
+                    tool: r.executor.to_string(),
+                    throughput: r.throughput.cloned(),
+                    error: r.samples.as_ref().err().map(|e| e.to_string()),
+                    partial: r.partial.clone(),
+                    suggested_warmup: r.suggested_warmup(),
+                    ratio_to_baseline: ratios.get(r.test).and_then(|t| t.get(r.executor)).copied(),
+                    statistics,
+                    runs: r
+                        .samples
+                        .iter()
+                        .flatten()
+                        .map(|s| RunJson {
+                            iteration: s.iteration,
+                            warmup: s.warmup,
+                            timing: s.timing.as_secs_f64(),
+                            exit_status: s.exit_status,
+                            censored: s.censored,
+                            metrics: s.metrics.clone(),
+                        })
+                        .collect(),
+                });
+            }
+        }
+        Ok(ResultsJson {
+            format_version: RESULTS_JSON_VERSION,
+            metadata: &self.metadata,
+            baseline: self.main_tool,
+            estimator: self.report.estimator(),
+            aborted: self.aborted.as_deref(),
+            cells,
+            skipped: &self.skipped,
+        })
+    }
+
+    /// Renders plots of the results into `results_dir`, separately for
+    /// each class of machine, as with the summaries
+    fn save_plots(&self, results_dir: &Path) -> Result<()> {
+        let facets = self.by_machine_class();
+        if facets.len() > 1 {
+            for (class, facet) in facets {
+                let dir = results_dir.join(class.unwrap_or("unlabeled"));
+                plots::save(&dir, &facet.results_json()?)?;
             }
-            data_writer.flush()?;
+            Ok(())
+        } else {
+            plots::save(results_dir, &self.results_json()?)
         }
+    }
 
-        for (test, results) in self.results_by_test() {
-            // Write out data for each test
-            use std::io::Write;
-            let mut file = std::fs::File::create(results_dir.join(format!("summary_{}.md", test)))?;
-            writeln!(file, "# Summary of runs for {}", test)?;
-            writeln!(file)?;
-            write!(file, "{}", format_summary(self.main_tool, results)?.1)?;
+    /// Writes the results in the on-disk format of Criterion.rs into
+    /// `dir`, as the baseline `baseline`, separately for each class of
+    /// machine
+    fn save_criterion(&self, dir: &Path, baseline: &str) -> Result<()> {
+        let facets = self.by_machine_class();
+        let faceted = facets.len() > 1;
+        for (class, facet) in facets {
+            let dir = match class {
+                Some(class) if faceted => dir.join(class),
+                None if faceted => dir.join("unlabeled"),
+                _ => dir.to_path_buf(),
+            };
+            for r in facet.results.iter() {
+                criterion::write(&dir, baseline, r, self.report.seed.unwrap_or_default())?;
+            }
         }
-
         Ok(())
     }
 
-    fn results_by_test(&self) -> Vec<(&'a str, Vec<(&'a str, Result<&[std::time::Duration]>)>)> {
+    /// Appends every run to the history database in `results_dir`
+    fn append_to_history(&self, results_dir: &Path) -> Result<()> {
+        let runs = self
+            .results
+            .iter()
+            .filter_map(|r| Some((r, r.samples.as_ref().ok()?)))
+            .flat_map(|(r, samples)| {
+                samples.iter().map(move |s| history::Run {
+                    cell_id: &r.id,
+                    machine_class: r.machine_class.as_deref(),
+                    test: r.test,
+                    tool: r.executor,
+                    iteration: s.iteration,
+                    warmup: s.warmup,
+                    timing: s.timing.as_secs_f64(),
+                    exit_status: s.exit_status,
+                    censored: s.censored,
+                })
+            });
+        history::append(results_dir, &self.metadata, runs)
+    }
+
+    /// Writes out the raw data of every run, in the data.csv format
+    fn write_data_csv(&self, writer: impl std::io::Write) -> Result<()> {
+        write_data_csv(&self.results, writer)
+    }
+
+    fn results_by_test(&self) -> Vec<(&'a str, TestResults<'a>)> {
         let mut mapped = HashMap::new();
-        for (test, executor, timings) in self.results.iter() {
-            mapped.entry(*test).or_insert(vec![]).push((*executor, {
-                timings.as_deref().map_err(|e| eyre!("{}", e))
-            }));
+        for r in self.results.iter() {
+            mapped.entry(r.test).or_insert(vec![]).push(ToolResults {
+                executor: r.executor,
+                timings: r.timings(),
+                partial: r.partial.is_some(),
+                max_rss: r.mean_metric(rusage::MAX_RSS_METRIC),
+                cpu_time: r.cpu_time(),
+                censored: r.censored_runs(),
+                suggested_warmup: r.suggested_warmup(),
+            });
         }
         let mut res = vec![];
-        for (test, _executor, _timings) in self.results.iter() {
-            if let Some((k, v)) = mapped.remove_entry(test) {
+        for r in self.results.iter() {
+            if let Some((k, v)) = mapped.remove_entry(r.test) {
                 res.push((k, v));
             }
         }
         res
     }
 
-    fn results_by_executor(&self) -> Vec<(&'a str, Vec<(&'a str, &[std::time::Duration])>)> {
+    fn results_by_executor(&self) -> Vec<(&'a str, ExecutorResults<'a>)> {
         let mut mapped = HashMap::new();
-        for (test, executor, timings) in self.results.iter() {
-            if let Ok(timings) = timings {
+        for r in self.results.iter() {
+            if let Ok(timings) = r.timings() {
                 mapped
-                    .entry(*executor)
+                    .entry(r.executor)
                     .or_insert(vec![])
-                    .push((*test, timings.as_ref()));
+                    .push((r.test, timings));
             }
         }
         let mut res = vec![];
-        for (_test, executor, _timings) in self.results.iter() {
-            if let Some((k, v)) = mapped.remove_entry(executor) {
+        for r in self.results.iter() {
+            if let Some((k, v)) = mapped.remove_entry(r.executor) {
                 res.push((k, v));
             }
         }
         res
     }
 
-    fn display_summary(&self) -> Result<()> {
+    /// Groups of tests, in the order they first show up. Tests without
+    /// a group are collected under `None`.
+    fn groups(&self) -> Vec<Option<&'a str>> {
+        let mut res = vec![];
+        for r in self.results.iter() {
+            if !res.contains(&r.group) {
+                res.push(r.group);
+            }
+        }
+        res
+    }
+
+    fn group_of(&self, test: &str) -> Option<&'a str> {
+        self.results
+            .iter()
+            .find(|r| r.test == test)
+            .and_then(|r| r.group)
+    }
+
+    fn display_summary(&self, out: &mut dyn std::io::Write) -> Result<()> {
+        let facets = self.by_machine_class();
+        if facets.len() > 1 {
+            for (class, facet) in facets {
+                writeln!(out)?;
+                writeln!(
+                    out,
+                    "==== Machine class: {} ====",
+                    class.unwrap_or("unlabeled")
+                )?;
+                facet.display_facet_summary(out)?;
+            }
+            Ok(())
+        } else {
+            self.display_facet_summary(out)
+        }
+    }
+
+    fn display_facet_summary(&self, out: &mut dyn std::io::Write) -> Result<()> {
+        let groups = self.groups();
+        let grouped = groups.iter().any(Option::is_some);
+
+        for group in groups {
+            if grouped {
+                writeln!(out)?;
+                writeln!(out, "# {}", group.unwrap_or("Ungrouped"))?;
+            }
+            let mut tests = vec![];
+            for (test, results) in self.results_by_test() {
+                if self.group_of(test) != group {
+                    continue;
+                }
+                writeln!(out)?;
+                writeln!(out, "{} {}", if grouped { "##" } else { "#" }, test)?;
+                writeln!(out)?;
+                write!(
+                    out,
+                    "{}",
+                    format_summary(self.main_tool, &self.report, results)?.1
+                )?;
+                write!(out, "{}", self.format_skipped(test))?;
+                write!(out, "{}", self.format_baseline_comparison(test)?)?;
+                writeln!(out)?;
+                tests.push(test);
+            }
+            if let Some(group) = group {
+                write!(
+                    out,
+                    "{}",
+                    self.format_geomeans(
+                        &format!("Sorted tools in group {} by geo mean of ratios:", group),
+                        &tests,
+                    )?
+                )?;
+            }
+        }
+
+        if grouped {
+            writeln!(out)?;
+            writeln!(out, "# All tests")?;
+            writeln!(out)?;
+        }
+        let tests: Vec<&str> = self.results_by_test().iter().map(|(t, _)| *t).collect();
+        write!(
+            out,
+            "{}",
+            self.format_geomeans("Sorted tools by geo mean of ratios:", &tests)?
+        )?;
+
+        write!(out, "{}", self.format_throughputs()?)?;
+
+        if let Some(baseline) = &self.baseline {
+            let verdicts = self.baseline_verdicts();
+            let count = |v| verdicts.iter().filter(|(_, _, x)| *x == v).count();
+            writeln!(
+                out,
+                "Compared to baseline {}{}: {} regressions, {} improvements, {} unchanged",
+                baseline.name,
+                baseline.formatted_note(),
+                count(baseline::Verdict::Regression),
+                count(baseline::Verdict::Improvement),
+                count(baseline::Verdict::NoChange),
+            )?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Footnote on the tools that were not run on `test`, and why.
+    /// Empty if all of them were.
+    fn format_skipped(&self, test: &str) -> String {
+        let skipped: Vec<String> = self
+            .skipped
+            .iter()
+            .filter(|s| s.test == test)
+            .map(|s| format!("{} ({})", s.tool, s.reason))
+            .collect();
+        if skipped.is_empty() {
+            String::new()
+        } else {
+            format!("\nNot run: {}\n", skipped.join(", "))
+        }
+    }
+
+    /// For each cell that ran successfully both now and in the
+    /// baseline: its result, its statistics in the baseline, and
+    /// whether it regressed or improved since
+    fn baseline_verdicts(&self) -> Vec<(&CellResult<'a>, Statistics, baseline::Verdict)> {
+        let Some(baseline) = &self.baseline else {
+            return vec![];
+        };
+        self.results
+            .iter()
+            .filter_map(|r| {
+                let base = self
+                    .report
+                    .summarize(baseline.timings(&r.id, r.machine_class.as_deref())?);
+                let new = self.report.summarize(&r.timings().ok()?);
+                let verdict = baseline::verdict(
+                    &base,
+                    self.report.central_value(&base),
+                    &new,
+                    self.report.central_value(&new),
+                    self.report.regression_threshold(),
+                );
+                Some((r, base, verdict))
+            })
+            .collect()
+    }
+
+    /// The (test, tool) pairs that regressed since the baseline
+    fn regressions(&self) -> Vec<hooks::Regression<'_>> {
+        self.baseline_verdicts()
+            .into_iter()
+            .filter(|(_, _, v)| *v == baseline::Verdict::Regression)
+            .filter_map(|(r, base, _)| {
+                let new = self.report.summarize(&r.timings().ok()?);
+                Some(hooks::Regression {
+                    test: r.test,
+                    tool: r.executor,
+                    baseline: self.report.central_value(&base).as_secs_f64(),
+                    new: self.report.central_value(&new).as_secs_f64(),
+                })
+            })
+            .collect()
+    }
+
+    /// Runs the hooks of `hooks` that apply, given the `regressions` and
+    /// `failed_assertions` of the results, logging any that fail
+    fn run_hooks(
+        &self,
+        hooks: &hooks::HooksConfig,
+        shell: Option<&shell::Shell>,
+        results_dir: &Path,
+        regressions: &[hooks::Regression<'_>],
+        failed_assertions: &[String],
+    ) -> Result<()> {
+        let regressed = !regressions.is_empty() || !failed_assertions.is_empty();
+        let events = [
+            ("complete", hooks.on_complete.as_ref()),
+            (
+                "regression",
+                hooks.on_regression.as_ref().filter(|_| regressed),
+            ),
+        ];
+        for (event, cmd) in events {
+            let Some(cmd) = cmd else {
+                continue;
+            };
+            let payload = hooks::Payload {
+                event,
+                results_dir,
+                regressions,
+                failed_assertions,
+                results: self.results_json()?,
+            };
+            if let Err(e) = hooks::run(cmd, shell, &payload) {
+                error!("The {} hook failed: {}", event, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Warns about each cell whose results are not to be relied on: those
+    /// whose runs were cut short, and those whose timings vary by more
+    /// than `max_cv` (as a coefficient of variation)
+    fn warn_about_quality(&self, max_cv: f64) {
+        for r in &self.results {
+            if let Some(reason) = &r.partial {
+                warnings::warn(format!(
+                    "{} with {} has partial results: {}",
+                    r.test, r.executor, reason
+                ));
+            }
+            let Ok(timings) = r.timings() else {
+                continue;
+            };
+            if timings.len() < 2 {
+                continue;
+            }
+            let stats = Statistics::new(&timings);
+            let cv = stats.sample_stddev.as_secs_f64() / stats.mean.as_secs_f64();
+            if cv > max_cv {
+                warnings::warn(format!(
+                    "{} with {} is too noisy to be relied on (CV of {:.1}%, over {:.1}%)",
+                    r.test,
+                    r.executor,
+                    cv * 100.,
+                    max_cv * 100.
+                ));
+            }
+        }
+    }
+
+    /// Each way in which `assertions` are violated: each test on which a
+    /// tool is slower, relative to the baseline, than allowed
+    fn failed_assertions(&self, assertions: &[&ci::Assertion]) -> Result<Vec<String>> {
+        let Some(baseline) = &self.baseline else {
+            return Ok(vec![]);
+        };
+        let mut failed = vec![];
+        for assertion in assertions {
+            let tool = if assertion.tool == "main_tool" {
+                self.main_tool
+                    .ok_or(eyre!("{} needs a main_tool to be selected", assertion))?
+            } else {
+                &assertion.tool
+            };
+            let cells: Vec<&CellResult> =
+                self.results.iter().filter(|r| r.executor == tool).collect();
+            if cells.is_empty() {
+                failed.push(format!("{} has no results to check {}", tool, assertion));
+            }
+            for r in cells {
+                let Some(base) = baseline.timings(&r.id, r.machine_class.as_deref()) else {
+                    continue;
+                };
+                let new = match r.timings() {
+                    Ok(new) if !new.is_empty() => new,
+                    _ => {
+                        failed.push(format!("{} did not run successfully on {}", tool, r.test));
+                        continue;
+                    }
+                };
+                let central = |timings: &[std::time::Duration]| {
+                    self.report
+                        .central_value(&self.report.summarize(timings))
+                        .as_secs_f64()
+                };
+                let ratio = central(&new) / central(base);
+                if ratio > assertion.max_ratio {
+                    failed.push(format!(
+                        "{} on {} is {:.3}x baseline, over {}",
+                        tool, r.test, ratio, assertion
+                    ));
+                }
+            }
+        }
+        Ok(failed)
+    }
+
+    /// Table of the changes in `test` since the baseline. Empty if not
+    /// comparing against one, or if none of its cells are in it.
+    fn format_baseline_comparison(&self, test: &str) -> Result<String> {
+        use std::fmt::Write;
+
+        let Some(baseline) = &self.baseline else {
+            return Ok(String::new());
+        };
+        let verdicts: Vec<_> = self
+            .baseline_verdicts()
+            .into_iter()
+            .filter(|(r, _, _)| r.test == test)
+            .collect();
+        let mut result = String::new();
+        if verdicts.is_empty() {
+            return Ok(result);
+        }
+        let name_length = verdicts
+            .iter()
+            .map(|(r, _, _)| r.executor.len())
+            .max()
+            .unwrap();
+        writeln!(&mut result)?;
+        writeln!(
+            &mut result,
+            "Compared to baseline {}{}:",
+            baseline.name,
+            baseline.formatted_note()
+        )?;
+        writeln!(&mut result)?;
+        writeln!(
+            &mut result,
+            "| {:<nl$} | {:>13} | {:>13} | {:>8} | {:<11} |",
+            "",
+            "Baseline (ms)",
+            "Now (ms)",
+            "Change",
+            "",
+            nl = name_length,
+        )?;
+        writeln!(
+            &mut result,
+            "|:{:-<nl$}-|-{:->13}:|-{:->13}:|-{:->8}:|:{:-<11}-|",
+            "-",
+            "-",
+            "-",
+            "-",
+            "-",
+            nl = name_length,
+        )?;
+        for (r, base, verdict) in verdicts {
+            let base = self.report.central_value(&base).as_secs_f64();
+            let now = self
+                .report
+                .central_value(&self.report.summarize(&r.timings()?))
+                .as_secs_f64();
+            writeln!(
+                &mut result,
+                "| {:<nl$} | {:>13.3} | {:>13.3} | {:>+7.1}% | {:<11} |",
+                r.executor,
+                base * 1000.,
+                now * 1000.,
+                (now / base - 1.) * 100.,
+                verdict.to_string(),
+                nl = name_length,
+            )?;
+        }
+        Ok(result)
+    }
+
+    /// Tools sorted by the geometric mean of their ratios to the main
+    /// tool over `tests`, under `heading`. Empty if no ratios could be
+    /// computed for any of them.
+    fn format_geomeans(&self, heading: &str, tests: &[&str]) -> Result<String> {
+        use std::fmt::Write;
+
         let mut prod_ratios: HashMap<String, f64> = self
             .results
             .iter()
-            .map(|(_test, tool, _timings)| (tool.to_string(), 1.))
+            .filter(|r| tests.contains(&r.test))
+            .map(|r| (r.executor.to_string(), 1.))
             .collect();
         let mut num_products = 0;
 
         for (test, results) in self.results_by_test() {
-            println!();
-            println!("# {}", test);
-            println!();
-            let (ratios, summary) = format_summary(self.main_tool, results)?;
-            print!("{}", summary);
-            println!();
-
-            if let Some(ratios) = ratios {
+            if !tests.contains(&test) {
+                continue;
+            }
+            if let (Some(ratios), _) = format_summary(self.main_tool, &self.report, results)? {
                 for (tool, prod) in prod_ratios.iter_mut() {
                     *prod = if let Some(r) = ratios.get(tool) {
                         *prod * r
@@ -907,6 +6190,7 @@ impl<'a> BenchifyResults<'a> {
             }
         }
 
+        let mut result = String::new();
         if num_products > 0 {
             let mut geomeans: Vec<_> = prod_ratios
                 .into_iter()
@@ -914,18 +6198,160 @@ impl<'a> BenchifyResults<'a> {
                 .collect();
             geomeans.sort_by_key(|nr| (nr.1 * 10000.) as u64);
 
-            println!("Sorted tools by geo mean of ratios:");
+            writeln!(&mut result, "{}", heading)?;
             for (tool, geomean) in geomeans {
                 if geomean.is_finite() {
-                    println!("{:>6.3} {}", geomean, tool);
+                    writeln!(&mut result, "{:>6.3} {}", geomean, tool)?;
                 } else {
-                    println!("-FAIL- {}", tool);
+                    writeln!(&mut result, "-FAIL- {}", tool)?;
                 }
             }
-            println!();
+            writeln!(&mut result)?;
         }
+        Ok(result)
+    }
 
-        Ok(())
+    /// For each tool with builds, the ratios of the central value of each
+    /// build to that of its first build, per test and by geo mean over
+    /// the tests that all of its builds succeeded on
+    fn format_build_impact(&self) -> Result<String> {
+        use std::fmt::Write;
+
+        let mut result = String::new();
+        writeln!(result, "# Impact of build configurations")?;
+        for build_set in self.build_sets {
+            writeln!(result)?;
+            writeln!(result, "## {}", build_set.tool)?;
+            writeln!(result)?;
+            let Some((first, _)) = build_set.builds.first() else {
+                continue;
+            };
+            writeln!(
+                result,
+                "Ratio of the {} of each build to that of {}.",
+                self.report.estimator_name().to_lowercase(),
+                first
+            )?;
+            writeln!(result)?;
+            write!(result, "| Test |")?;
+            for (build, _) in &build_set.builds {
+                write!(result, " {} |", build)?;
+            }
+            writeln!(result)?;
+            write!(result, "|:-----|")?;
+            for _ in &build_set.builds {
+                write!(result, "------:|")?;
+            }
+            writeln!(result)?;
+
+            let mut log_ratios = vec![0.; build_set.builds.len()];
+            let mut num_tests = 0;
+            for (test, results) in self.results_by_test() {
+                let central_values: Vec<Option<f64>> = build_set
+                    .builds
+                    .iter()
+                    .map(|(_, tool)| {
+                        let r = results.iter().find(|r| r.executor == tool)?;
+                        let stats = self.report.summarize(r.timings.as_ref().ok()?);
+                        Some(self.report.central_value(&stats).as_secs_f64())
+                    })
+                    .collect();
+                if central_values.iter().all(Option::is_none) {
+                    continue;
+                }
+                write!(result, "| {} |", test)?;
+                for value in &central_values {
+                    match (value, central_values[0]) {
+                        (Some(value), Some(first)) => write!(result, " {:.3} |", value / first)?,
+                        _ => write!(result, " FAIL |")?,
+                    }
+                }
+                writeln!(result)?;
+                if let Some(values) = central_values.iter().copied().collect::<Option<Vec<_>>>() {
+                    for (log_ratio, value) in log_ratios.iter_mut().zip(&values) {
+                        *log_ratio += (value / values[0]).ln();
+                    }
+                    num_tests += 1;
+                }
+            }
+            if num_tests > 0 {
+                write!(result, "| **Geo mean** |")?;
+                for log_ratio in &log_ratios {
+                    write!(result, " {:.3} |", (log_ratio / num_tests as f64).exp())?;
+                }
+                writeln!(result)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Per-tool aggregates of the throughputs of all tests that declare
+    /// one, for each unit of throughput.
+    fn format_throughputs(&self) -> Result<String> {
+        use std::fmt::Write;
+
+        // unit -> tool -> per-test throughputs (`None` on failure)
+        let mut throughputs: BTreeMap<&str, BTreeMap<&str, Vec<Option<f64>>>> = BTreeMap::new();
+        for r in self.results.iter() {
+            if let Some(throughput) = r.throughput {
+                let rate = r.timings().ok().map(|t| {
+                    throughput.amount
+                        / self
+                            .report
+                            .central_value(&self.report.summarize(&t))
+                            .as_secs_f64()
+                });
+                throughputs
+                    .entry(&throughput.unit)
+                    .or_default()
+                    .entry(r.executor)
+                    .or_default()
+                    .push(rate);
+            }
+        }
+
+        let mut result = String::new();
+        for (unit, tools) in &throughputs {
+            // The harmonic mean is the right way to aggregate rates: for
+            // equal amounts of work, it is the total work divided by the
+            // total time.
+            let mut harmonic_means: Vec<(&str, Option<f64>)> = tools
+                .iter()
+                .map(|(tool, rates)| {
+                    let reciprocal_sum =
+                        rates.iter().map(|r| r.map(|r| 1. / r)).sum::<Option<f64>>();
+                    (*tool, reciprocal_sum.map(|s| rates.len() as f64 / s))
+                })
+                .collect();
+            // Highest first, with those that have none last
+            harmonic_means.sort_by(|a, b| match (b.1, a.1) {
+                (Some(b), Some(a)) => b.total_cmp(&a),
+                (b, a) => b.is_some().cmp(&a.is_some()),
+            });
+
+            writeln!(
+                &mut result,
+                "Sorted tools by harmonic mean of throughput ({}/s):",
+                unit
+            )?;
+            for (tool, harmonic_mean) in harmonic_means {
+                match harmonic_mean {
+                    Some(h) => writeln!(&mut result, "{:>12.3} {}", h, tool)?,
+                    None => writeln!(&mut result, "{:>12} {}", "-FAIL-", tool)?,
+                }
+            }
+            writeln!(&mut result)?;
+        }
+        if !throughputs.is_empty() {
+            writeln!(
+                &mut result,
+                "Note: throughputs are aggregated across tests using the harmonic mean, which \
+                 (for equal amounts of work per test) is the total work divided by the total \
+                 time. Arithmetic or geometric means of rates would overstate it."
+            )?;
+            writeln!(&mut result)?;
+        }
+        Ok(result)
     }
 }
 
@@ -936,6 +6362,12 @@ struct Statistics {
     min: std::time::Duration,
     max: std::time::Duration,
     count: usize,
+    median: std::time::Duration,
+    // Median absolute deviation from the median
+    mad: std::time::Duration,
+    // Number of runs flagged as outliers by `is_outlier`
+    outliers: usize,
+    sorted: Vec<std::time::Duration>,
 }
 
 impl Statistics {
@@ -946,23 +6378,204 @@ impl Statistics {
         let count = data.len();
         assert_ne!(count, 0);
         let mean = std::time::Duration::sum(data.iter()) / (count as u32);
-        let sample_variance = (data
-            .iter()
-            .map(|t| (t.as_secs_f64() - mean.as_secs_f64()).powf(2.))
-            .sum::<f64>())
-            / ((data.len() - 1) as f64).powf(2.);
+        // A single run (say, from an interrupted benchmark) has no
+        // spread to speak of
+        let sample_variance = if count < 2 {
+            0.
+        } else {
+            (data
+                .iter()
+                .map(|t| (t.as_secs_f64() - mean.as_secs_f64()).powf(2.))
+                .sum::<f64>())
+                / ((data.len() - 1) as f64)
+        };
         let sample_stddev = std::time::Duration::from_secs_f64(sample_variance.sqrt());
         let min = *data.iter().min().unwrap();
         let max = *data.iter().max().unwrap();
+        let mut sorted = data.to_vec();
+        sorted.sort();
+        let median = percentile_of_sorted(&sorted, 50.);
+        let mut deviations: Vec<std::time::Duration> =
+            sorted.iter().map(|t| t.abs_diff(median)).collect();
+        deviations.sort();
+        let mad = percentile_of_sorted(&deviations, 50.);
 
-        Statistics {
+        let mut stats = Statistics {
             mean,
             sample_stddev,
             min,
             max,
             count,
+            median,
+            mad,
+            outliers: 0,
+            sorted,
+        };
+        stats.outliers = stats
+            .sorted
+            .iter()
+            .filter(|t| stats.is_outlier(**t))
+            .count();
+        stats
+    }
+
+    /// Whether `t` is an outlier among the runs, going by its modified
+    /// z-score (Iglewicz and Hoaglin): its distance from the median,
+    /// relative to the median absolute deviation. Unlike the usual
+    /// z-score, this is not itself thrown off by the outliers.
+    fn is_outlier(&self, t: std::time::Duration) -> bool {
+        const THRESHOLD: f64 = 3.5;
+        if self.mad.is_zero() {
+            return false;
+        }
+        0.6745 * t.abs_diff(self.median).as_secs_f64() / self.mad.as_secs_f64() > THRESHOLD
+    }
+
+    /// Statistics of the runs other than the outliers. Still counts the
+    /// outliers that were left out.
+    fn without_outliers(&self) -> Statistics {
+        let kept: Vec<std::time::Duration> = self
+            .sorted
+            .iter()
+            .copied()
+            .filter(|t| !self.is_outlier(*t))
+            .collect();
+        Statistics {
+            outliers: self.outliers,
+            ..Statistics::new(&kept)
+        }
+    }
+
+    /// The `p`th percentile (0 to 100) of the runs
+    fn percentile(&self, p: f64) -> std::time::Duration {
+        percentile_of_sorted(&self.sorted, p)
+    }
+
+    /// Number of runs dropped (or clamped) at each end when trimming
+    /// (or winsorizing) a `fraction` of them. Always leaves at least
+    /// one run in the middle.
+    fn trim_count(&self, fraction: f64) -> usize {
+        ((self.count as f64 * fraction).floor() as usize).min((self.count - 1) / 2)
+    }
+
+    /// Mean after dropping the `fraction` fastest and slowest runs
+    fn trimmed_mean(&self, fraction: f64) -> std::time::Duration {
+        let k = self.trim_count(fraction);
+        let kept = &self.sorted[k..self.count - k];
+        kept.iter().sum::<std::time::Duration>() / (kept.len() as u32)
+    }
+
+    /// Mean after clamping the `fraction` fastest and slowest runs to
+    /// the fastest and slowest of the remaining runs
+    fn winsorized_mean(&self, fraction: f64) -> std::time::Duration {
+        let k = self.trim_count(fraction);
+        let (lo, hi) = (self.sorted[k], self.sorted[self.count - 1 - k]);
+        self.sorted
+            .iter()
+            .map(|t| (*t).clamp(lo, hi))
+            .sum::<std::time::Duration>()
+            / (self.count as u32)
+    }
+}
+
+/// The `p`th percentile (0 to 100) of the non-empty `sorted`,
+/// interpolating linearly between the closest ranks
+fn percentile_of_sorted(sorted: &[std::time::Duration], p: f64) -> std::time::Duration {
+    let rank = p / 100. * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    let (lo_value, hi_value) = (sorted[lo].as_secs_f64(), sorted[hi].as_secs_f64());
+    std::time::Duration::from_secs_f64(lo_value + (hi_value - lo_value) * (rank - lo as f64))
+}
+
+/// Compares the results recorded in the `baseline` and `other` results
+/// directories, printing the ratio of means for each (test, tool) pair
+/// they have in common.
+fn compare(baseline: &Path, other: &Path) -> Result<()> {
+    let read_metadata = |dir: &Path| -> Option<metadata::Metadata> {
+        serde_json::from_str(&std::fs::read_to_string(dir.join("metadata.json")).ok()?).ok()
+    };
+    let (baseline_metadata, other_metadata) = (read_metadata(baseline), read_metadata(other));
+    let mut noted = false;
+    for (dir, metadata) in [(baseline, &baseline_metadata), (other, &other_metadata)] {
+        if let Some(note) = metadata.as_ref().and_then(|m| m.note.as_ref()) {
+            println!("Note on {:?}: {}", dir, note);
+            noted = true;
+        }
+        if let Some(metadata) = metadata.as_ref().filter(|m| !m.labels.is_empty()) {
+            println!("Labels of {:?}: {}", dir, metadata.formatted_labels());
+            noted = true;
+        }
+    }
+    if noted {
+        println!();
+    }
+    match (baseline_metadata, other_metadata) {
+        (Some(a), Some(b)) => {
+            let differences = a.differences(&b);
+            if !differences.is_empty() {
+                eprintln!(
+                    "WARNING: The results were recorded in different environments, and may \
+                     not be comparable:\n\t{}\n",
+                    differences.join("\n\t")
+                );
+            }
+        }
+        _ => eprintln!(
+            "WARNING: Could not find metadata for both results. Unable to compare environments.\n"
+        ),
+    }
+
+    // Cells are matched up by their IDs, so that they can be compared
+    // even if they have been renamed in between.
+    // Runs from different classes of machines are never mixed.
+    type Key = (String, Option<String>);
+    type Means = BTreeMap<Key, (String, String, std::time::Duration)>;
+    let read_means = |dir: &Path| -> Result<Means> {
+        let mut timings: BTreeMap<Key, (String, String, Vec<std::time::Duration>)> =
+            BTreeMap::new();
+        for r in read_data_csv(&dir.join("data.csv"))? {
+            if !r.sample.warmup {
+                let executor = match &r.machine_class {
+                    Some(class) => format!("{} [{}]", r.executor, class),
+                    None => r.executor.clone(),
+                };
+                timings
+                    .entry((r.cell_id(), r.machine_class))
+                    .or_insert((r.test, executor, vec![]))
+                    .2
+                    .push(r.sample.timing);
+            }
+        }
+        Ok(timings
+            .into_iter()
+            .map(|(k, (test, executor, t))| (k, (test, executor, Statistics::new(&t).mean)))
+            .collect())
+    };
+    let baseline_means = read_means(baseline)?;
+    let other_means = read_means(other)?;
+
+    println!(
+        "| {:<20} | {:<20} | {:>14} | {:>14} | {:>8} |",
+        "Test", "Executor", "Baseline (ms)", "Other (ms)", "Ratio"
+    );
+    println!(
+        "|:{:-<20}-|:{:-<20}-|-{:->14}:|-{:->14}:|-{:->8}:|",
+        "-", "-", "-", "-", "-"
+    );
+    for (id, (_, _, base)) in &baseline_means {
+        if let Some((test, executor, other)) = other_means.get(id) {
+            println!(
+                "| {:<20} | {:<20} | {:>14.3} | {:>14.3} | {:>8.3} |",
+                test,
+                executor,
+                base.as_secs_f64() * 1000.,
+                other.as_secs_f64() * 1000.,
+                other.as_secs_f64() / base.as_secs_f64()
+            );
         }
     }
+
+    Ok(())
 }
 
 fn main() -> Result<()> {
@@ -979,6 +6592,8 @@ fn main() -> Result<()> {
 
     let opts = CmdLineOpts::parse();
 
+    interrupt::install_handler()?;
+
     if let Some(m) = opts.max_parallelism {
         wait_for_free_cpu::restrict_free_cpus_to(m);
     }
@@ -990,15 +6605,307 @@ fn main() -> Result<()> {
         } else {
             std::fs::write(opts.benchify_toml, include_str!("template.toml"))?;
         }
+    } else if let Some(Command::Compare { baseline, other }) = &opts.command {
+        compare(baseline, other)?;
     } else {
-        let config: BenchifyConfig = toml::from_str(
-            &std::fs::read_to_string(&opts.benchify_toml)
-                .or(Err(eyre!("Could not read {:?}", &opts.benchify_toml)))?,
-        )?;
+        let mut config = BenchifyConfig::load(&opts.benchify_toml)
+            .unwrap_or_else(|e| ci::CiConfig::default().exit(ci::Outcome::ConfigError, e));
+        let ci = config.ci();
+        if let Some(Command::Git { revisions }) = &opts.command {
+            if let Err(e) = config.benchmark_revisions(revisions) {
+                ci.exit(ci::Outcome::ConfigError, e);
+            }
+        }
+        let selected = config
+            .select_groups(&opts.group, &opts.skip_group)
+            .and_then(|()| config.select_tests(&opts.test))
+            .and_then(|()| config.select_tools(&opts.tool, &opts.skip_tool));
+        if let Err(e) = selected {
+            ci.exit(ci::Outcome::ConfigError, e);
+        }
+        // Loaded up front, so that a missing baseline is reported before
+        // spending any time on benchmarking
+        let baseline = opts.load_baseline.as_ref().map(|name| {
+            baseline::Baseline::load(&config.results_dir(), name)
+                .unwrap_or_else(|e| ci.exit(ci::Outcome::ConfigError, e))
+        });
+        // As is anything wrong with the imported results
+        let imported: Vec<hyperfine::Imported> = opts
+            .import_hyperfine
+            .iter()
+            .map(hyperfine::import)
+            .collect::<Result<Vec<_>>>()
+            .unwrap_or_else(|e| ci.exit(ci::Outcome::ConfigError, e))
+            .into_iter()
+            .flatten()
+            .collect();
+        let imported = config
+            .hyperfine_cells(&imported)
+            .unwrap_or_else(|e| ci.exit(ci::Outcome::ConfigError, e));
+        let assertions: Vec<&ci::Assertion> =
+            ci.assertions().iter().chain(&opts.assertions).collect();
+        let benchmarking = matches!(opts.command, None | Some(Command::Git { .. }));
+        if benchmarking && !assertions.is_empty() && baseline.is_none() {
+            ci.exit(
+                ci::Outcome::ConfigError,
+                eyre!("Assertions need a baseline to compare against, with --load-baseline"),
+            );
+        }
+        if let Some(assertion) = assertions
+            .iter()
+            .find(|a| a.tool == "main_tool" && config.main_tool.is_none())
+        {
+            ci.exit(
+                ci::Outcome::ConfigError,
+                eyre!("{} needs a main_tool to be selected", assertion),
+            );
+        }
 
-        let results = config.execute(opts.use_known_csv_data, opts.store_preparation_time)?;
-        results.save_to_directory(&config.results_dir())?;
-        results.display_summary()?;
+        match &opts.command {
+            Some(Command::Eval { test, template }) => {
+                config.eval(test, template)?;
+            }
+            Some(Command::Check { json }) => {
+                let diagnostics = config.confirm_config_sanity();
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+                } else if diagnostics.is_empty() {
+                    println!("No problems found.");
+                } else {
+                    print!("{}", format_diagnostics(&diagnostics)?);
+                }
+                if !diagnostics.is_empty() {
+                    std::process::exit(ci.exit_code(diagnostics_outcome(&diagnostics)));
+                }
+            }
+            Some(Command::Worker) => {
+                let plan: plan::Plan = serde_json::from_reader(std::io::stdin())?;
+                let results = config.execute(&plan, false, false, false, false);
+                remove_scratch_dir();
+                let mut results = results?;
+                if let Some(class) = &opts.machine_class {
+                    results.label_machine_class(class);
+                }
+                results.write_data_csv(std::io::stdout().lock())?;
+            }
+            Some(Command::Clean {
+                older_than,
+                dry_run,
+            }) => {
+                clean::clean(
+                    &config.results_dir(),
+                    &config.retention.clone().unwrap_or_default(),
+                    older_than.as_deref(),
+                    *dry_run,
+                )?;
+            }
+            Some(Command::ImportSamples { file, test, tool }) => {
+                let imported =
+                    config.import_samples(file, test, tool, opts.machine_class.as_deref())?;
+                println!(
+                    "Imported {} samples of {} on {}. Run with --use-known-csv-data to report \
+                     them along with the rest.",
+                    imported, tool, test
+                );
+            }
+            Some(Command::History { test, tool, label }) => {
+                let entries = history::entries(
+                    &config.results_dir(),
+                    test.as_deref(),
+                    tool.as_deref(),
+                    label,
+                )?;
+                print!("{}", history::format(&entries));
+            }
+            Some(Command::List { only }) => config.list(*only),
+            Some(Command::Compare { .. }) => unreachable!(),
+            None | Some(Command::Git { .. }) if opts.dry_run => {
+                // Planning needs every tool to have a runner for every
+                // test, and so on
+                config.exit_if_insane();
+                let plan = match &opts.from_plan {
+                    Some(path) => plan::Plan::read(path)?,
+                    None => config.plan(),
+                };
+                config.dry_run(&plan)?;
+            }
+            None | Some(Command::Git { .. }) if opts.emit_plan.is_some() => {
+                config.exit_if_insane();
+                let plan = config.plan();
+                let plan = if opts.redact { plan.redacted() } else { plan };
+                plan.write(opts.emit_plan.as_ref().unwrap())?;
+            }
+            None | Some(Command::Git { .. }) => {
+                config.exit_if_insane();
+                let plan = match &opts.from_plan {
+                    Some(path) => plan::Plan::read(path)?,
+                    None => config.plan(),
+                };
+                let system = opts.check_system.then(|| {
+                    let checks = system::check();
+                    for warning in &checks.warnings {
+                        warnings::warn(warning.clone());
+                    }
+                    checks
+                });
+                let results = if opts.worker.is_empty() {
+                    config.execute(
+                        &plan,
+                        opts.use_known_csv_data,
+                        opts.store_preparation_time,
+                        opts.fail_fast,
+                        opts.resume,
+                    )
+                } else {
+                    config.execute_distributed(&plan, &opts.worker)
+                };
+                remove_scratch_dir();
+                let mut results = match results {
+                    Ok(results) => results,
+                    Err(e) if interrupt::interrupted() => ci.exit(ci::Outcome::Interrupted, e),
+                    Err(e) => return Err(e),
+                };
+                // Compared against as if they had been run alongside
+                results.results.extend(imported.iter().cloned());
+                if let Some(class) = &opts.machine_class {
+                    results.label_machine_class(class);
+                }
+                results.metadata.note = opts.note.clone();
+                results.metadata.labels = opts.label.iter().cloned().collect();
+                results.metadata.system = system;
+                if results.report.is_randomized() {
+                    let seed = opts
+                        .seed
+                        .or(results.report.seed)
+                        .unwrap_or_else(randomize::random);
+                    results.report.seed = Some(seed);
+                    results.metadata.seed = Some(seed);
+                }
+                if let Some(baseline) = baseline {
+                    if let Some(differences) = baseline
+                        .metadata
+                        .as_ref()
+                        .map(|m| m.differences(&results.metadata))
+                        .filter(|d| !d.is_empty())
+                    {
+                        warnings::warn(format!(
+                            "The baseline was recorded in a different environment, and may \
+                             not be comparable:\n\t{}",
+                            differences.join("\n\t")
+                        ));
+                    }
+                    results.baseline = Some(baseline);
+                }
+                // Only once compared against the baseline, as everything
+                // from here on is exported
+                if opts.redact {
+                    results.redact();
+                }
+                results.save_to_directory(&config.results_dir())?;
+                // Failed (test, tool) pairs are left to be retried with
+                // --resume
+                if results.aborted.is_none()
+                    && !interrupt::interrupted()
+                    && results.results.iter().all(|r| r.samples.is_ok())
+                {
+                    config.remove_checkpoint();
+                }
+                if config.history() {
+                    results.append_to_history(&config.results_dir())?;
+                }
+                if config.plots() {
+                    // The results are saved either way
+                    if let Err(e) = results.save_plots(&config.results_dir()) {
+                        error!("Could not render plots: {}", e);
+                    }
+                }
+                if config.criterion() {
+                    // Named as by Criterion's own `--save-baseline`
+                    let baseline = opts.save_baseline.as_deref().unwrap_or("new");
+                    results.save_criterion(&config.results_dir().join("criterion"), baseline)?;
+                }
+                if let Some(prometheus) = config.export.as_ref().and_then(|e| e.prometheus.as_ref())
+                {
+                    // The results are saved either way
+                    if let Err(e) = prometheus.push(&results.results_json()?) {
+                        error!("{}", e);
+                    }
+                }
+                if let Some(path) = &opts.export_hyperfine {
+                    hyperfine::export(path, &results.results_json()?)?;
+                }
+                if let Some(path) = &opts.export_github_comment {
+                    std::fs::write(path, results.github_comment()?)?;
+                }
+                if let Some(name) = &opts.save_baseline {
+                    baseline::save(&results, &config.results_dir(), name)?;
+                }
+                if opts.json {
+                    results.display_summary(&mut std::io::stderr())?;
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&results.results_json()?)?
+                    );
+                } else {
+                    results.display_summary(&mut std::io::stdout())?;
+                }
+                // The last (test, tool) pair may have been cut short
+                // without the suite being marked as aborted
+                if interrupt::interrupted() {
+                    ci.exit(ci::Outcome::Interrupted, eyre!("Interrupted"));
+                }
+                let regressions = results.regressions();
+                let failed_assertions = results.failed_assertions(&assertions)?;
+                if let Some(hooks) = &config.hooks {
+                    results.run_hooks(
+                        hooks,
+                        config.shell.as_ref(),
+                        &config.results_dir(),
+                        &regressions,
+                        &failed_assertions,
+                    )?;
+                }
+                if let Some(reason) = &results.aborted {
+                    ci.exit(ci::Outcome::BenchmarkFailure, eyre!("{}", reason));
+                }
+                let failures = results
+                    .results
+                    .iter()
+                    .filter(|r| r.samples.is_err())
+                    .count();
+                if failures > 0 && ci.fails_on(ci::Outcome::BenchmarkFailure) {
+                    ci.exit(
+                        ci::Outcome::BenchmarkFailure,
+                        eyre!("{} (test, tool) pairs failed", failures),
+                    );
+                }
+                if !regressions.is_empty() && ci.fails_on(ci::Outcome::Regression) {
+                    ci.exit(
+                        ci::Outcome::Regression,
+                        eyre!("{} regressions since the baseline", regressions.len()),
+                    );
+                }
+                if !failed_assertions.is_empty() {
+                    for failed in &failed_assertions {
+                        eprintln!("Assertion failed: {}", failed);
+                    }
+                    ci.exit(
+                        ci::Outcome::Regression,
+                        eyre!("{} assertions failed", failed_assertions.len()),
+                    );
+                }
+                if opts.strict {
+                    results.warn_about_quality(ci.max_cv());
+                    let warnings = warnings::count();
+                    if warnings > 0 {
+                        ci.exit(
+                            ci::Outcome::Warnings,
+                            eyre!("{} warnings, which are errors with --strict", warnings),
+                        );
+                    }
+                }
+            }
+        }
     }
 
     Ok(())
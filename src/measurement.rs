@@ -0,0 +1,330 @@
+//! Benchmarking of a (test, tool) pair one run at a time: its warmup
+//! runs, then the runs that estimate how many measured runs it needs,
+//! and then the rest of those, so that the runs of several pairs can be
+//! interleaved (see [`crate::scheduler::RunOrder`]).
+
+use crate::ui::{Phase, UiConfig};
+use crate::{interrupt, plan, BenchifyConfig, Sample, Test, Tool};
+use color_eyre::eyre::{eyre, Result};
+use indicatif::ProgressBar;
+use std::time::{Duration, Instant};
+
+/// What a [`Measurement`] is doing
+enum Stage {
+    /// Making warmup runs, since `started`
+    Warmup {
+        started: Instant,
+    },
+    /// Making the first measured runs, which estimate how long each run
+    /// takes
+    InitialEstimates,
+    /// Making measured runs, up to `runs` of them in all
+    Benchmarking {
+        runs: usize,
+    },
+    Done,
+}
+
+pub struct Measurement<'a> {
+    config: &'a BenchifyConfig,
+    ui: &'a UiConfig,
+    test: &'a Test,
+    tool: &'a Tool,
+    policy: &'a plan::IterationPolicy,
+    stage: Stage,
+    /// Progress of the current stage. Hidden if progress is shown by
+    /// the caller, on `status`.
+    pb: ProgressBar,
+    /// Message of `pb`
+    message: String,
+    /// Bar that the caller shows progress on, if it does
+    status: Option<ProgressBar>,
+    logged_phase: Option<Phase<'a>>,
+    warmup_samples: Vec<Sample>,
+    samples: Vec<Sample>,
+    /// How the pair ended: its mean time, or how it failed
+    summary: Option<String>,
+}
+
+impl<'a> Measurement<'a> {
+    /// Starts benchmarking `tool` on `test`, as per `policy`. Progress
+    /// is shown on bars of its own, unless the caller shows it on
+    /// `status`, in which case that is only used to show waiting for a
+    /// quiet system.
+    pub fn new(
+        config: &'a BenchifyConfig,
+        ui: &'a UiConfig,
+        test: &'a Test,
+        tool: &'a Tool,
+        policy: &'a plan::IterationPolicy,
+        status: Option<ProgressBar>,
+    ) -> Self {
+        let mut measurement = Measurement {
+            config,
+            ui,
+            test,
+            tool,
+            policy,
+            stage: Stage::Done,
+            pb: ProgressBar::hidden(),
+            message: String::new(),
+            status,
+            logged_phase: None,
+            warmup_samples: vec![],
+            samples: vec![],
+            summary: None,
+        };
+        if policy.warmup > 0 || policy.warmup_time.is_some() {
+            measurement.start_warmup();
+        } else {
+            measurement.start_initial_estimates();
+        }
+        measurement
+    }
+
+    pub fn label(&self) -> String {
+        format!("[{}] [{}]", self.test.name, self.tool.name)
+    }
+
+    /// How the pair ended, once it has
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    fn num_initial_estimates(&self) -> usize {
+        self.policy.max_runs.min(2) as usize
+    }
+
+    /// Moves on to `stage`, with a bar of length `len` showing `message`,
+    /// and logging `phase`
+    fn start_stage(&mut self, stage: Stage, len: u64, message: &str, phase: String) {
+        self.pb = if self.status.is_some() {
+            let pb = ProgressBar::hidden();
+            pb.set_length(len);
+            pb
+        } else {
+            self.ui.bar(len)
+        };
+        self.message = format!("{} {}", self.label(), message);
+        self.pb.set_message(&self.message);
+        self.logged_phase = Some(self.ui.phase(phase));
+        self.stage = stage;
+    }
+
+    fn finish_phase(&mut self) {
+        if let Some(phase) = self.logged_phase.take() {
+            phase.finish();
+        }
+    }
+
+    fn start_warmup(&mut self) {
+        let warmup_runs = self.policy.warmup as usize;
+        let phase = match self.policy.warmup_time {
+            Some(t) if warmup_runs == 0 => format!("{} Warmup runs for {:.1}s", self.label(), t),
+            Some(t) => format!(
+                "{} At least {} warmup runs, for {:.1}s",
+                self.label(),
+                warmup_runs,
+                t
+            ),
+            None => format!("{} {} warmup runs", self.label(), warmup_runs),
+        };
+        let stage = Stage::Warmup {
+            started: Instant::now(),
+        };
+        self.start_stage(stage, warmup_runs.max(1) as u64, "Warmup runs", phase);
+    }
+
+    fn start_initial_estimates(&mut self) {
+        let runs = self.num_initial_estimates();
+        let phase = format!("{} {} initial estimate runs", self.label(), runs);
+        self.start_stage(
+            Stage::InitialEstimates,
+            runs as u64,
+            "Initial estimates",
+            phase,
+        );
+    }
+
+    fn start_benchmarking(&mut self) {
+        let policy = self.policy;
+        let mean_estimated_time_per_iter_secs = self
+            .samples
+            .iter()
+            .map(|s| s.timing.as_secs_f32())
+            .sum::<f32>()
+            / self.num_initial_estimates() as f32;
+        // Run until stable enough, if so configured, rather than for the
+        // target time
+        let runs = match policy.stop_when {
+            Some(_) => policy.max_runs,
+            None => policy.max_runs.min(
+                policy
+                    .min_runs
+                    .max((policy.target_time as f32 / mean_estimated_time_per_iter_secs) as _),
+            ),
+        };
+        let phase = match policy.stop_when {
+            Some(stop_when) => format!(
+                "{} Up to {} benchmarking runs, until {}",
+                self.label(),
+                runs,
+                stop_when
+            ),
+            None => format!("{} {} benchmarking runs", self.label(), runs),
+        };
+        let stage = Stage::Benchmarking {
+            runs: runs as usize,
+        };
+        self.start_stage(stage, runs as u64, "Benchmarking", phase);
+    }
+
+    /// Whether the timings are stable enough to stop at, as per
+    /// `stop_when`
+    fn stopped(&self) -> bool {
+        self.policy.stop_when.is_some_and(|stop_when| {
+            self.samples.len() >= self.policy.min_runs as usize && stop_when.is_met(&self.samples)
+        })
+    }
+
+    /// Shows the pair's mean time, once it is done
+    fn finish_benchmarking(&mut self) {
+        self.pb.set_style(self.ui.message_style());
+        if self.samples.is_empty() {
+            self.pb.finish_and_clear();
+            return;
+        }
+        let mean_timing =
+            self.samples.iter().map(|s| s.timing).sum::<Duration>() / (self.samples.len() as u32);
+        let message = format!(
+            "{}\tMean {:?} in {} runs",
+            self.label(),
+            mean_timing,
+            self.samples.len()
+        );
+        self.ui.log(&message);
+        self.pb.finish_with_message(&message);
+        self.summary = Some(message);
+    }
+
+    /// Waits for the machine to quiet down before a measured run, if so
+    /// configured
+    fn wait_for_quiet(&self) {
+        let Some(quiet) = &self.config.wait_for_quiet else {
+            return;
+        };
+        let pb = self.status.as_ref().unwrap_or(&self.pb);
+        if quiet.wait(self.ui, pb, &self.label()) && self.status.is_none() {
+            self.pb.set_message(&self.message);
+        }
+    }
+
+    /// Runs the tool once, as a run of `phase`, producing `None` if
+    /// benchify has been interrupted, in which case no further runs
+    /// should be made
+    fn run_once(&mut self, phase: &str, warmup: bool) -> Result<Option<Sample>> {
+        if interrupt::interrupted() {
+            return Ok(None);
+        }
+        let iteration = self.warmup_samples.len() + self.samples.len();
+        match self
+            .tool
+            .run_within_timeout(self.config, self.test, iteration, warmup)
+        {
+            Ok(sample) => Ok(Some(Sample {
+                iteration,
+                warmup,
+                ..sample
+            })),
+            // The run was most likely killed by the same Ctrl-C
+            Err(_) if interrupt::interrupted() => Ok(None),
+            Err(e) => {
+                let message = format!("{} Failure during {}: {}", self.label(), phase, e);
+                self.ui.log(&message);
+                self.pb.set_style(self.ui.message_style());
+                self.pb.finish_with_message(&message);
+                self.summary = Some(message);
+                self.stage = Stage::Done;
+                Err(e)
+            }
+        }
+    }
+
+    /// Makes the next run of the pair, returning whether there was one
+    /// left to make. A run that fails ends the pair, with its error.
+    pub fn step(&mut self) -> Result<bool> {
+        loop {
+            match self.stage {
+                Stage::Warmup { started } => {
+                    let i = self.warmup_samples.len();
+                    let warmup_time = self.policy.warmup_time.map(Duration::from_secs_f64);
+                    if i < self.policy.warmup as usize
+                        || warmup_time.is_some_and(|t| started.elapsed() < t)
+                    {
+                        // How many runs the time allows for is only known
+                        // once they are made
+                        if i as u64 >= self.pb.length() {
+                            self.pb.set_length(i as u64 + 1);
+                        }
+                        self.pb.inc(1);
+                        let Some(sample) = self.run_once("warmup", true)? else {
+                            self.pb.finish_and_clear();
+                            self.stage = Stage::Done;
+                            return Err(eyre!("Interrupted during warmup"));
+                        };
+                        self.warmup_samples.push(sample);
+                        return Ok(true);
+                    }
+                    self.pb.finish_and_clear();
+                    self.finish_phase();
+                    self.start_initial_estimates();
+                }
+                Stage::InitialEstimates => {
+                    if self.samples.len() < self.num_initial_estimates() {
+                        self.pb.inc(1);
+                        self.wait_for_quiet();
+                        let Some(sample) = self.run_once("initial estimates", false)? else {
+                            self.pb.finish_and_clear();
+                            self.stage = Stage::Done;
+                            return Ok(false);
+                        };
+                        self.samples.push(sample);
+                        return Ok(true);
+                    }
+                    self.pb.finish_and_clear();
+                    self.finish_phase();
+                    self.start_benchmarking();
+                }
+                Stage::Benchmarking { runs } => {
+                    if self.samples.len() < runs && !self.stopped() {
+                        self.pb.set_position(self.samples.len() as u64);
+                        let phase = format!("benchmarking run#{}", self.samples.len());
+                        self.wait_for_quiet();
+                        if let Some(sample) = self.run_once(&phase, false)? {
+                            self.samples.push(sample);
+                            return Ok(true);
+                        }
+                    } else {
+                        self.finish_phase();
+                    }
+                    self.finish_benchmarking();
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return Ok(false),
+            }
+        }
+    }
+
+    /// The samples of every run that was made, once no more are left to
+    /// make
+    pub fn into_samples(self) -> Result<Vec<Sample>> {
+        if self.samples.is_empty() {
+            return Err(eyre!("Interrupted before any runs completed"));
+        }
+        Ok(self
+            .warmup_samples
+            .into_iter()
+            .chain(self.samples)
+            .collect())
+    }
+}
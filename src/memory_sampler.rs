@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// How often the sampler polls the process
+const INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Approximates the peak resident set size of a running process by
+/// polling it in a background thread. Short-lived processes (or spikes
+/// in between polls) may be missed entirely.
+pub struct MemorySampler {
+    stop: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<Option<u64>>,
+}
+
+impl MemorySampler {
+    pub fn start(pid: u32) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                let mut peak = None;
+                while !stop.load(Ordering::SeqCst) {
+                    if let Some(rss) = rss_kib(pid) {
+                        peak = peak.max(Some(rss));
+                    }
+                    std::thread::sleep(INTERVAL);
+                }
+                peak
+            })
+        };
+        MemorySampler { stop, handle }
+    }
+
+    /// Stops sampling, producing the peak RSS (in KiB) seen, if any
+    pub fn finish(self) -> Option<u64> {
+        self.stop.store(true, Ordering::SeqCst);
+        self.handle.join().unwrap()
+    }
+}
+
+/// Peak RSS of `pid` so far (or failing that, its current RSS), in KiB
+#[cfg(target_os = "linux")]
+fn rss_kib(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix(name))
+            .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+    };
+    field("VmHWM:").or_else(|| field("VmRSS:"))
+}
+
+/// Current RSS of `pid`, in KiB
+#[cfg(target_os = "macos")]
+fn rss_kib(pid: u32) -> Option<u64> {
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of_val(&info) as libc::c_int;
+    // SAFETY: `info` is valid for writes of `size` bytes
+    let res = unsafe {
+        libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDTASKINFO,
+            0,
+            &mut info as *mut libc::proc_taskinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    (res == size).then(|| info.pti_resident_size / 1024)
+}
+
+/// Current RSS of `pid`, in KiB
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn rss_kib(pid: u32) -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Information about the environment a set of results was recorded
+/// in. All of it is best-effort: anything that cannot be determined
+/// is simply left out.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Metadata {
+    pub benchify_version: String,
+    /// Seconds since the Unix epoch at which the run started
+    pub timestamp: u64,
+    pub hostname: Option<String>,
+    pub cpu: Option<String>,
+    pub kernel: Option<String>,
+    /// Label for the class of machine, as given by the user
+    pub machine_class: Option<String>,
+    /// Context for the results, as given by the user with `--note`
+    pub note: Option<String>,
+    /// Labels of the results, as given by the user with `--label`
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Hash of the benchify.toml used for the run
+    pub config_hash: Option<String>,
+    /// Whether the environment of each run was randomized
+    #[serde(default)]
+    pub randomize_environment: bool,
+    /// Seed that randomized statistics were computed with, if any were
+    pub seed: Option<u64>,
+    /// First line of output of each tool's existence confirmation, by
+    /// tool name
+    pub tool_versions: BTreeMap<String, String>,
+    /// CPUs that runs were pinned to, by runner (as `tool (tag)`), for
+    /// the runners that were pinned
+    #[serde(default)]
+    pub cpu_affinity: BTreeMap<String, Vec<usize>>,
+    /// Whether jobs run in parallel were found to interfere with each
+    /// other, if any were run in parallel
+    pub interference: Option<bool>,
+    /// Settings of the machine that affect reproducibility, if checked
+    /// with `--check-system`
+    #[serde(default)]
+    pub system: Option<crate::system::SystemChecks>,
+    /// Whether the hostname, usernames and absolute paths were scrubbed
+    /// out, with `--redact`
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+impl Metadata {
+    /// Collects metadata about the current machine. Tool versions and
+    /// the config hash are left for the caller to fill in.
+    pub fn collect() -> Self {
+        Metadata {
+            benchify_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            hostname: hostname(),
+            cpu: cpu(),
+            kernel: command_output("uname", &["-srm"]),
+            machine_class: None,
+            note: None,
+            labels: BTreeMap::new(),
+            config_hash: None,
+            randomize_environment: false,
+            seed: None,
+            tool_versions: BTreeMap::new(),
+            cpu_affinity: BTreeMap::new(),
+            interference: None,
+            system: None,
+            redacted: false,
+        }
+    }
+
+    /// A copy with the hostname removed, and any usernames or
+    /// absolute paths scrubbed from the tool versions
+    pub fn redacted(&self) -> Self {
+        Metadata {
+            hostname: None,
+            tool_versions: self
+                .tool_versions
+                .iter()
+                .map(|(tool, version)| (tool.clone(), crate::redact::redact(version)))
+                .collect(),
+            redacted: true,
+            ..self.clone()
+        }
+    }
+
+    /// The labels, as `key=value, ...`
+    pub fn formatted_labels(&self) -> String {
+        self.labels
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Human-readable descriptions of the ways in which `self` and
+    /// `other` differ, in any manner that could make comparing their
+    /// results meaningless.
+    pub fn differences(&self, other: &Metadata) -> Vec<String> {
+        fn show(x: &Option<String>) -> &str {
+            x.as_deref().unwrap_or("unknown")
+        }
+
+        // Compared as both would have been recorded with `--redact`, if
+        // either was
+        if self.redacted != other.redacted {
+            return self.redacted().differences(&other.redacted());
+        }
+
+        let mut res = vec![];
+        if self.benchify_version != other.benchify_version {
+            res.push(format!(
+                "benchify version: {} vs {}",
+                self.benchify_version, other.benchify_version
+            ));
+        }
+        if self.randomize_environment != other.randomize_environment {
+            res.push(format!(
+                "environment randomization: {} vs {}",
+                self.randomize_environment, other.randomize_environment
+            ));
+        }
+        for (what, a, b) in [
+            ("hostname", &self.hostname, &other.hostname),
+            ("cpu", &self.cpu, &other.cpu),
+            ("kernel", &self.kernel, &other.kernel),
+            ("machine class", &self.machine_class, &other.machine_class),
+            ("config hash", &self.config_hash, &other.config_hash),
+        ] {
+            if a != b {
+                res.push(format!("{}: {} vs {}", what, show(a), show(b)));
+            }
+        }
+        let tools: std::collections::BTreeSet<&String> = self
+            .tool_versions
+            .keys()
+            .chain(other.tool_versions.keys())
+            .collect();
+        for tool in tools {
+            let a = self.tool_versions.get(tool).cloned();
+            let b = other.tool_versions.get(tool).cloned();
+            if a != b {
+                res.push(format!("version of {}: {} vs {}", tool, show(&a), show(&b)));
+            }
+        }
+        if self.cpu_affinity != other.cpu_affinity {
+            res.push("pinning of runs to CPUs (see cpu_affinity in metadata.json)".to_string());
+        }
+        if let (Some(a), Some(b)) = (&self.system, &other.system) {
+            if a != b {
+                res.push("system settings (see system in metadata.json)".to_string());
+            }
+        }
+        res
+    }
+}
+
+/// A stable (across platforms and versions of benchify) hash of
+/// `data`, for detecting changes. Not cryptographically secure.
+pub fn stable_hash(data: &[u8]) -> String {
+    // 64-bit FNV-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// The first non-empty line of output (stdout, or failing that,
+/// stderr) of running `program` with `args`
+pub fn command_output<S: AsRef<std::ffi::OsStr>>(program: &str, args: &[S]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    [&output.stdout, &output.stderr].iter().find_map(|out| {
+        String::from_utf8_lossy(out)
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .map(str::to_string)
+    })
+}
+
+pub fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|h| h.trim().to_string())
+        .or_else(|| command_output::<&str>("hostname", &[]))
+}
+
+fn cpu() -> Option<String> {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo
+                .lines()
+                .find(|l| l.starts_with("model name"))
+                .and_then(|l| l.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .or_else(|| command_output("sysctl", &["-n", "machdep.cpu.brand_string"]))
+}
@@ -0,0 +1,92 @@
+//! Hardware performance counters of runs (e.g., instructions, cycles,
+//! branch and cache misses), collected by running them under `perf
+//! stat`. Unlike timings, counts such as instructions barely vary from
+//! run to run, even on noisy shared machines.
+
+use color_eyre::eyre::Result;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of runs counted so far, to name their output files uniquely
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether `perf stat` can be run, and is allowed to count events (see
+/// `kernel.perf_event_paranoid`)
+pub fn available() -> bool {
+    std::process::Command::new("perf")
+        .args(["stat", "-x", ",", "-e", "instructions", "--", "true"])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Name of the metric for the count of `event`
+fn metric_name(event: &str) -> String {
+    format!("{} (perf)", event)
+}
+
+/// The counters of a single run
+pub struct PerfStat {
+    output: PathBuf,
+}
+
+impl PerfStat {
+    /// Wraps `command` to run under `perf stat`, counting `events` in it
+    /// and everything it starts. Only the program, arguments, working
+    /// directory and environment of `command` are carried over.
+    pub fn wrap(
+        command: &std::process::Command,
+        events: &[String],
+    ) -> std::io::Result<(std::process::Command, Self)> {
+        std::fs::create_dir_all(&*crate::SCRATCH_DIR)?;
+        let output = crate::SCRATCH_DIR.join(format!(
+            "perf-stat-{}.csv",
+            RUNS.fetch_add(1, Ordering::SeqCst)
+        ));
+        let mut perf = std::process::Command::new("perf");
+        perf.args(["stat", "--no-big-num", "-x", ","])
+            .arg("-o")
+            .arg(&output)
+            .arg("-e")
+            .arg(events.join(","))
+            .arg("--")
+            .arg(command.get_program())
+            .args(command.get_args());
+        if let Some(dir) = command.get_current_dir() {
+            perf.current_dir(dir);
+        }
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => perf.env(key, value),
+                None => perf.env_remove(key),
+            };
+        }
+        Ok((perf, PerfStat { output }))
+    }
+
+    /// The counts, as metrics named after their events, once the run is
+    /// over. Events that could not be counted are left out.
+    pub fn finish(self) -> Result<Vec<(String, f64)>> {
+        let output = std::fs::read_to_string(&self.output)?;
+        let _ = std::fs::remove_file(&self.output);
+        Ok(parse(&output))
+    }
+}
+
+/// Parses the output of `perf stat -x ,`, which has a line per event
+/// starting with its count (or `<not counted>`), unit and name
+fn parse(output: &str) -> Vec<(String, f64)> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let count = fields.next()?.parse().ok()?;
+            let _unit = fields.next()?;
+            let event = fields.next()?;
+            Some((metric_name(event), count))
+        })
+        .collect()
+}
@@ -0,0 +1,71 @@
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How many times a (test, tool) cell is run
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct IterationPolicy {
+    pub warmup: u32,
+    /// Seconds to keep making warmup runs for, after the `warmup` ones
+    #[serde(default)]
+    pub warmup_time: Option<f64>,
+    pub min_runs: u32,
+    pub max_runs: u32,
+    /// Total time, in seconds, that the measured runs should take.
+    /// Bounded by `min_runs` and `max_runs`.
+    pub target_time: f64,
+    /// When to stop early, before `max_runs`, instead of after
+    /// `target_time`
+    #[serde(default)]
+    pub stop_when: Option<crate::StopCondition>,
+}
+
+/// A single (test, tool) cell of a [`Plan`]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PlannedCell {
+    pub id: String,
+    pub test: String,
+    pub tool: String,
+    pub tag: String,
+    /// The command being benchmarked, fully interpolated
+    pub command: String,
+    pub policy: IterationPolicy,
+}
+
+/// A fully-resolved execution plan: every cell that is to be run, in
+/// order, along with how it is to be run.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Plan {
+    pub benchify_version: String,
+    /// Hash of the benchify.toml the plan was made from
+    pub config_hash: Option<String>,
+    pub cells: Vec<PlannedCell>,
+}
+
+impl Plan {
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).or(Err(eyre!("Could not read {:?}", path)))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// A copy with any hostnames, usernames or absolute paths scrubbed
+    /// from the commands
+    pub fn redacted(&self) -> Self {
+        Plan {
+            cells: self
+                .cells
+                .iter()
+                .map(|cell| PlannedCell {
+                    command: crate::redact::redact(&cell.command),
+                    ..cell.clone()
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+//! Box plots and bar charts of the results, rendered as both SVG and PNG
+//! into the results directory with `plots = true`, for reports that need
+//! images rather than `report.html`. Only available when built with the
+//! `plots` feature.
+
+use crate::{CellJson, ResultsJson};
+use color_eyre::eyre::Result;
+use std::path::{Path, PathBuf};
+
+/// A chart of each tool on a row of its own
+pub enum Chart<'a> {
+    /// The distribution of the measured runs of each tool, in ms
+    Boxes(Vec<(&'a str, Vec<f64>)>),
+    /// A single value for each tool, in `unit`
+    Bars(Vec<(&'a str, f64)>, &'a str),
+}
+
+#[cfg(feature = "plots")]
+mod imp {
+    use super::Chart;
+    use color_eyre::eyre::{eyre, Result};
+    use plotters::coord::Shift;
+    use plotters::prelude::*;
+    use std::path::Path;
+
+    /// Width of the images, and height of each row of a chart, in pixels
+    const WIDTH: u32 = 800;
+    const ROW_HEIGHT: u32 = 40;
+
+    fn draw<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        title: &str,
+        chart: &Chart,
+    ) -> Result<(), String> {
+        let e = |e: DrawingAreaErrorKind<DB::ErrorType>| e.to_string();
+        root.fill(&WHITE).map_err(e)?;
+        // Listed from the top down, in the order of the rows
+        let tools: Vec<&str> = match chart {
+            Chart::Boxes(rows) => rows.iter().rev().map(|(tool, _)| *tool).collect(),
+            Chart::Bars(rows, _) => rows.iter().rev().map(|(tool, _)| *tool).collect(),
+        };
+        let max = match chart {
+            Chart::Boxes(rows) => rows.iter().flat_map(|(_, t)| t).fold(0., |a, b| b.max(a)),
+            Chart::Bars(rows, _) => rows.iter().map(|(_, v)| *v).fold(0., f64::max),
+        };
+        let mut builder = ChartBuilder::on(root);
+        builder
+            .caption(title, ("sans-serif", 20))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(200);
+        let mut context = builder
+            .build_cartesian_2d(0f32..(max * 1.1) as f32, tools[..].into_segmented())
+            .map_err(e)?;
+        context
+            .configure_mesh()
+            .disable_y_mesh()
+            .y_label_formatter(&|tool| match tool {
+                SegmentValue::Exact(tool) | SegmentValue::CenterOf(tool) => tool.to_string(),
+                SegmentValue::Last => String::new(),
+            })
+            .x_desc(match chart {
+                Chart::Boxes(..) => "Time (ms)",
+                Chart::Bars(_, unit) => unit,
+            })
+            .draw()
+            .map_err(e)?;
+        match chart {
+            Chart::Boxes(rows) => {
+                context
+                    .draw_series(rows.iter().map(|(tool, timings)| {
+                        Boxplot::new_horizontal(
+                            SegmentValue::CenterOf(tool),
+                            &Quartiles::new(timings),
+                        )
+                        .width(ROW_HEIGHT / 2)
+                        .style(BLUE)
+                    }))
+                    .map_err(e)?;
+            }
+            Chart::Bars(rows, _) => {
+                context
+                    .draw_series(
+                        Histogram::horizontal(&context)
+                            .style(BLUE.mix(0.6).filled())
+                            .margin(ROW_HEIGHT / 4)
+                            .data(rows.iter().map(|(tool, value)| (tool, *value as f32))),
+                    )
+                    .map_err(e)?;
+            }
+        }
+        root.present().map_err(e)
+    }
+
+    /// Renders `chart` to `<stem>.svg` and `<stem>.png`
+    pub fn save(stem: &Path, title: &str, chart: &Chart) -> Result<()> {
+        let rows = match chart {
+            Chart::Boxes(rows) => rows.len(),
+            Chart::Bars(rows, _) => rows.len(),
+        };
+        let size = (WIDTH, 100 + ROW_HEIGHT * rows as u32);
+        let svg = stem.with_extension("svg");
+        draw(
+            &SVGBackend::new(&svg, size).into_drawing_area(),
+            title,
+            chart,
+        )
+        .map_err(|e| eyre!("Could not plot {:?}: {}", svg, e))?;
+        let png = stem.with_extension("png");
+        draw(
+            &BitMapBackend::new(&png, size).into_drawing_area(),
+            title,
+            chart,
+        )
+        .map_err(|e| eyre!("Could not plot {:?}: {}", png, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "plots"))]
+mod imp {
+    use super::Chart;
+    use color_eyre::eyre::{eyre, Result};
+    use std::path::Path;
+
+    pub fn save(_stem: &Path, _title: &str, _chart: &Chart) -> Result<()> {
+        Err(eyre!("benchify was built without the `plots` feature"))
+    }
+}
+
+/// Whether plots can be rendered at all
+pub fn available() -> bool {
+    cfg!(feature = "plots")
+}
+
+/// Renders, for each test, a box plot of the runs of each tool
+/// (`boxplot`) and a bar chart of their central values (`bars`) into the
+/// test's directory, and a bar chart of the geo mean of each tool's
+/// ratios to the baseline (`comparison`) into `dir`
+pub fn save(dir: &Path, json: &ResultsJson) -> Result<()> {
+    let mut tests: Vec<(&str, PathBuf, Vec<&CellJson>)> = vec![];
+    for c in &json.cells {
+        match tests.iter_mut().find(|(test, _, _)| *test == c.test) {
+            Some((_, _, cells)) => cells.push(c),
+            None => {
+                let cell_dir = crate::cell_dir(&c.tag, &c.id);
+                let test_dir = cell_dir.parent().unwrap_or(Path::new("")).to_path_buf();
+                tests.push((&c.test, dir.join(test_dir), vec![c]));
+            }
+        }
+    }
+    for (test, test_dir, cells) in &tests {
+        let distributions: Vec<(&str, Vec<f64>)> = cells
+            .iter()
+            .map(|c| {
+                let timings = c
+                    .runs
+                    .iter()
+                    .filter(|r| !r.warmup)
+                    .map(|r| r.timing * 1000.)
+                    .collect();
+                (c.tool.as_str(), timings)
+            })
+            .filter(|(_, timings): &(&str, Vec<f64>)| !timings.is_empty())
+            .collect();
+        if distributions.is_empty() {
+            continue;
+        }
+        std::fs::create_dir_all(test_dir)?;
+        imp::save(
+            &test_dir.join("boxplot"),
+            test,
+            &Chart::Boxes(distributions),
+        )?;
+        let central_values = cells
+            .iter()
+            .filter_map(|c| {
+                Some((
+                    c.tool.as_str(),
+                    c.statistics.as_ref()?.central_value * 1000.,
+                ))
+            })
+            .collect();
+        imp::save(
+            &test_dir.join("bars"),
+            test,
+            &Chart::Bars(central_values, "Time (ms)"),
+        )?;
+    }
+    let cells: Vec<&CellJson> = json.cells.iter().collect();
+    let geomeans: Vec<(&str, f64)> = crate::html_report::geomeans(&cells)
+        .into_iter()
+        .filter_map(|(tool, g)| Some((tool, g?)))
+        .collect();
+    if !geomeans.is_empty() {
+        imp::save(
+            &dir.join("comparison"),
+            &format!(
+                "Geo mean of ratios to {}",
+                json.baseline.unwrap_or("the baseline")
+            ),
+            &Chart::Bars(geomeans, "Ratio"),
+        )?;
+    }
+    Ok(())
+}
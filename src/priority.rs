@@ -0,0 +1,188 @@
+//! CPU and I/O priorities of the processes benchify starts, as configured
+//! in `[priority]`: measured runs can be favored on a busy machine, and
+//! preparations and cleanups kept out of the way of whatever else is
+//! running (e.g., the runs of other pairs, with `parallel_benchmarks`).
+
+use serde::{Deserialize, Serialize};
+
+/// Scheduling class of an I/O priority, as in ionice(1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IoClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+/// An I/O priority: a class, and a level within it (0, the highest, to
+/// 7), written as e.g. `best-effort:2` or `idle`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct IoPriority {
+    class: IoClass,
+    level: u8,
+}
+
+impl TryFrom<String> for IoPriority {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let (class, level) = match s.split_once(':') {
+            Some((class, level)) => (class, Some(level)),
+            None => (s.as_str(), None),
+        };
+        let class = match class {
+            "realtime" => IoClass::Realtime,
+            "best-effort" => IoClass::BestEffort,
+            "idle" => IoClass::Idle,
+            _ => {
+                return Err(format!(
+                    "unknown I/O class {:?}, expected realtime, best-effort or idle",
+                    class
+                ))
+            }
+        };
+        let level = match level {
+            None => 4,
+            Some(_) if class == IoClass::Idle => {
+                return Err("the idle I/O class has no levels".to_string())
+            }
+            Some(level) => level
+                .parse()
+                .ok()
+                .filter(|level| *level <= 7)
+                .ok_or(format!(
+                    "I/O priority level {:?} is not within 0 to 7",
+                    level
+                ))?,
+        };
+        Ok(IoPriority { class, level })
+    }
+}
+
+impl From<IoPriority> for String {
+    fn from(p: IoPriority) -> String {
+        match p.class {
+            IoClass::Realtime => format!("realtime:{}", p.level),
+            IoClass::BestEffort => format!("best-effort:{}", p.level),
+            IoClass::Idle => "idle".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PriorityConfig {
+    /// Niceness of measured runs
+    nice: Option<i32>,
+    /// I/O priority of measured runs
+    ionice: Option<IoPriority>,
+    /// Niceness of preparations and cleanups
+    prepare_nice: Option<i32>,
+    /// I/O priority of preparations and cleanups
+    prepare_ionice: Option<IoPriority>,
+}
+
+/// The priorities that a process is started with
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Priority {
+    nice: Option<i32>,
+    ionice: Option<IoPriority>,
+}
+
+impl PriorityConfig {
+    /// Of measured runs
+    pub fn run(&self) -> Priority {
+        Priority {
+            nice: self.nice,
+            ionice: self.ionice,
+        }
+    }
+
+    /// Of preparations and cleanups
+    pub fn prepare(&self) -> Priority {
+        Priority {
+            nice: self.prepare_nice,
+            ionice: self.prepare_ionice,
+        }
+    }
+}
+
+impl Priority {
+    pub fn is_default(&self) -> bool {
+        self.nice.is_none() && self.ionice.is_none()
+    }
+
+    /// Makes `command` start with these priorities. Niceness is only
+    /// supported on Unix, and I/O priorities only on Linux; elsewhere,
+    /// they are not applied (see `problem`).
+    pub fn apply(&self, command: &mut std::process::Command) {
+        if self.is_default() {
+            return;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let priority = *self;
+            // SAFETY: `setpriority` and `syscall` are async-signal-safe,
+            // and the closure does not allocate.
+            unsafe {
+                command.pre_exec(move || priority.set());
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    /// Sets these priorities for the current process
+    #[cfg(unix)]
+    fn set(&self) -> std::io::Result<()> {
+        if let Some(nice) = self.nice {
+            // SAFETY: plain syscall, on the current process
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(ionice) = self.ionice {
+            const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+            const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+            let class = match ionice.class {
+                IoClass::Realtime => 1,
+                IoClass::BestEffort => 2,
+                IoClass::Idle => 3,
+            };
+            let ioprio = (class << IOPRIO_CLASS_SHIFT) | ionice.level as libc::c_int;
+            // SAFETY: plain syscall, on the current process
+            if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Why processes cannot be started with these priorities, if they
+    /// cannot: the platform does not support them, or benchify is not
+    /// allowed to set them (e.g., raising priorities usually needs root)
+    pub fn problem(&self) -> Option<String> {
+        if self.is_default() {
+            return None;
+        }
+        if self.nice.is_some() && !cfg!(unix) {
+            return Some("niceness is only supported on Unix".to_string());
+        }
+        if self.ionice.is_some() && !cfg!(target_os = "linux") {
+            return Some("I/O priorities are only supported on Linux".to_string());
+        }
+        let mut command = crate::shell::command(None, "exit 0");
+        self.apply(&mut command);
+        match command.status() {
+            Ok(_) => None,
+            Err(e) => Some(format!(
+                "could not be set ({}); raising priorities (with a negative niceness, or the \
+                 realtime I/O class) usually needs root, or CAP_SYS_NICE",
+                e
+            )),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Pushing the statistics of each (test, tool) pair to a Prometheus
+//! Pushgateway once the results are saved, as configured in
+//! `[export.prometheus]`, for graphing performance over the long term
+//! (e.g., in Grafana). Pushed with curl, in the text exposition format.
+
+use crate::ResultsJson;
+use color_eyre::eyre::{eyre, Result};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PrometheusConfig {
+    /// URL of the Pushgateway, e.g. `http://localhost:9091`
+    pub url: String,
+    /// Job that the metrics are grouped under ("benchify", if not set)
+    pub job: Option<String>,
+    /// Host that the metrics are labeled with (this machine's hostname,
+    /// if not set)
+    pub host: Option<String>,
+}
+
+/// Statistics that are pushed, each as a gauge in seconds, by the name
+/// of their metric
+const STATISTICS: [(&str, &str); 4] = [
+    ("mean", "Mean time of the measured runs"),
+    (
+        "stddev",
+        "Sample standard deviation of the times of the measured runs",
+    ),
+    ("min", "Shortest time of the measured runs"),
+    ("max", "Longest time of the measured runs"),
+];
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+/// Percent-encodes `s` for a segment of a URL path
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The statistics of every successful cell of `json`, in the text
+/// exposition format
+fn exposition(json: &ResultsJson) -> Result<String> {
+    let mut text = String::new();
+    for (statistic, help) in STATISTICS {
+        let name = format!("{}_{}_seconds", crate::PROGRAM_NAME, statistic);
+        writeln!(text, "# HELP {} {}", name, help)?;
+        writeln!(text, "# TYPE {} gauge", name)?;
+        for c in &json.cells {
+            let Some(s) = &c.statistics else {
+                continue;
+            };
+            let value = match statistic {
+                "mean" => s.mean,
+                "stddev" => s.sample_stddev,
+                "min" => s.min,
+                _ => s.max,
+            };
+            let mut labels = format!(r#"test="{}",tool="{}""#, escape(&c.test), escape(&c.tool));
+            if let Some(class) = &c.machine_class {
+                write!(labels, r#",machine_class="{}""#, escape(class))?;
+            }
+            writeln!(text, "{}{{{}}} {}", name, labels, value)?;
+        }
+    }
+    Ok(text)
+}
+
+impl PrometheusConfig {
+    fn host(&self) -> String {
+        self.host
+            .clone()
+            .or_else(crate::metadata::hostname)
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// URL that the metrics are pushed to, grouped by the job and host,
+    /// so that each push replaces the previous one from the same host
+    fn push_url(&self) -> String {
+        format!(
+            "{}/metrics/job/{}/host/{}",
+            self.url.trim_end_matches('/'),
+            encode(self.job.as_deref().unwrap_or(crate::PROGRAM_NAME)),
+            encode(&self.host())
+        )
+    }
+
+    /// Why the metrics could not be pushed, if they could not
+    pub fn problem(&self) -> Option<String> {
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Some(format!("{:?} is not an HTTP(S) URL", self.url));
+        }
+        let available = Command::new("curl")
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output()
+            .is_ok_and(|output| output.status.success());
+        (!available).then(|| "curl could not be run".to_string())
+    }
+
+    /// Pushes the statistics of `json` to the Pushgateway, replacing
+    /// those previously pushed from this host
+    pub fn push(&self, json: &ResultsJson) -> Result<()> {
+        let url = self.push_url();
+        trace!("Pushing metrics to {}", url);
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "-X", "PUT"])
+            .args(["--data-binary", "@-", &url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| eyre!("Could not run curl: {}", e))?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(exposition(json)?.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(eyre!(
+                "Could not push metrics to {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Waiting for the machine to quiet down before each measured run, as
+//! configured in `[wait_for_quiet]`, so that runs do not start while
+//! something else (e.g., a build, or another user's job) is busy on it.
+//! Unlike [`crate::wait_for_free_cpu`], this sees every process on the
+//! machine, not just benchify's own.
+
+use crate::ui::UiConfig;
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How often the system is checked again while waiting
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How long CPU usage is sampled over
+const CPU_USAGE_SAMPLE: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Whether waiting has already run out, and been warned about, so that
+/// a machine that stays busy is only warned about once
+static GAVE_UP: AtomicBool = AtomicBool::new(false);
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct QuietConfig {
+    /// Highest 1-minute load average at which a measured run may start
+    pub max_load: Option<f64>,
+    /// Highest percentage of the machine's CPU time in use (sampled
+    /// over a fraction of a second) at which a measured run may start
+    pub max_cpu_usage: Option<f64>,
+    /// Longest to wait (e.g., "5m"), after which the run starts anyway,
+    /// with a warning. Forever, if not set.
+    pub max_wait: Option<String>,
+}
+
+#[cfg(unix)]
+fn load_average() -> Option<f64> {
+    let mut load = [0.];
+    // SAFETY: `load` has room for the one sample asked for.
+    let samples = unsafe { libc::getloadavg(load.as_mut_ptr(), 1) };
+    (samples == 1).then_some(load[0])
+}
+
+#[cfg(not(unix))]
+fn load_average() -> Option<f64> {
+    None
+}
+
+/// Total and idle time of all CPUs so far, in ticks
+fn cpu_times() -> Option<(u64, u64)> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    let times: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .filter_map(|t| t.parse().ok())
+        .collect();
+    // Waiting on I/O counts as idle
+    let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+    Some((times.iter().sum(), idle))
+}
+
+/// Percentage of the machine's CPU time that is in use, right now
+fn cpu_usage() -> Option<f64> {
+    let (total_before, idle_before) = cpu_times()?;
+    std::thread::sleep(CPU_USAGE_SAMPLE);
+    let (total, idle) = cpu_times()?;
+    let total = total.checked_sub(total_before).filter(|t| *t > 0)?;
+    let idle = idle.saturating_sub(idle_before);
+    Some(100. * (total - idle.min(total)) as f64 / total as f64)
+}
+
+impl QuietConfig {
+    fn max_wait(&self) -> Option<std::time::Duration> {
+        self.max_wait
+            .as_deref()
+            .and_then(|t| crate::parse_duration(t).ok())
+    }
+
+    /// How the system is busier than allowed, if it is
+    fn busy(&self) -> Option<String> {
+        if let Some((max, load)) = self.max_load.zip(load_average()) {
+            if load > max {
+                return Some(format!("load average {:.2}, above {}", load, max));
+            }
+        }
+        if let Some((max, usage)) = self.max_cpu_usage.zip(cpu_usage()) {
+            if usage > max {
+                return Some(format!("CPU usage {:.0}%, above {}%", usage, max));
+            }
+        }
+        None
+    }
+
+    /// Why the system could not be waited on as configured, if it
+    /// could not
+    pub fn problem(&self) -> Option<String> {
+        if self.max_load.is_none() && self.max_cpu_usage.is_none() {
+            return Some("neither max_load nor max_cpu_usage is set".to_string());
+        }
+        if self.max_load.is_some() && load_average().is_none() {
+            return Some("the load average could not be read".to_string());
+        }
+        if self.max_cpu_usage.is_some() && cpu_times().is_none() {
+            return Some("CPU usage could not be read from /proc/stat (Linux only)".to_string());
+        }
+        if let Some(Err(e)) = self.max_wait.as_deref().map(crate::parse_duration) {
+            return Some(format!("max_wait: {}", e));
+        }
+        None
+    }
+
+    /// Waits until the system is quiet, or for `max_wait`, showing why
+    /// on `pb` (under `label`) while it does. Returns whether it had to
+    /// wait at all, in which case `pb` shows a message of its own.
+    pub fn wait(&self, ui: &UiConfig, pb: &ProgressBar, label: &str) -> bool {
+        let Some(mut busy) = self.busy() else {
+            return false;
+        };
+        let logged_phase = ui.phase(format!("{} Waiting for a quiet system ({})", label, busy));
+        let started = std::time::Instant::now();
+        loop {
+            pb.set_message(&format!("{} Waiting for a quiet system: {}", label, busy));
+            pb.tick();
+            if crate::interrupt::interrupted() {
+                return true;
+            }
+            if self.max_wait().is_some_and(|t| started.elapsed() >= t) {
+                if !GAVE_UP.swap(true, Ordering::SeqCst) {
+                    crate::warnings::warn(format!(
+                        "{} The system did not quiet down within {} ({}). Running anyway, \
+                         here and wherever else it does not.",
+                        label,
+                        self.max_wait.as_deref().unwrap_or_default(),
+                        busy
+                    ));
+                }
+                logged_phase.finish();
+                return true;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+            match self.busy() {
+                Some(now) => busy = now,
+                None => break,
+            }
+        }
+        logged_phase.finish();
+        true
+    }
+}
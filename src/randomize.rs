@@ -0,0 +1,27 @@
+//! Randomization of harmless aspects of the environment of each run, so
+//! that measurements average over memory-layout effects rather than
+//! being biased by one particular layout (as advocated by Mytkowicz et
+//! al.'s "Producing Wrong Data Without Doing Anything Obviously Wrong!"
+//! and Curtsinger and Berger's "Stabilizer").
+
+/// Environment variable padded out to a random length. Since the
+/// environment is copied onto the initial stack of a process, this
+/// shifts the stack (and everything on it) by a random offset.
+pub const PADDING_VAR: &str = "BENCHIFY_PADDING";
+
+/// Padding is up to this many bytes, so that the stack offset covers a
+/// full page
+const MAX_PADDING: u64 = 4096;
+
+/// A fresh random number, from the OS-seeded keys of a `RandomState`
+pub fn random() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+/// Padding of a random length, to be set as [`PADDING_VAR`]
+pub fn padding() -> String {
+    "x".repeat((random() % MAX_PADDING) as usize)
+}
@@ -0,0 +1,48 @@
+/// Strips details of the environment out of `s`, so that it can be
+/// shared publicly: the hostname and username are replaced by
+/// placeholders, and absolute paths are cut down to their final
+/// component.
+pub fn redact(s: &str) -> String {
+    let mut res = redact_paths(s);
+    for (value, placeholder) in [
+        (crate::metadata::hostname(), "<host>"),
+        (std::env::var("USER").ok(), "<user>"),
+    ] {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            res = res.replace(&value, placeholder);
+        }
+    }
+    res
+}
+
+/// Replaces every absolute path in `s` by `<path>/` followed by its
+/// final component
+fn redact_paths(s: &str) -> String {
+    let is_boundary = |c: char| c.is_whitespace() || "\"'=:,()[]{}".contains(c);
+    let mut res = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('/') {
+        let at_token_start = rest[..start].chars().next_back().is_none_or(is_boundary);
+        if !at_token_start {
+            res.push_str(&rest[..=start]);
+            rest = &rest[start + 1..];
+            continue;
+        }
+        res.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(is_boundary)
+            .map_or(rest.len(), |e| start + e);
+        let path = &rest[start..end];
+        match path.trim_end_matches('/').rsplit_once('/') {
+            Some((dir, file)) if !dir.is_empty() => {
+                res.push_str("<path>/");
+                res.push_str(file);
+            }
+            // Paths directly in the root (e.g., `/tmp`) are left be
+            _ => res.push_str(path),
+        }
+        rest = &rest[end..];
+    }
+    res.push_str(rest);
+    res
+}
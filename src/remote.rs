@@ -0,0 +1,140 @@
+//! Running the processes of a tool (its runs, and its `prepare` and
+//! `cleanup` commands) on a remote host over SSH, while benchify drives
+//! and records everything locally. Unlike `--worker`, the host needs
+//! nothing but a shell: test files are staged onto it before a tool's
+//! runs on a test, and runs are timed from this side of the connection.
+
+use crate::distributed::shell_quote;
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// SSH destination, e.g. `user@host`
+    pub host: String,
+    /// Directory on the host that commands are run in, and that test
+    /// files are staged into
+    pub dir: String,
+}
+
+impl RemoteConfig {
+    /// An SSH command to the host, that shares a single connection with
+    /// every other one, so that each run only pays for a round trip
+    /// rather than for a handshake
+    fn ssh(&self) -> Command {
+        // The control socket needs its directory to exist
+        let _ = std::fs::create_dir_all(&*crate::SCRATCH_DIR);
+        let mut ssh = Command::new("ssh");
+        ssh.args(["-o", "BatchMode=yes"])
+            .args(["-o", "ControlMaster=auto"])
+            .arg("-o")
+            .arg(format!(
+                "ControlPath={}",
+                crate::SCRATCH_DIR.join("ssh-%C").display()
+            ))
+            .args(["-o", "ControlPersist=30"])
+            .arg(&self.host);
+        ssh
+    }
+
+    /// Where `file` is staged on the host: at the same path if it is
+    /// absolute, and relative to `dir` otherwise
+    fn staged_path(&self, file: &Path) -> String {
+        if file.is_absolute() {
+            file.to_string_lossy().into_owned()
+        } else {
+            format!(
+                "{}/{}",
+                self.dir.trim_end_matches('/'),
+                file.to_string_lossy()
+            )
+        }
+    }
+
+    /// `command`, run instead on the host, in `dir` (or the command's
+    /// working directory, relative to it), with its environment
+    /// variables carried over. Its stdin and output go over the
+    /// connection.
+    pub fn wrap(&self, command: &Command) -> Command {
+        let dir = match command.get_current_dir() {
+            Some(dir) => self.staged_path(dir),
+            None => self.dir.clone(),
+        };
+        // `{TMPDIR}` is interpolated to the local scratch directory, so
+        // it also has to exist on the host
+        let mut script = format!(
+            "mkdir -p {} && cd {} && exec env",
+            shell_quote(&crate::SCRATCH_DIR.to_string_lossy()),
+            shell_quote(&dir)
+        );
+        for (key, value) in command.get_envs() {
+            if let Some(value) = value {
+                script += &format!(
+                    " {}={}",
+                    key.to_string_lossy(),
+                    shell_quote(&value.to_string_lossy())
+                );
+            }
+        }
+        for arg in std::iter::once(command.get_program()).chain(command.get_args()) {
+            script += " ";
+            script += &shell_quote(&arg.to_string_lossy());
+        }
+        let mut ssh = self.ssh();
+        ssh.arg(script);
+        ssh
+    }
+
+    /// Copies `file` onto the host, where it is found by commands that
+    /// refer to it by the same path
+    pub fn stage(&self, file: &Path) -> Result<()> {
+        let dest = self.staged_path(file);
+        let parent = match dest.rsplit_once('/') {
+            Some((parent, _)) if !parent.is_empty() => parent,
+            _ => ".",
+        };
+        let output = self
+            .ssh()
+            .arg(format!(
+                "mkdir -p {} && cat > {}",
+                shell_quote(parent),
+                shell_quote(&dest)
+            ))
+            .stdin(std::fs::File::open(file)?)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()?;
+        if !output.status.success() {
+            return Err(eyre!(
+                "Could not stage {:?} onto {}: {}",
+                file,
+                self.host,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Why commands cannot be run on the host, if they cannot
+    pub fn problem(&self) -> Option<String> {
+        let output = self
+            .ssh()
+            .arg(format!("mkdir -p {}", shell_quote(&self.dir)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output();
+        match output {
+            Err(e) => Some(format!("could not run ssh: {}", e)),
+            Ok(output) if !output.status.success() => Some(format!(
+                "could not run commands in {}: {}",
+                self.dir,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Ok(_) => None,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+/// Name of the metric for the maximum resident set size
+pub const MAX_RSS_METRIC: &str = "Max RSS (KiB)";
+/// Name of the metric for the CPU time spent in user mode
+pub const USER_TIME_METRIC: &str = "User Time (s)";
+/// Name of the metric for the CPU time spent in the kernel
+pub const SYSTEM_TIME_METRIC: &str = "System Time (s)";
+
+/// Resources used by a finished process (and any of its descendants that
+/// it waited for), as reported by the OS
+#[derive(Debug, Clone, Default)]
+pub struct ResourceUsage {
+    pub minor_page_faults: u64,
+    pub major_page_faults: u64,
+    pub voluntary_context_switches: u64,
+    pub involuntary_context_switches: u64,
+    /// Maximum resident set size, in KiB. Linux counts the memory of
+    /// the process as it was just before `exec` too, i.e., that of
+    /// benchify, so this is never below benchify's own footprint.
+    pub max_rss: u64,
+    /// CPU time spent in user mode, summed over all threads
+    pub user_time: std::time::Duration,
+    /// CPU time spent in the kernel on behalf of the process, summed
+    /// over all threads
+    pub system_time: std::time::Duration,
+    /// Peak physical memory footprint, in KiB, which (unlike the maximum
+    /// RSS) counts compressed and swapped out memory, as Activity Monitor
+    /// does. Only reported on macOS.
+    pub peak_footprint: Option<u64>,
+    /// Instructions retired and CPU cycles, counted by the kernel without
+    /// any access to performance counters. Only reported on macOS, and
+    /// not on all of its hardware.
+    pub instructions: Option<u64>,
+    pub cycles: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// The usage as metrics, by name, including the page faults and
+    /// context switches if `faults_and_switches`
+    pub fn metrics(&self, faults_and_switches: bool) -> Vec<(&'static str, f64)> {
+        let optional = [
+            ("Peak Footprint (KiB)", self.peak_footprint),
+            ("Instructions (rusage)", self.instructions),
+            ("Cycles (rusage)", self.cycles),
+        ];
+        let mut metrics = vec![
+            (MAX_RSS_METRIC, self.max_rss as f64),
+            (USER_TIME_METRIC, self.user_time.as_secs_f64()),
+            (SYSTEM_TIME_METRIC, self.system_time.as_secs_f64()),
+        ];
+        if faults_and_switches {
+            metrics.extend([
+                ("Minor Page Faults", self.minor_page_faults as f64),
+                ("Major Page Faults", self.major_page_faults as f64),
+                (
+                    "Voluntary Context Switches",
+                    self.voluntary_context_switches as f64,
+                ),
+                (
+                    "Involuntary Context Switches",
+                    self.involuntary_context_switches as f64,
+                ),
+            ]);
+        }
+        metrics.extend(
+            optional
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value? as f64))),
+        );
+        metrics
+    }
+}
+
+/// Waits for `child` to exit, also producing the resources it used,
+/// where the platform supports it
+#[cfg(unix)]
+pub fn wait(
+    child: &mut std::process::Child,
+) -> std::io::Result<(std::process::ExitStatus, Option<ResourceUsage>)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    // What `wait4` does not report can only be asked for while the
+    // process is a zombie, before it is reaped
+    #[cfg(target_os = "macos")]
+    let info = {
+        wait_for_exit(child)?;
+        rusage_info(child.id())
+    };
+    let mut status = 0;
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: `status` and `usage` are valid for writes, and
+        // `child` has not been reaped (`Child` only reaps it on `wait`,
+        // which is not called on it).
+        let res = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut usage) };
+        if res != -1 {
+            break;
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+    #[allow(unused_mut)]
+    let mut resource_usage = ResourceUsage {
+        minor_page_faults: usage.ru_minflt as u64,
+        major_page_faults: usage.ru_majflt as u64,
+        voluntary_context_switches: usage.ru_nvcsw as u64,
+        involuntary_context_switches: usage.ru_nivcsw as u64,
+        // In bytes on macOS, and KiB everywhere else
+        max_rss: if cfg!(target_os = "macos") {
+            usage.ru_maxrss as u64 / 1024
+        } else {
+            usage.ru_maxrss as u64
+        },
+        user_time: duration_of(usage.ru_utime),
+        system_time: duration_of(usage.ru_stime),
+        peak_footprint: None,
+        instructions: None,
+        cycles: None,
+    };
+    #[cfg(target_os = "macos")]
+    if let Some(info) = info {
+        resource_usage.peak_footprint = Some(info.ri_lifetime_max_phys_footprint / 1024);
+        // Zero where the hardware does not count them (e.g., in VMs)
+        resource_usage.instructions = Some(info.ri_instructions).filter(|n| *n > 0);
+        resource_usage.cycles = Some(info.ri_cycles).filter(|n| *n > 0);
+    }
+    Ok((
+        std::process::ExitStatus::from_raw(status),
+        Some(resource_usage),
+    ))
+}
+
+/// The resource usage of the process `pid`, which may be a zombie
+#[cfg(target_os = "macos")]
+fn rusage_info(pid: u32) -> Option<libc::rusage_info_v4> {
+    let mut info: libc::rusage_info_v4 = unsafe { std::mem::zeroed() };
+    // SAFETY: `info` is valid for writes, and of the flavor asked for
+    let res = unsafe {
+        libc::proc_pid_rusage(
+            pid as libc::c_int,
+            libc::RUSAGE_INFO_V4,
+            &mut info as *mut libc::rusage_info_v4 as *mut libc::rusage_info_t,
+        )
+    };
+    (res == 0).then_some(info)
+}
+
+#[cfg(unix)]
+fn duration_of(t: libc::timeval) -> std::time::Duration {
+    std::time::Duration::from_secs(t.tv_sec as u64)
+        + std::time::Duration::from_micros(t.tv_usec as u64)
+}
+
+#[cfg(not(unix))]
+pub fn wait(
+    child: &mut std::process::Child,
+) -> std::io::Result<(std::process::ExitStatus, Option<ResourceUsage>)> {
+    Ok((child.wait()?, None))
+}
+
+/// Waits for `child` to exit, without reaping it, so that it can still
+/// be signalled safely (its PID cannot have been reused)
+#[cfg(unix)]
+pub fn wait_for_exit(child: &mut std::process::Child) -> std::io::Result<()> {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: `info` is valid for writes
+        let res = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                child.id() as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if res != -1 {
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Waits for `child` to exit. Elsewhere, waiting does not release its
+/// process ID, which stays reserved until the handle to it is closed.
+#[cfg(not(unix))]
+pub fn wait_for_exit(child: &mut std::process::Child) -> std::io::Result<()> {
+    child.wait().map(|_| ())
+}
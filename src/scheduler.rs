@@ -0,0 +1,77 @@
+//! Order in which the (test, tool) pairs of a suite are benchmarked, when
+//! several are benchmarked at once (see `parallel_benchmarks`): that of
+//! the plan, except that no two pairs of the same tool are run at the
+//! same time, as they may share its daemon, or the outputs of its
+//! preparation. Pairs that are run round-robin (see [`RunOrder`]) are
+//! scheduled together, as a single unit of work.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// How long to wait before checking again for a unit whose tools are
+/// free
+const INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Order in which the runs of the tools on a test are made
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOrder {
+    /// Every run of a tool, and then every run of the next
+    #[default]
+    Sequential,
+    /// A run of each tool in turn (A, B, C, A, B, C, ...), so that slow
+    /// drift of the machine (e.g., heating up, or background jobs)
+    /// affects them all alike
+    RoundRobin,
+}
+
+pub struct Scheduler {
+    /// Tools of each unit of work, in the order of the plan
+    tools: Vec<Vec<String>>,
+    started: Vec<bool>,
+    /// Tools that a unit is running for
+    busy: HashSet<String>,
+}
+
+impl Scheduler {
+    pub fn new<'a>(units: impl Iterator<Item = Vec<&'a str>>) -> Self {
+        let tools: Vec<Vec<String>> = units
+            .map(|tools| tools.into_iter().map(str::to_string).collect())
+            .collect();
+        Scheduler {
+            started: vec![false; tools.len()],
+            tools,
+            busy: HashSet::new(),
+        }
+    }
+
+    /// Claims the first unit that has not been started yet, and whose
+    /// tools are all free, waiting for one to be. `None` once every unit
+    /// has been claimed.
+    pub fn next(scheduler: &Mutex<Self>) -> Option<usize> {
+        loop {
+            let mut s = scheduler.lock().unwrap();
+            if s.started.iter().all(|started| *started) {
+                return None;
+            }
+            let free = (0..s.tools.len())
+                .find(|&i| !s.started[i] && s.tools[i].iter().all(|t| !s.busy.contains(t)));
+            if let Some(i) = free {
+                s.started[i] = true;
+                let tools = s.tools[i].clone();
+                s.busy.extend(tools);
+                return Some(i);
+            }
+            drop(s);
+            std::thread::sleep(INTERVAL);
+        }
+    }
+
+    /// Marks the unit `i` as done, freeing its tools
+    pub fn finish(&mut self, i: usize) {
+        for tool in &self.tools[i] {
+            self.busy.remove(tool);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! The shell that shell commands in the config (e.g., `run_cmd` and
+//! `prepare`) are run with: `sh` on Unix, and `cmd` on Windows, unless
+//! configured with `shell`
+
+use serde::{Deserialize, Serialize};
+
+/// A shell, either by name (e.g., "bash" or "pwsh"), or as the argv
+/// that a command is appended to (e.g., `["bash", "-euo", "pipefail",
+/// "-c"]`)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Shell {
+    Named(String),
+    Argv(Vec<String>),
+}
+
+impl Shell {
+    /// The argv that a command is appended to
+    fn argv(&self) -> Vec<String> {
+        match self {
+            Shell::Named(name) => {
+                let flags: &[&str] = match name.as_str() {
+                    "cmd" => &["/C"],
+                    "pwsh" | "powershell" => &["-NoProfile", "-Command"],
+                    _ => &["-c"],
+                };
+                std::iter::once(name.as_str())
+                    .chain(flags.iter().copied())
+                    .map(str::to_string)
+                    .collect()
+            }
+            Shell::Argv(argv) => argv.clone(),
+        }
+    }
+
+    /// Whether the shell has a program to run
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Shell::Named(name) => name.is_empty(),
+            Shell::Argv(argv) => argv.first().is_none_or(String::is_empty),
+        }
+    }
+}
+
+impl std::fmt::Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.argv().join(" "))
+    }
+}
+
+/// The shell used if none is configured
+fn default_shell() -> Shell {
+    if cfg!(windows) {
+        Shell::Named("cmd".to_string())
+    } else {
+        Shell::Named("sh".to_string())
+    }
+}
+
+/// A command that runs the shell command `cmd` with `shell`, or the
+/// default shell if `None`
+pub fn command(shell: Option<&Shell>, cmd: &str) -> std::process::Command {
+    let argv = shell.cloned().unwrap_or_else(default_shell).argv();
+    let (program, args) = argv
+        .split_first()
+        .map_or(("", &[][..]), |(p, a)| (p.as_str(), a));
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+    append_command(&mut command, program, cmd);
+    command
+}
+
+#[cfg(not(windows))]
+fn append_command(command: &mut std::process::Command, _shell: &str, cmd: &str) {
+    command.arg(cmd);
+}
+
+#[cfg(windows)]
+fn append_command(command: &mut std::process::Command, shell: &str, cmd: &str) {
+    use std::os::windows::process::CommandExt;
+    if shell.eq_ignore_ascii_case("cmd") {
+        // Passed on as is, since `cmd` does not parse its command line
+        // the way that arguments are usually quoted for
+        command.raw_arg(cmd);
+    } else {
+        command.arg(cmd);
+    }
+}
@@ -0,0 +1,107 @@
+//! Checks of the settings of the machine that make timings less
+//! reproducible (frequency scaling, turbo boost, SMT, ASLR and
+//! swapping), run before benchmarking with `--check-system`, and
+//! recorded in the metadata of the results. Only Linux exposes them;
+//! elsewhere, nothing is found.
+
+use serde::{Deserialize, Serialize};
+
+/// The settings that were found, and what about them hurts
+/// reproducibility
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+pub struct SystemChecks {
+    /// Frequency governors of the CPUs (e.g., "performance"), each once
+    #[serde(default)]
+    pub cpu_governors: Vec<String>,
+    pub turbo_boost: Option<bool>,
+    /// Whether simultaneous multithreading (hyper-threading) is active
+    pub smt: Option<bool>,
+    /// Whether the address space layout of processes is randomized
+    pub aslr: Option<bool>,
+    pub swappiness: Option<u32>,
+    /// Problems with the settings, with how to fix them
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// Above this, the kernel swaps out pages of the runs readily enough to
+/// slow them down unpredictably
+const MAX_SWAPPINESS: u32 = 10;
+
+fn read(path: &str) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn cpu_governors() -> Vec<String> {
+    let mut governors: Vec<String> =
+        glob::glob("/sys/devices/system/cpu/cpu*/cpufreq/scaling_governor")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|path| read(path.to_str()?))
+            .collect();
+    governors.sort();
+    governors.dedup();
+    governors
+}
+
+fn turbo_boost() -> Option<bool> {
+    // intel_pstate has its own knob, inverted; other drivers have boost
+    read("/sys/devices/system/cpu/intel_pstate/no_turbo")
+        .map(|no_turbo| no_turbo == "0")
+        .or_else(|| read("/sys/devices/system/cpu/cpufreq/boost").map(|boost| boost == "1"))
+}
+
+/// Inspects the settings of this machine
+pub fn check() -> SystemChecks {
+    let mut checks = SystemChecks {
+        cpu_governors: cpu_governors(),
+        turbo_boost: turbo_boost(),
+        smt: read("/sys/devices/system/cpu/smt/active").map(|active| active == "1"),
+        aslr: read("/proc/sys/kernel/randomize_va_space").map(|level| level != "0"),
+        swappiness: read("/proc/sys/vm/swappiness").and_then(|s| s.parse().ok()),
+        warnings: vec![],
+    };
+    let mut warnings = vec![];
+    if checks.cpu_governors.iter().any(|g| g != "performance") {
+        warnings.push(format!(
+            "CPU frequency governor is {}, rather than performance, so the clock speed varies \
+             with load. Fix with `sudo cpupower frequency-set -g performance`.",
+            checks.cpu_governors.join(", ")
+        ));
+    }
+    if checks.turbo_boost == Some(true) {
+        warnings.push(
+            "Turbo boost is on, so the clock speed varies with temperature. Fix with `echo 1 | \
+             sudo tee /sys/devices/system/cpu/intel_pstate/no_turbo` (or `echo 0 | sudo tee \
+             /sys/devices/system/cpu/cpufreq/boost`)."
+                .to_string(),
+        );
+    }
+    if checks.smt == Some(true) {
+        warnings.push(
+            "SMT (hyper-threading) is on, so runs share cores with whatever runs on their \
+             siblings. Fix with `echo off | sudo tee /sys/devices/system/cpu/smt/control`."
+                .to_string(),
+        );
+    }
+    if checks.aslr == Some(true) {
+        warnings.push(
+            "ASLR is on, so the memory layout (and with it, cache behavior) varies from run to \
+             run. Fix with `echo 0 | sudo tee /proc/sys/kernel/randomize_va_space`."
+                .to_string(),
+        );
+    }
+    if checks.swappiness.is_some_and(|s| s > MAX_SWAPPINESS) {
+        warnings.push(format!(
+            "Swappiness is {}, so memory of runs may be swapped out. Fix with `sudo sysctl \
+             vm.swappiness={}`.",
+            checks.swappiness.unwrap(),
+            MAX_SWAPPINESS
+        ));
+    }
+    checks.warnings = warnings;
+    checks
+}
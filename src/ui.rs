@@ -0,0 +1,300 @@
+//! Appearance of the progress display, as configured in `[ui]`
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+
+const BAR_TEMPLATE: &str =
+    "{spinner:.green} {msg} [{wide_bar:.cyan/blue}] {pos}/{len} ({elapsed} -- ETA {eta})";
+const SPINNER_TEMPLATE: &str = "{spinner:.green} {msg} ({elapsed_precise})";
+const MESSAGE_TEMPLATE: &str = "{spinner:.green} {msg}";
+const PROGRESS_CHARS: &str = "#>-";
+
+// Uncolored, for dumb terminals and log files
+const ASCII_BAR_TEMPLATE: &str =
+    "{spinner} {msg} [{wide_bar}] {pos}/{len} ({elapsed} -- ETA {eta})";
+const ASCII_SPINNER_TEMPLATE: &str = "{spinner} {msg} ({elapsed_precise})";
+const ASCII_MESSAGE_TEMPLATE: &str = "{spinner} {msg}";
+// The last character is shown once finished
+const ASCII_TICK_CHARS: &str = "|/-\\ ";
+
+/// Container that new bars and spinners are drawn through, while those
+/// of concurrent (test, tool) pairs are shown
+static SHARED: Mutex<Option<Arc<MultiProgress>>> = Mutex::new(None);
+
+/// `pb`, drawn through the shared container, if there is one
+fn shared(pb: ProgressBar) -> ProgressBar {
+    match &*SHARED.lock().unwrap() {
+        Some(mpb) => mpb.add(pb),
+        None => pb,
+    }
+}
+
+/// How progress is shown
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressMode {
+    /// Bars on a terminal, and log lines otherwise
+    #[default]
+    Auto,
+    /// Animated progress bars
+    Bars,
+    /// A timestamped line as each phase starts and finishes
+    Log,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct UiConfig {
+    progress: Option<ProgressMode>,
+    /// Template of the bars shown while a (test, tool) pair is run
+    bar_template: Option<String>,
+    /// Template of the spinners shown during preparation and cleanup
+    spinner_template: Option<String>,
+    /// Template of the line left behind once a (test, tool) pair is done
+    message_template: Option<String>,
+    /// Characters for the filled, current and empty parts of bars
+    progress_chars: Option<String>,
+    /// Characters cycled through by spinners, the last one being shown
+    /// once done
+    tick_chars: Option<String>,
+    /// Milliseconds between redraws of spinners and bars, even when
+    /// there is no progress to report
+    tick_rate_ms: Option<u64>,
+    /// Whether to use only uncolored ASCII in the defaults
+    ascii: Option<bool>,
+}
+
+impl UiConfig {
+    fn ascii(&self) -> bool {
+        self.ascii.unwrap_or_default()
+    }
+
+    /// Why progress could not be shown as configured, if it could not
+    pub fn problem(&self) -> Option<String> {
+        // indicatif needs a character for the done state of spinners
+        // and the filled end of bars, besides at least one other
+        for (name, chars) in [
+            ("tick_chars", &self.tick_chars),
+            ("progress_chars", &self.progress_chars),
+        ] {
+            if chars.as_ref().is_some_and(|c| c.chars().count() < 2) {
+                return Some(format!("{} should have at least 2 characters", name));
+            }
+        }
+        None
+    }
+
+    fn style(
+        &self,
+        base: ProgressStyle,
+        template: &Option<String>,
+        default: &str,
+    ) -> ProgressStyle {
+        let style = base.template(template.as_deref().unwrap_or(default));
+        match (&self.tick_chars, self.ascii()) {
+            (Some(tick_chars), _) => style.tick_chars(tick_chars),
+            (None, true) => style.tick_chars(ASCII_TICK_CHARS),
+            (None, false) => style,
+        }
+    }
+
+    pub fn bar_style(&self) -> ProgressStyle {
+        let default = if self.ascii() {
+            ASCII_BAR_TEMPLATE
+        } else {
+            BAR_TEMPLATE
+        };
+        self.style(ProgressStyle::default_bar(), &self.bar_template, default)
+            .progress_chars(self.progress_chars.as_deref().unwrap_or(PROGRESS_CHARS))
+    }
+
+    pub fn spinner_style(&self) -> ProgressStyle {
+        let default = if self.ascii() {
+            ASCII_SPINNER_TEMPLATE
+        } else {
+            SPINNER_TEMPLATE
+        };
+        self.style(
+            ProgressStyle::default_spinner(),
+            &self.spinner_template,
+            default,
+        )
+    }
+
+    pub fn message_style(&self) -> ProgressStyle {
+        let default = if self.ascii() {
+            ASCII_MESSAGE_TEMPLATE
+        } else {
+            MESSAGE_TEMPLATE
+        };
+        self.style(
+            ProgressStyle::default_bar(),
+            &self.message_template,
+            default,
+        )
+    }
+
+    /// Makes `pb` redraw itself at the configured tick rate, if any
+    pub fn start_ticking(&self, pb: &ProgressBar) {
+        if let Some(ms) = self.tick_rate_ms {
+            pb.enable_steady_tick(ms);
+        }
+    }
+
+    /// Whether progress is logged line by line, rather than shown with
+    /// progress bars
+    pub fn logs_progress(&self) -> bool {
+        match self.progress.unwrap_or_default() {
+            ProgressMode::Auto => !std::io::stderr().is_terminal(),
+            ProgressMode::Bars => false,
+            ProgressMode::Log => true,
+        }
+    }
+
+    /// A progress bar of length `len`, hidden if progress is logged
+    pub fn bar(&self, len: u64) -> ProgressBar {
+        let pb = if self.logs_progress() {
+            let pb = ProgressBar::hidden();
+            pb.set_length(len);
+            pb
+        } else {
+            shared(ProgressBar::new(len))
+        };
+        pb.set_style(self.bar_style());
+        self.start_ticking(&pb);
+        pb
+    }
+
+    /// A spinner, hidden if progress is logged
+    pub fn spinner(&self) -> ProgressBar {
+        if self.logs_progress() {
+            ProgressBar::hidden()
+        } else {
+            shared(ProgressBar::new_spinner())
+        }
+    }
+
+    /// Container for concurrent progress bars, hidden if progress is
+    /// logged
+    pub fn multi(&self) -> MultiProgress {
+        let mpb = MultiProgress::new();
+        if self.logs_progress() {
+            mpb.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        mpb
+    }
+
+    /// Draws the bars and spinners made from now on through a single
+    /// container, until the returned guard is dropped, so that those of
+    /// concurrent (test, tool) pairs do not overwrite each other
+    pub fn share_bars(&self) -> SharedBars {
+        if self.logs_progress() {
+            return SharedBars { drawing: None };
+        }
+        let mpb = Arc::new(MultiProgress::new());
+        // Never drawn, but keeps the container from being done while no
+        // other bar is shown
+        let keeper = mpb.add(ProgressBar::new(0));
+        *SHARED.lock().unwrap() = Some(mpb.clone());
+        let drawer = std::thread::spawn(move || mpb.join());
+        SharedBars {
+            drawing: Some((keeper, drawer)),
+        }
+    }
+
+    /// Prints `line` with a timestamp, if progress is logged
+    pub fn log(&self, line: &str) {
+        if self.logs_progress() {
+            eprintln!("[{}] {}", timestamp(), line);
+        }
+    }
+
+    /// Starts the phase of work `what`, logging it, and logging again
+    /// once it is over
+    pub fn phase(&self, what: String) -> Phase<'_> {
+        self.log(&format!("{} started", what));
+        Phase {
+            ui: self,
+            what,
+            started: std::time::Instant::now(),
+            finished: false,
+        }
+    }
+}
+
+/// Bars and spinners being drawn through a shared container. Every bar
+/// made meanwhile must be finished, or dropped, before this is.
+pub struct SharedBars {
+    drawing: Option<(ProgressBar, std::thread::JoinHandle<std::io::Result<()>>)>,
+}
+
+impl Drop for SharedBars {
+    fn drop(&mut self) {
+        if let Some((keeper, drawer)) = self.drawing.take() {
+            *SHARED.lock().unwrap() = None;
+            keeper.finish_and_clear();
+            let _ = drawer.join();
+        }
+    }
+}
+
+/// A phase of work, such as the warmup runs of a (test, tool) pair. If
+/// dropped without being finished, it is logged as having stopped.
+pub struct Phase<'a> {
+    ui: &'a UiConfig,
+    what: String,
+    started: std::time::Instant,
+    finished: bool,
+}
+
+impl Phase<'_> {
+    pub fn finish(mut self) {
+        self.finished = true;
+        self.ui.log(&format!(
+            "{} finished in {:.2?}",
+            self.what,
+            self.started.elapsed()
+        ));
+    }
+}
+
+impl Drop for Phase<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.ui.log(&format!(
+                "{} stopped after {:.2?}",
+                self.what,
+                self.started.elapsed()
+            ));
+        }
+    }
+}
+
+/// The current time in UTC, as in RFC 3339 (e.g., `2021-03-04T05:06:07Z`)
+fn timestamp() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = ((secs / 86400) as i64, secs % 86400);
+    // Howard Hinnant's `civil_from_days`
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
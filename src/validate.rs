@@ -0,0 +1,43 @@
+//! Validation of the output of each tool on a test, so that a tool that
+//! is fast because it produced the wrong answer is failed rather than
+//! reported as a win. The output of each run is saved into the cell's
+//! directory, and once all the cells have run, checked against that of
+//! the main tool, or with a command of the test's.
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the file that the output of a cell's runs is saved to, in
+/// the cell's directory
+pub const OUTPUT: &str = "output";
+
+/// `validate` of a test: either `true`, to compare the stdout of each
+/// tool against that of the main tool, or how to validate
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Validate {
+    Enabled(bool),
+    With(ValidateConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ValidateConfig {
+    /// File that the runs write their output to, which is validated
+    /// rather than their stdout
+    pub output: Option<String>,
+    /// Shell command that succeeds if the output at `{OUTPUT}` is
+    /// correct (with the main tool's at `{EXPECTED}`), rather than
+    /// comparing it against the main tool's
+    pub cmd: Option<String>,
+}
+
+impl Validate {
+    /// How to validate, if at all
+    pub fn config(&self) -> Option<ValidateConfig> {
+        match self {
+            Validate::Enabled(false) => None,
+            Validate::Enabled(true) => Some(ValidateConfig::default()),
+            Validate::With(config) => Some(config.clone()),
+        }
+    }
+}
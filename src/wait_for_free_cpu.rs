@@ -2,14 +2,19 @@ use lazy_static::lazy_static;
 use std::sync::Mutex;
 
 pub struct WaitForFreeCPU {
-    num_cpus: usize,
-    num_blocked: usize,
+    // The CPUs that may be handed out, whether or not they currently are
+    cpus: Vec<usize>,
+    // The CPUs that are currently handed out
+    blocked: Vec<usize>,
+    // How many CPUs may be handed out at once
+    limit: usize,
 }
 
 lazy_static! {
     static ref WAIT_FOR_FREE_CPU: Mutex<WaitForFreeCPU> = Mutex::new(WaitForFreeCPU {
-        num_cpus: num_cpus::get(),
-        num_blocked: 0,
+        cpus: crate::affinity::usable_cpus(),
+        blocked: vec![],
+        limit: usize::MAX,
     });
 }
 
@@ -18,13 +23,27 @@ lazy_static! {
 /// should be sufficient to prevent spinning up too many CPU-heavy
 /// processes in one go.
 pub fn and_run<T>(f: impl FnOnce() -> T) -> T {
+    and_run_on_cpu(|_| f())
+}
+
+/// Like `and_run`, but also tells `f` which CPU it has been given. No
+/// two concurrent calls are given the same CPU, so processes pinned to
+/// their CPU never share a core with each other.
+pub fn and_run_on_cpu<T>(f: impl FnOnce(usize) -> T) -> T {
     loop {
         let mut w = WAIT_FOR_FREE_CPU.lock().unwrap();
-        if w.num_blocked < w.num_cpus {
-            w.num_blocked += 1;
+        let free = (w.blocked.len() < w.limit)
+            .then(|| w.cpus.iter().find(|c| !w.blocked.contains(c)).copied())
+            .flatten();
+        if let Some(cpu) = free {
+            w.blocked.push(cpu);
             drop(w);
-            let res = f();
-            WAIT_FOR_FREE_CPU.lock().unwrap().num_blocked -= 1;
+            let res = f(cpu);
+            WAIT_FOR_FREE_CPU
+                .lock()
+                .unwrap()
+                .blocked
+                .retain(|c| *c != cpu);
             return res;
         } else {
             drop(w);
@@ -36,20 +55,31 @@ pub fn and_run<T>(f: impl FnOnce() -> T) -> T {
 /// Set the max limit for number of "free CPUs" available. Will
 /// automatically clamp to the total number of CPUs known to exist on
 /// the system. Exists only to make the "free CPUs" estimate more
-/// conservative.
+/// conservative. CPUs that are already handed out are kept until they
+/// are given back, so that the limit takes effect as they are.
 pub fn restrict_free_cpus_to(n: usize) {
     let mut w = WAIT_FOR_FREE_CPU.lock().unwrap();
 
-    // We can't ever let it go below the number that are currently
-    // blocked, otherwise the expected invariant used by `and_run`
-    // goes bad.
-    let n = n.max(w.num_blocked);
-
     // We don't let it ever go above the number of CPUs known to exist
     // on the system. This is what we guarantee by contract of this
     // function.
-    let n = n.min(num_cpus::get());
+    w.limit = n.min(crate::affinity::usable_cpus().len());
+}
+
+/// Takes a free CPU out of those handed out, until it is given back
+/// with [`unreserve_cpu`], so that work of benchify's own can run on it
+/// without competing with what it hands CPUs out to. Never takes the
+/// last CPU.
+pub fn reserve_cpu() -> Option<usize> {
+    let mut w = WAIT_FOR_FREE_CPU.lock().unwrap();
+    if w.cpus.len() < 2 {
+        return None;
+    }
+    let i = w.cpus.iter().rposition(|c| !w.blocked.contains(c))?;
+    Some(w.cpus.remove(i))
+}
 
-    // Set the value
-    w.num_cpus = n;
+/// Gives back a CPU taken with [`reserve_cpu`]
+pub fn unreserve_cpu(cpu: usize) {
+    WAIT_FOR_FREE_CPU.lock().unwrap().cpus.push(cpu);
 }
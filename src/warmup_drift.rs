@@ -0,0 +1,95 @@
+//! Detection of warm-up drift: measured runs that keep getting faster,
+//! because the warmup runs were too few for the tool to reach a steady
+//! state (e.g., JIT compilation or caches still warming up)
+
+/// Mann-Kendall z-scores below this are significant decreasing trends
+/// (one-sided, p < 0.01)
+const Z_THRESHOLD: f64 = -2.33;
+
+/// Fewest runs that a trend is looked for in
+const MIN_RUNS: usize = 10;
+
+/// Mann-Kendall z-score of the trend of `timings` over time, negative
+/// if they tend to decrease. Ties are not corrected for.
+fn mann_kendall_z(timings: &[std::time::Duration]) -> f64 {
+    let n = timings.len();
+    let s: i64 = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .map(|(i, j)| match timings[j].cmp(&timings[i]) {
+            std::cmp::Ordering::Greater => 1,
+            std::cmp::Ordering::Less => -1,
+            std::cmp::Ordering::Equal => 0,
+        })
+        .sum();
+    let n = n as f64;
+    let variance = n * (n - 1.) * (2. * n + 5.) / 18.;
+    // With a continuity correction
+    (s - s.signum()) as f64 / variance.sqrt()
+}
+
+/// Number of runs at the start of `timings` (the measured runs of a
+/// cell, in the order they were made) that drift towards the later ones,
+/// if they do so significantly. If the drift never settles, this is all
+/// of them.
+pub fn drifting_runs(timings: &[std::time::Duration]) -> Option<usize> {
+    if timings.len() < MIN_RUNS || mann_kendall_z(timings) >= Z_THRESHOLD {
+        return None;
+    }
+    // Dropping ever more of the early runs, in steps of a tenth, until
+    // what is left has no trend
+    let step = (timings.len() / 10).max(1);
+    Some(
+        (step..=timings.len() - MIN_RUNS)
+            .step_by(step)
+            .find(|&k| mann_kendall_z(&timings[k..]) >= Z_THRESHOLD)
+            .unwrap_or(timings.len()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn ms(timings: impl IntoIterator<Item = u64>) -> Vec<Duration> {
+        timings.into_iter().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn monotone_series() {
+        // S = -45, with a variance of 125
+        let decreasing = ms((0..10).map(|i| 200 - 10 * i));
+        assert!((mann_kendall_z(&decreasing) + 44. / 125f64.sqrt()).abs() < 1e-9);
+        let increasing = ms((0..10).map(|i| 100 + 10 * i));
+        assert!((mann_kendall_z(&increasing) - 44. / 125f64.sqrt()).abs() < 1e-9);
+        assert_eq!(drifting_runs(&increasing), None);
+    }
+
+    #[test]
+    fn flat_series() {
+        let flat = ms([100; 30]);
+        assert_eq!(mann_kendall_z(&flat), 0.);
+        assert_eq!(drifting_runs(&flat), None);
+    }
+
+    #[test]
+    fn too_few_runs() {
+        assert_eq!(drifting_runs(&ms((0..9).map(|i| 200 - 10 * i))), None);
+    }
+
+    #[test]
+    fn exactly_min_runs() {
+        // No run can be dropped while leaving enough to look for a trend
+        // in, so the drift cannot be shown to settle
+        let decreasing = ms((0..10).map(|i| 200 - 10 * i));
+        assert_eq!(drifting_runs(&decreasing), Some(10));
+    }
+
+    #[test]
+    fn drift_that_settles() {
+        // Dropping 3 runs leaves a trend (z = -3.34), but dropping 6 does
+        // not (z = -2.11)
+        let settling = ms((0..10).map(|i| 200 - 10 * i).chain([100; 20]));
+        assert_eq!(drifting_runs(&settling), Some(6));
+    }
+}
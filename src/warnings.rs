@@ -0,0 +1,19 @@
+//! Warnings that cast doubt on the results (e.g., a noisy environment),
+//! which are recorded so that `--strict` can turn them into an error once
+//! the results are saved
+
+use std::sync::Mutex;
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Prints `warning` prominently, and records it
+pub fn warn(warning: impl Into<String>) {
+    let warning = warning.into();
+    eprintln!("WARNING: {}\n", warning);
+    WARNINGS.lock().unwrap().push(warning);
+}
+
+/// How many warnings were printed so far
+pub fn count() -> usize {
+    WARNINGS.lock().unwrap().len()
+}
@@ -0,0 +1,85 @@
+//! Tools that are WebAssembly modules, run by a Wasm runtime. Modules
+//! are compiled ahead of time, once per tool, so that timed runs measure
+//! instantiating and executing the module rather than the engine
+//! compiling it.
+
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A runtime that runs Wasm modules, whose command-line is then the
+/// tool's program
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Runtime {
+    Wasmtime,
+}
+
+impl std::fmt::Display for Runtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Runtime::Wasmtime => write!(f, "wasmtime"),
+        }
+    }
+}
+
+/// Compiles `module` ahead of time, with the runtime's `program`, into
+/// the scratch directory. The arguments of `program` (e.g., `-O` or `-W`
+/// flags) are passed here too, since the runtime only accepts modules
+/// compiled with the same settings it runs them with.
+pub fn precompile(
+    runtime: Runtime,
+    program: &crate::Program,
+    module: &str,
+    tool_id: &str,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(&*crate::SCRATCH_DIR)?;
+    let compiled = crate::SCRATCH_DIR.join(format!("{}.cwasm", tool_id));
+    let output = match runtime {
+        Runtime::Wasmtime => std::process::Command::new(program.executable())
+            .arg("compile")
+            .args(program.args())
+            .arg(module)
+            .arg("-o")
+            .arg(&compiled)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .map_err(|e| eyre!("Could not run {} ({}): {}", runtime, program, e))?,
+    };
+    if !output.status.success() {
+        return Err(eyre!(
+            "Could not compile {} with {}: {}",
+            module,
+            runtime,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(compiled)
+}
+
+/// The command that runs `module` (compiled ahead of time, if
+/// `precompiled`), calling the exported function `invoke` if given
+/// (rather than the WASI entry point). The run arguments are to be added
+/// to it.
+pub fn command(
+    runtime: Runtime,
+    program: &crate::Program,
+    module: &Path,
+    precompiled: bool,
+    invoke: Option<&str>,
+) -> std::process::Command {
+    let mut command = std::process::Command::new(program.executable());
+    match runtime {
+        Runtime::Wasmtime => {
+            command.arg("run").args(program.args());
+            if let Some(invoke) = invoke {
+                command.arg("--invoke").arg(invoke);
+            }
+            if precompiled {
+                command.arg("--allow-precompiled");
+            }
+            command.arg(module);
+        }
+    }
+    command
+}
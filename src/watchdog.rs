@@ -0,0 +1,106 @@
+//! Killing of runs that take too long
+
+use std::sync::{Arc, Condvar, Mutex};
+
+#[derive(Default)]
+struct State {
+    exited: bool,
+    timed_out: bool,
+}
+
+/// Terminates the process group of a run if it has not exited within a
+/// timeout, and kills it if it has not exited within a grace period
+/// after that. The run must be the leader of its own process group, so
+/// that anything it started is terminated along with it.
+pub struct Watchdog {
+    state: Arc<(Mutex<State>, Condvar)>,
+    handle: std::thread::JoinHandle<()>,
+}
+
+impl Watchdog {
+    pub fn start(
+        pid: u32,
+        timeout: std::time::Duration,
+        grace_period: std::time::Duration,
+    ) -> Self {
+        let state = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        let handle = {
+            let state = state.clone();
+            std::thread::spawn(move || {
+                let (lock, cvar) = &*state;
+                let (mut state, _) = cvar
+                    .wait_timeout_while(lock.lock().unwrap(), timeout, |s| !s.exited)
+                    .unwrap();
+                // The lock is held while signalling, so that the process
+                // cannot be reaped (and its PID reused) in the meantime
+                if state.exited {
+                    return;
+                }
+                state.timed_out = true;
+                terminate_group(pid);
+                let (state, _) = cvar
+                    .wait_timeout_while(state, grace_period, |s| !s.exited)
+                    .unwrap();
+                if !state.exited {
+                    kill_group(pid);
+                }
+            })
+        };
+        Watchdog { state, handle }
+    }
+
+    /// Stops watching, producing whether the run timed out. Must be
+    /// called once the run has exited, but before it has been reaped.
+    pub fn disarm(self) -> bool {
+        let (lock, cvar) = &*self.state;
+        lock.lock().unwrap().exited = true;
+        cvar.notify_one();
+        self.handle.join().unwrap();
+        let timed_out = lock.lock().unwrap().timed_out;
+        timed_out
+    }
+}
+
+#[cfg(unix)]
+fn terminate_group(pid: u32) {
+    // SAFETY: plain syscall
+    unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGTERM) };
+}
+
+#[cfg(unix)]
+fn kill_group(pid: u32) {
+    // SAFETY: plain syscall
+    unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) };
+}
+
+/// Asks the process `pid`, and the processes it started, to stop
+#[cfg(windows)]
+fn terminate_group(pid: u32) {
+    taskkill(pid, false);
+}
+
+#[cfg(windows)]
+fn kill_group(pid: u32) {
+    taskkill(pid, true);
+}
+
+/// Stops the process tree of `pid` with `taskkill`, forcefully if
+/// `force`
+#[cfg(windows)]
+fn taskkill(pid: u32, force: bool) {
+    let mut command = std::process::Command::new("taskkill");
+    command.arg("/T").arg("/PID").arg(pid.to_string());
+    if force {
+        command.arg("/F");
+    }
+    let _ = command
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+#[cfg(not(any(unix, windows)))]
+fn terminate_group(_pid: u32) {}
+
+#[cfg(not(any(unix, windows)))]
+fn kill_group(_pid: u32) {}